@@ -0,0 +1,71 @@
+//! How long the live cells have been alive, for coloring them by age
+
+use std::{collections::HashMap, mem};
+
+use crate::engine::Engine;
+
+/// Generations each live cell has been alive for, counting the one it was born in. Kept apart
+/// from the engines, whose cells are alive or not no matter how old they are.
+pub struct Ages {
+    ages: HashMap<(i64, i64), u16>,
+    /// The ages of the previous generation, kept to reuse the allocation
+    previous: HashMap<(i64, i64), u16>,
+}
+
+impl Ages {
+    /// Starts out with all the live cells newborn
+    pub fn new(engine: &dyn Engine) -> Self {
+        let mut ages = Self {
+            ages: HashMap::new(),
+            previous: HashMap::new(),
+        };
+        ages.reset(engine);
+        ages
+    }
+
+    /// Forgets the ages of the cells, making all the live ones newborn
+    pub fn reset(&mut self, engine: &dyn Engine) {
+        self.ages.clear();
+        self.ages
+            .extend(engine.live_cells().into_iter().map(|cell| (cell, 1)));
+    }
+
+    /// Ages the cells that survived into the generation the engine is at, and starts the ones
+    /// born in it at 1. Dead cells are dropped.
+    pub fn update(&mut self, engine: &dyn Engine) {
+        mem::swap(&mut self.ages, &mut self.previous);
+        self.ages.clear();
+        for cell in engine.live_cells() {
+            let age = self
+                .previous
+                .get(&cell)
+                .map_or(1, |age| age.saturating_add(1));
+            self.ages.insert(cell, age);
+        }
+    }
+
+    /// Takes the ages of the live cells as they were, for picking up a simulation that was
+    /// left off
+    pub(crate) fn restore(ages: impl IntoIterator<Item = ((i64, i64), u16)>) -> Self {
+        Self {
+            ages: ages.into_iter().collect(),
+            previous: HashMap::new(),
+        }
+    }
+
+    /// Moves the ages along with the cells, which moved by `dx` and `dy`
+    pub(crate) fn shift(&mut self, dx: i64, dy: i64) {
+        let ages = mem::take(&mut self.ages).into_iter();
+        self.ages = ages.map(|((x, y), age)| ((x + dx, y + dy), age)).collect();
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = ((i64, i64), u16)> + '_ {
+        self.ages.iter().map(|(&cell, &age)| (cell, age))
+    }
+
+    /// The age of a live cell, which is 1 for cells brought to life by hand since the last
+    /// generation
+    pub fn get(&self, x: i64, y: i64) -> u16 {
+        self.ages.get(&(x, y)).copied().unwrap_or(1)
+    }
+}
@@ -0,0 +1,101 @@
+//! Measuring how fast the generations are computed, without showing them
+
+use std::{
+    num::NonZeroUsize,
+    time::{Duration, Instant},
+};
+
+use clap::Args;
+
+use game_of_life::{engine::EngineKind, symmetry::Symmetry, Field, Life, Rule};
+
+use crate::parse_probability;
+
+/// Compute the generations of a random soup as fast as possible and report how fast that was
+#[derive(Args)]
+pub struct Bench {
+    /// Width of the field
+    #[arg(long, default_value_t = 256, value_parser = crate::parse_size)]
+    width: usize,
+    /// Height of the field
+    #[arg(long, default_value_t = 256, value_parser = crate::parse_size)]
+    height: usize,
+    /// Seed of the random soup
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+    /// Fraction of live cells in the random soup
+    #[arg(long, default_value_t = 0.25, value_parser = parse_probability)]
+    density: f64,
+    /// Rule in B/S notation
+    #[arg(long, default_value_t = Rule::default())]
+    rule: Rule,
+    /// How the generations are computed
+    #[arg(long, value_enum, default_value_t = EngineKind::Naive)]
+    engine: EngineKind,
+    /// Number of threads computing the generations of a bounded field
+    #[arg(long, default_value_t = NonZeroUsize::MIN)]
+    threads: NonZeroUsize,
+    /// Simulate an infinite plane, of which the field is the part the soup starts on
+    #[arg(long)]
+    unbounded: bool,
+    /// Generations to compute before measuring, so that the caches are warm and the soup is
+    /// past its first burst of activity
+    #[arg(long, default_value_t = 0)]
+    warmup: u64,
+    /// Generations to measure
+    #[arg(long, conflicts_with = "duration")]
+    generations: Option<u64>,
+    /// Seconds to measure for, unless measuring a number of --generations
+    #[arg(long, default_value_t = 5.0)]
+    duration: f64,
+}
+
+impl Bench {
+    /// Runs the benchmark, returning its report
+    pub fn run(&self) -> Result<String, String> {
+        let field = Field::random(
+            self.seed,
+            self.width,
+            self.height,
+            self.density,
+            Symmetry::None,
+        )?;
+        let mut life = Life::new(
+            field,
+            self.rule,
+            self.unbounded,
+            self.engine,
+            self.threads.get(),
+        )?;
+        life.fast_forward(self.warmup);
+
+        let duration = Duration::try_from_secs_f64(self.duration)
+            .map_err(|_| format!("{} is not a duration in seconds", self.duration))?;
+        let started = Instant::now();
+        let mut generations = 0;
+        match self.generations {
+            // All at once, as engines like HashLife skip the ones in between
+            Some(limit) => {
+                life.fast_forward(limit);
+                generations = limit;
+            }
+            None => {
+                while started.elapsed() < duration {
+                    life.step();
+                    generations += 1;
+                }
+            }
+        }
+        let seconds = started.elapsed().as_secs_f64();
+
+        let cells = self.width as f64 * self.height as f64;
+        let rate = generations as f64 / seconds;
+        Ok(format!(
+            "{generations} generations of {}x{} cells in {seconds:.3}s: {rate:.1} generations \
+             and {:.1}M cell updates per second",
+            self.width,
+            self.height,
+            rate * cells / 1e6
+        ))
+    }
+}
@@ -0,0 +1,78 @@
+//! What lies beyond the edges of the field
+
+use clap::ValueEnum;
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum Boundary {
+    /// Opposite edges are joined, making the field a torus
+    #[default]
+    Wrap,
+    /// Everything outside the field is permanently dead
+    Dead,
+    /// The field is reflected at its edges, so edge cells see themselves as neighbors
+    Mirror,
+}
+
+/// How the edges of a wrapping field are glued together
+#[derive(Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum Topology {
+    /// Plain wrapping
+    #[default]
+    Torus,
+    /// Klein bottle: leaving through the left or right edge also mirrors the row
+    KleinH,
+    /// Klein bottle: leaving through the top or bottom edge also mirrors the column
+    KleinV,
+    /// Both pairs of edges are glued with a twist. Cells near a corner see their diagonal
+    /// neighbors across both seams with both mirrorings applied.
+    CrossSurface,
+}
+
+impl Boundary {
+    /// Maps possibly out of range coordinates to the cell they refer to, if any
+    pub fn resolve(
+        self,
+        topology: Topology,
+        x: i64,
+        y: i64,
+        width: usize,
+        height: usize,
+    ) -> Option<(usize, usize)> {
+        let (width, height) = (width as i64, height as i64);
+        match self {
+            Self::Wrap => Some(topology.wrap(x, y, width, height)),
+            Self::Dead if (0..width).contains(&x) && (0..height).contains(&y) => {
+                Some((x as usize, y as usize))
+            }
+            Self::Dead => None,
+            Self::Mirror => Some((reflect(x, width) as usize, reflect(y, height) as usize)),
+        }
+    }
+}
+
+impl Topology {
+    fn wrap(self, x: i64, y: i64, width: i64, height: i64) -> (usize, usize) {
+        // Every crossing of a twisted seam mirrors the other coordinate, and mirroring twice
+        // cancels out, so only the parity of the number of crossings matters
+        let crossed_x = x.div_euclid(width) % 2 != 0;
+        let crossed_y = y.div_euclid(height) % 2 != 0;
+        let (mut x, mut y) = (x.rem_euclid(width), y.rem_euclid(height));
+        if crossed_x && matches!(self, Self::KleinH | Self::CrossSurface) {
+            y = height - 1 - y;
+        }
+        if crossed_y && matches!(self, Self::KleinV | Self::CrossSurface) {
+            x = width - 1 - x;
+        }
+        (x as usize, y as usize)
+    }
+}
+
+/// Folds `coord` into `0..size` as if the range were mirrored over and over on both sides
+fn reflect(coord: i64, size: i64) -> i64 {
+    let coord = coord.rem_euclid(2 * size);
+    if coord < size {
+        coord
+    } else {
+        2 * size - 1 - coord
+    }
+}
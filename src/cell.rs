@@ -0,0 +1,55 @@
+//! What the cells of a field can hold
+
+use crate::rule::{OuterTotalistic, ALIVE, DEAD};
+
+/// The state of a single cell, as far as computing its next generation goes
+pub trait CellState: Copy + PartialEq {
+    fn dead() -> Self;
+
+    fn alive() -> Self;
+
+    /// Whether the cell counts as a live neighbor
+    fn is_alive(&self) -> bool;
+
+    /// The state of the cell in the next generation, given how many of its neighbors are
+    /// alive
+    fn next(self, rule: &OuterTotalistic, alive_neighbors: u8) -> Self;
+}
+
+/// Every state of the rule, dying ones included
+impl CellState for u8 {
+    fn dead() -> Self {
+        DEAD
+    }
+
+    fn alive() -> Self {
+        ALIVE
+    }
+
+    fn is_alive(&self) -> bool {
+        *self == ALIVE
+    }
+
+    fn next(self, rule: &OuterTotalistic, alive_neighbors: u8) -> Self {
+        rule.next(self, alive_neighbors)
+    }
+}
+
+/// Only alive or dead, so under rules with more states cells die as soon as they start dying
+impl CellState for bool {
+    fn dead() -> Self {
+        false
+    }
+
+    fn alive() -> Self {
+        true
+    }
+
+    fn is_alive(&self) -> bool {
+        *self
+    }
+
+    fn next(self, rule: &OuterTotalistic, alive_neighbors: u8) -> Self {
+        rule.next(if self { ALIVE } else { DEAD }, alive_neighbors) == ALIVE
+    }
+}
@@ -0,0 +1,165 @@
+//! Naming the objects a settled soup leaves behind, the way apgsearch takes a census of its ash
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+};
+
+use crate::{engine::EngineKind, Field, Life};
+
+/// A cell and the ones around it: cells this close can still meet, so they count as one
+type Cluster = Vec<(i64, i64)>;
+
+/// Objects by name, period and a phase of each shape they take, with its live cells as `#`
+const OBJECTS: &[(&str, u64, &[&[&str]])] = &[
+    ("block", 1, &[&["##", "##"]]),
+    ("beehive", 1, &[&[".##.", "#..#", ".##."]]),
+    ("loaf", 1, &[&[".##.", "#..#", ".#.#", "..#."]]),
+    ("boat", 1, &[&["##.", "#.#", ".#."]]),
+    ("tub", 1, &[&[".#.", "#.#", ".#."]]),
+    ("pond", 1, &[&[".##.", "#..#", "#..#", ".##."]]),
+    ("blinker", 2, &[&["###"]]),
+    (
+        "toad",
+        2,
+        &[&[".###", "###."], &["..#.", "#..#", "#..#", ".#.."]],
+    ),
+    (
+        "beacon",
+        2,
+        &[
+            &["##..", "##..", "..##", "..##"],
+            &["##..", "#...", "...#", "..##"],
+        ],
+    ),
+    (
+        "glider",
+        4,
+        &[&[".#.", "..#", "###"], &["#.#", ".##", ".#."]],
+    ),
+];
+
+/// How many of each object there are, in the order of the dictionary, along with the
+/// clusters of cells that are none of them
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Census {
+    pub objects: Vec<(&'static str, u64)>,
+    pub unidentified: u64,
+}
+
+impl Census {
+    /// The objects found at least once
+    pub fn found(&self) -> impl Iterator<Item = (&'static str, u64)> + '_ {
+        self.objects.iter().copied().filter(|&(_, count)| count > 0)
+    }
+}
+
+impl fmt::Display for Census {
+    /// On a line, like `2 block, 1 blinker, 3 unidentified`
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (name, count) in self.found() {
+            write!(f, "{count} {name}, ")?;
+        }
+        write!(f, "{} unidentified", self.unidentified)
+    }
+}
+
+/// Sorts the live cells of `life` into clusters and looks each up among the objects, whatever
+/// way it is turned or mirrored. A cluster only counts as an object if a copy of it on its own
+/// comes back to its shape after the period of the object under the rules of `life`.
+pub fn census(life: &Life) -> Census {
+    let mut shapes = HashMap::new();
+    for (object, &(_, _, phases)) in OBJECTS.iter().enumerate() {
+        for rows in phases {
+            let cells = rows.iter().zip(0..).flat_map(|(row, y)| {
+                row.chars()
+                    .zip(0..)
+                    .filter(|&(c, _)| c == '#')
+                    .map(move |(_, x)| (x, y))
+            });
+            shapes.insert(canonical(cells.collect()), object);
+        }
+    }
+
+    let mut census = Census {
+        objects: OBJECTS.iter().map(|&(name, ..)| (name, 0)).collect(),
+        unidentified: 0,
+    };
+    for cluster in clusters(life) {
+        let shape = canonical(cluster);
+        match shapes.get(&shape) {
+            Some(&object) if comes_back(&shape, OBJECTS[object].1, life) => {
+                census.objects[object].1 += 1
+            }
+            _ => census.unidentified += 1,
+        }
+    }
+    census
+}
+
+/// Splits the live cells into groups joined by cells at most two apart across or along,
+/// across the edges of a wrapping field too, where the cells of a group go on past the edge
+fn clusters(life: &Life) -> Vec<Cluster> {
+    let mut left: HashSet<_> = life.engine.live_cells().into_iter().collect();
+    let mut clusters = Vec::new();
+    while let Some(&start) = left.iter().next() {
+        left.remove(&start);
+        let mut cluster = vec![start];
+        let mut i = 0;
+        while let Some(&(x, y)) = cluster.get(i) {
+            for dy in -2..=2 {
+                for dx in -2..=2 {
+                    let wrapped = life.resolve(x + dx, y + dy);
+                    if wrapped.is_some_and(|cell| left.remove(&cell)) {
+                        cluster.push((x + dx, y + dy));
+                    }
+                }
+            }
+            i += 1;
+        }
+        clusters.push(cluster);
+    }
+    clusters
+}
+
+/// The same shape whichever of the 8 ways the cells are turned or mirrored, and wherever
+/// they are: the least of the sorted cells of all 8, moved to start at the origin
+fn canonical(cells: Cluster) -> Cluster {
+    (0..8)
+        .map(|symmetry| {
+            let mut image: Cluster = cells
+                .iter()
+                .map(|&(x, y)| {
+                    let (x, y) = if symmetry & 4 == 0 { (x, y) } else { (y, x) };
+                    let x = if symmetry & 1 == 0 { x } else { -x };
+                    let y = if symmetry & 2 == 0 { y } else { -y };
+                    (x, y)
+                })
+                .collect();
+            let left = image.iter().map(|&(x, _)| x).min().unwrap_or_default();
+            let top = image.iter().map(|&(_, y)| y).min().unwrap_or_default();
+            for (x, y) in &mut image {
+                (*x, *y) = (*x - left, *y - top);
+            }
+            image.sort_unstable();
+            image
+        })
+        .min()
+        .unwrap_or_default()
+}
+
+/// Whether the shape on its own on an unbounded plane takes the same shape again after
+/// `period` generations under the rules of `life`
+fn comes_back(shape: &Cluster, period: u64, life: &Life) -> bool {
+    let width = shape.iter().map(|&(x, _)| x + 1).max().unwrap_or_default();
+    let height = shape.iter().map(|&(_, y)| y + 1).max().unwrap_or_default();
+    let mut field = Field::new(width as usize, height as usize);
+    for &(x, y) in shape {
+        field.set(x as usize, y as usize, true).unwrap();
+    }
+    let Ok(mut copy) = Life::new(field, life.rules.clone(), true, EngineKind::Naive, 1) else {
+        return false;
+    };
+    copy.fast_forward(period);
+    canonical(copy.engine.live_cells()) == *shape
+}
@@ -0,0 +1,263 @@
+//! Characters that live and dead cells are drawn with
+
+use std::str::FromStr;
+
+use clap::ValueEnum;
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum Charset {
+    /// `*` and a space
+    #[default]
+    Ascii,
+    /// `█` and a space
+    Block,
+    /// `●` and `·`
+    Dots,
+}
+
+impl Charset {
+    /// The glyphs of live and dead cells
+    fn glyphs(self) -> (&'static str, &'static str) {
+        match self {
+            Self::Ascii => ("*", " "),
+            Self::Block => ("█", " "),
+            Self::Dots => ("●", "·"),
+        }
+    }
+}
+
+/// How many columns of the terminal a cell is drawn over, which are about half as wide as they
+/// are tall
+#[derive(Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum Aspect {
+    /// A column for each, as wide as the glyphs are
+    #[default]
+    Narrow,
+    /// Two columns for each, so that the cells come out about square: blocks doubled and
+    /// other glyphs followed by a space, unless already that wide
+    Square,
+}
+
+/// A single character as the terminal shows it, which may be made of several code points, such
+/// as an emoji with a variation selector
+#[derive(Clone)]
+pub struct Glyph {
+    text: String,
+    /// Columns of the terminal it takes up
+    width: u16,
+}
+
+impl FromStr for Glyph {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars();
+        let Some(first) = chars.next() else {
+            return Err("expected a character".to_string());
+        };
+        let mut width = char_width(first);
+        if first.is_control() || width == 0 {
+            return Err(format!("{first:?} doesn't show as a character of its own"));
+        }
+        let mut joined = false;
+        let mut flag = is_regional_indicator(first);
+        for c in chars {
+            match c {
+                // Asks for the emoji presentation, which is two columns wide
+                '\u{fe0f}' => width = 2,
+                '\u{200d}' => joined = true,
+                // Emoji joined into one, such as a family
+                _ if joined && char_width(c) > 0 => joined = false,
+                // Pairs of regional indicators make up a flag
+                _ if flag && is_regional_indicator(c) => flag = false,
+                _ if char_width(c) == 0 => {}
+                _ => return Err(format!("expected a single character, not `{s}`")),
+            }
+        }
+        Ok(Self {
+            text: s.to_string(),
+            width,
+        })
+    }
+}
+
+/// The glyphs that live and dead cells are drawn with, both as wide as each other
+#[derive(Clone)]
+pub struct Glyphs {
+    pub alive: String,
+    pub dead: String,
+    /// Columns of the terminal each cell takes up
+    pub width: u16,
+}
+
+impl Glyphs {
+    /// Takes the glyphs of `charset` unless others are given, widened to the `aspect`
+    pub fn new(
+        charset: Charset,
+        alive: Option<Glyph>,
+        dead: Option<Glyph>,
+        aspect: Aspect,
+    ) -> Result<Self, String> {
+        let (charset_alive, charset_dead) = charset.glyphs();
+        let alive = alive.unwrap_or_else(|| charset_alive.parse().unwrap());
+        let dead = dead.unwrap_or_else(|| charset_dead.parse().unwrap());
+        if alive.width != dead.width {
+            return Err(format!(
+                "the glyphs of live and dead cells must be as wide as each other to keep the \
+                 columns lined up, but `{}` is {} wide and `{}` {}",
+                alive.text, alive.width, dead.text, dead.width
+            ));
+        }
+        if aspect == Aspect::Square && alive.width == 1 {
+            return Ok(Self {
+                alive: widen(&alive.text),
+                dead: widen(&dead.text),
+                width: 2,
+            });
+        }
+        Ok(Self {
+            alive: alive.text,
+            dead: dead.text,
+            width: alive.width,
+        })
+    }
+}
+
+/// A glyph a column wide made two columns wide, with blocks staying solid
+fn widen(glyph: &str) -> String {
+    match glyph {
+        "█" => "██".to_string(),
+        glyph => format!("{glyph} "),
+    }
+}
+
+fn is_regional_indicator(c: char) -> bool {
+    ('\u{1f1e6}'..='\u{1f1ff}').contains(&c)
+}
+
+/// Columns of the terminal a code point takes up on its own: none for those that combine with
+/// the one before, two for East Asian wide and fullwidth characters and most emoji, and one for
+/// everything else
+fn char_width(c: char) -> u16 {
+    const ZERO: &[(u32, u32)] = &[
+        (0x0300, 0x036f),
+        (0x0483, 0x0489),
+        (0x0591, 0x05bd),
+        (0x0610, 0x061a),
+        (0x064b, 0x065f),
+        (0x0e31, 0x0e31),
+        (0x0e34, 0x0e3a),
+        (0x0e47, 0x0e4e),
+        (0x1ab0, 0x1aff),
+        (0x1dc0, 0x1dff),
+        (0x200b, 0x200f),
+        (0x20d0, 0x20ff),
+        (0xfe00, 0xfe0f),
+        (0xfe20, 0xfe2f),
+        (0x1f3fb, 0x1f3ff),
+        (0xe0020, 0xe007f),
+        (0xe0100, 0xe01ef),
+    ];
+    const WIDE: &[(u32, u32)] = &[
+        (0x1100, 0x115f),
+        (0x231a, 0x231b),
+        (0x2329, 0x232a),
+        (0x23e9, 0x23ec),
+        (0x23f0, 0x23f0),
+        (0x23f3, 0x23f3),
+        (0x25fd, 0x25fe),
+        (0x2614, 0x2615),
+        (0x2648, 0x2653),
+        (0x267f, 0x267f),
+        (0x2693, 0x2693),
+        (0x26a1, 0x26a1),
+        (0x26aa, 0x26ab),
+        (0x26bd, 0x26be),
+        (0x26c4, 0x26c5),
+        (0x26ce, 0x26ce),
+        (0x26d4, 0x26d4),
+        (0x26ea, 0x26ea),
+        (0x26f2, 0x26f3),
+        (0x26f5, 0x26f5),
+        (0x26fa, 0x26fa),
+        (0x26fd, 0x26fd),
+        (0x2705, 0x2705),
+        (0x270a, 0x270b),
+        (0x2728, 0x2728),
+        (0x274c, 0x274c),
+        (0x274e, 0x274e),
+        (0x2753, 0x2755),
+        (0x2757, 0x2757),
+        (0x2795, 0x2797),
+        (0x27b0, 0x27b0),
+        (0x27bf, 0x27bf),
+        (0x2b1b, 0x2b1c),
+        (0x2b50, 0x2b50),
+        (0x2b55, 0x2b55),
+        (0x2e80, 0x303e),
+        (0x3041, 0x33ff),
+        (0x3400, 0x4dbf),
+        (0x4e00, 0x9fff),
+        (0xa000, 0xa4cf),
+        (0xa960, 0xa97f),
+        (0xac00, 0xd7a3),
+        (0xf900, 0xfaff),
+        (0xfe10, 0xfe19),
+        (0xfe30, 0xfe6f),
+        (0xff00, 0xff60),
+        (0xffe0, 0xffe6),
+        (0x16fe0, 0x16fe4),
+        (0x17000, 0x18cff),
+        (0x1b000, 0x1b2ff),
+        (0x1f004, 0x1f004),
+        (0x1f0cf, 0x1f0cf),
+        (0x1f18e, 0x1f18e),
+        (0x1f191, 0x1f19a),
+        (0x1f1e6, 0x1f1ff),
+        (0x1f200, 0x1f2ff),
+        (0x1f300, 0x1f320),
+        (0x1f32d, 0x1f335),
+        (0x1f337, 0x1f37c),
+        (0x1f37e, 0x1f393),
+        (0x1f3a0, 0x1f3ca),
+        (0x1f3cf, 0x1f3d3),
+        (0x1f3e0, 0x1f3f0),
+        (0x1f3f4, 0x1f3f4),
+        (0x1f3f8, 0x1f43e),
+        (0x1f440, 0x1f440),
+        (0x1f442, 0x1f4fc),
+        (0x1f4ff, 0x1f53d),
+        (0x1f54b, 0x1f54e),
+        (0x1f550, 0x1f567),
+        (0x1f57a, 0x1f57a),
+        (0x1f595, 0x1f596),
+        (0x1f5a4, 0x1f5a4),
+        (0x1f5fb, 0x1f64f),
+        (0x1f680, 0x1f6c5),
+        (0x1f6cc, 0x1f6cc),
+        (0x1f6d0, 0x1f6d2),
+        (0x1f6d5, 0x1f6d7),
+        (0x1f6dc, 0x1f6df),
+        (0x1f6eb, 0x1f6ec),
+        (0x1f6f4, 0x1f6fc),
+        (0x1f7e0, 0x1f7eb),
+        (0x1f7f0, 0x1f7f0),
+        (0x1f90c, 0x1f93a),
+        (0x1f93c, 0x1f945),
+        (0x1f947, 0x1f9ff),
+        (0x1fa70, 0x1faff),
+        (0x20000, 0x3fffd),
+    ];
+    let contains = |ranges: &[(u32, u32)]| {
+        ranges
+            .iter()
+            .any(|&(first, last)| (first..=last).contains(&(c as u32)))
+    };
+    if contains(ZERO) {
+        0
+    } else if contains(WIDE) {
+        2
+    } else {
+        1
+    }
+}
@@ -0,0 +1,98 @@
+//! Saving the state of long runs every so often, to go on from if they are cut short
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use game_of_life::{state, Life};
+
+const PREFIX: &str = "checkpoint-";
+const EXTENSION: &str = ".state";
+
+/// Writes the state every `every` generations to a file named after the generation, deleting
+/// all but the `keep` latest ones. Unless `strict`, failing to is only reported the first
+/// time, and the simulation goes on.
+pub struct Checkpoints {
+    every: u64,
+    dir: PathBuf,
+    keep: usize,
+    strict: bool,
+    /// Generations of the checkpoints in the directory, oldest first
+    written: Vec<u64>,
+    /// What went wrong the first time writing one failed
+    error: Option<String>,
+}
+
+impl Checkpoints {
+    /// Creates the directory if need be. Checkpoints already in it count towards the ones kept.
+    pub fn new(every: u64, dir: &Path, keep: usize, strict: bool) -> Result<Self, String> {
+        fs::create_dir_all(dir)
+            .map_err(|err| format!("failed to create {}: {err}", dir.display()))?;
+        let entries =
+            fs::read_dir(dir).map_err(|err| format!("failed to read {}: {err}", dir.display()))?;
+        let mut written: Vec<u64> = entries
+            .filter_map(|entry| {
+                let name = entry.ok()?.file_name();
+                let name = name.to_str()?;
+                name.strip_prefix(PREFIX)?
+                    .strip_suffix(EXTENSION)?
+                    .parse()
+                    .ok()
+            })
+            .collect();
+        written.sort_unstable();
+        Ok(Self {
+            every,
+            dir: dir.to_path_buf(),
+            keep,
+            strict,
+            written,
+            error: None,
+        })
+    }
+
+    /// Writes a checkpoint if the generation the simulation is at is due for one. Returns
+    /// what went wrong if it is the first time writing one failed, or fails with it if
+    /// strict.
+    pub fn record(&mut self, life: &Life) -> Result<Option<String>, String> {
+        let generation = life.generation();
+        if generation == 0 || !generation.is_multiple_of(self.every) {
+            return Ok(None);
+        }
+        match self.write(life, generation) {
+            Ok(()) => Ok(None),
+            Err(err) if self.strict => Err(err),
+            Err(_) if self.error.is_some() => Ok(None),
+            Err(err) => {
+                self.error = Some(err.clone());
+                Ok(Some(err))
+            }
+        }
+    }
+
+    /// What went wrong the first time writing a checkpoint failed, if it ever did
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    fn write(&mut self, life: &Life, generation: u64) -> Result<(), String> {
+        // The states of modest fields are small enough to write without holding up the frames
+        state::save(life, &self.path(generation))?;
+        // Going back through the history writes the same generation again
+        self.written.retain(|&written| written != generation);
+        self.written.push(generation);
+        self.written.sort_unstable();
+        while self.written.len() > self.keep {
+            let oldest = self.written.remove(0);
+            let oldest = self.path(oldest);
+            fs::remove_file(&oldest)
+                .map_err(|err| format!("failed to delete {}: {err}", oldest.display()))?;
+        }
+        Ok(())
+    }
+
+    fn path(&self, generation: u64) -> PathBuf {
+        self.dir.join(format!("{PREFIX}{generation}{EXTENSION}"))
+    }
+}
@@ -0,0 +1,319 @@
+//! Colors of the cells, as far as the terminal supports them
+
+use std::{env, str::FromStr};
+
+use clap::ValueEnum;
+use crossterm::style;
+
+use game_of_life::{trail, variant::Side};
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum Theme {
+    /// No colors at all
+    #[default]
+    Never,
+    /// Green on black
+    Matrix,
+    /// Amber on black, like an old monitor
+    Amber,
+    /// White on black
+    White,
+    /// Live cells by how long they have been alive, from bright newborns to old ones in a cool
+    /// blue, on black
+    Age,
+}
+
+impl Theme {
+    /// Colors of live cells, dead cells and the background
+    fn colors(self) -> Option<(Color, Color, Color)> {
+        let (alive, dead) = match self {
+            Self::Never => return None,
+            Self::Matrix => (Color::Rgb(0x00, 0xff, 0x41), Color::Rgb(0x00, 0x3b, 0x00)),
+            Self::Amber => (Color::Rgb(0xff, 0xb0, 0x00), Color::Rgb(0x4d, 0x35, 0x00)),
+            Self::White => (Color::Rgb(0xff, 0xff, 0xff), Color::Rgb(0x5f, 0x5f, 0x5f)),
+            Self::Age => (age_color(0), Color::Rgb(0x1c, 0x1c, 0x30)),
+        };
+        Some((alive, dead, Color::Rgb(0, 0, 0)))
+    }
+}
+
+/// Colors of live cells from newborn to old, evenly spread over the buckets of ages
+const AGE_GRADIENT: [[u8; 3]; 5] = [
+    [0xff, 0xff, 0xb0],
+    [0xff, 0xb0, 0x00],
+    [0xff, 0x40, 0x40],
+    [0xb0, 0x40, 0xd0],
+    [0x30, 0x50, 0xff],
+];
+
+/// Red and blue, so that both stand out on the dark backgrounds of the themes
+const SIDES: [Color; 2] = [Color::Rgb(0xff, 0x40, 0x40), Color::Rgb(0x40, 0x90, 0xff)];
+
+/// Blue electron heads with red tails on amber wires, as Wireworld is usually drawn
+const WIREWORLD: [Color; 3] = [
+    Color::Rgb(0x40, 0x90, 0xff),
+    Color::Rgb(0xff, 0x40, 0x40),
+    Color::Rgb(0xff, 0xb0, 0x00),
+];
+
+/// Ages of live cells come in buckets of powers of two: 1, 2 to 3, 4 to 7 and so on, and
+/// the last one has all the older cells
+const AGE_BUCKETS: usize = 8;
+
+/// Shades of blocks of cells, from empty to full
+pub const SHADES: usize = 5;
+
+/// The 16 colors every color terminal has, by name and roughly how they look
+const NAMED: [(&str, style::Color, (u8, u8, u8)); 16] = [
+    ("black", style::Color::Black, (0, 0, 0)),
+    ("dark-red", style::Color::DarkRed, (128, 0, 0)),
+    ("dark-green", style::Color::DarkGreen, (0, 128, 0)),
+    ("dark-yellow", style::Color::DarkYellow, (128, 128, 0)),
+    ("dark-blue", style::Color::DarkBlue, (0, 0, 128)),
+    ("dark-magenta", style::Color::DarkMagenta, (128, 0, 128)),
+    ("dark-cyan", style::Color::DarkCyan, (0, 128, 128)),
+    ("grey", style::Color::Grey, (192, 192, 192)),
+    ("dark-grey", style::Color::DarkGrey, (128, 128, 128)),
+    ("red", style::Color::Red, (255, 0, 0)),
+    ("green", style::Color::Green, (0, 255, 0)),
+    ("yellow", style::Color::Yellow, (255, 255, 0)),
+    ("blue", style::Color::Blue, (0, 0, 255)),
+    ("magenta", style::Color::Magenta, (255, 0, 255)),
+    ("cyan", style::Color::Cyan, (0, 255, 255)),
+    ("white", style::Color::White, (255, 255, 255)),
+];
+
+/// A color given by name or as `#rrggbb`
+#[derive(Clone, Copy)]
+pub enum Color {
+    /// Index into `NAMED`
+    Named(usize),
+    Rgb(u8, u8, u8),
+}
+
+impl FromStr for Color {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(hex) = s.strip_prefix('#') {
+            let channel = |i: usize| {
+                hex.get(i..i + 2)
+                    .and_then(|channel| u8::from_str_radix(channel, 16).ok())
+            };
+            return match (hex.len(), channel(0), channel(2), channel(4)) {
+                (6, Some(r), Some(g), Some(b)) => Ok(Self::Rgb(r, g, b)),
+                _ => Err(format!("expected a color like `#00ff41`, not `{s}`")),
+            };
+        }
+        let name = s.to_ascii_lowercase().replace("gray", "grey");
+        NAMED
+            .iter()
+            .position(|&(named, ..)| named == name)
+            .map(Self::Named)
+            .ok_or_else(|| {
+                let names: Vec<_> = NAMED.iter().map(|&(name, ..)| name).collect();
+                format!(
+                    "unknown color `{s}`, expected `#rrggbb` or one of {}",
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+/// Whether the terminal shows colors at all, going by `NO_COLOR` and a `TERM` of `dumb`
+pub fn supported() -> bool {
+    env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+        && !env::var("TERM").is_ok_and(|term| term == "dumb")
+}
+
+/// How many colors the terminal can show
+#[derive(Clone, Copy)]
+enum Depth {
+    TrueColor,
+    Ansi256,
+    Ansi16,
+}
+
+impl Depth {
+    /// Goes by what terminals commonly advertise in `COLORTERM` and `TERM`
+    fn detect() -> Self {
+        let colorterm = env::var("COLORTERM").unwrap_or_default();
+        if colorterm == "truecolor" || colorterm == "24bit" {
+            Self::TrueColor
+        } else if env::var("TERM").is_ok_and(|term| term.contains("256color")) {
+            Self::Ansi256
+        } else {
+            Self::Ansi16
+        }
+    }
+}
+
+impl Color {
+    pub fn rgb(self) -> (u8, u8, u8) {
+        match self {
+            Self::Named(i) => NAMED[i].2,
+            Self::Rgb(r, g, b) => (r, g, b),
+        }
+    }
+
+    /// The color a fraction `t` of the way from this one to `other`
+    fn blend(self, other: Self, t: f64) -> Self {
+        let ((r, g, b), (other_r, other_g, other_b)) = (self.rgb(), other.rgb());
+        let mix = |a: u8, b: u8| (a as f64 * (1.0 - t) + b as f64 * t).round() as u8;
+        Self::Rgb(mix(r, other_r), mix(g, other_g), mix(b, other_b))
+    }
+
+    /// The closest color the terminal can show
+    fn downsample(self, depth: Depth) -> style::Color {
+        let (r, g, b) = match self {
+            Self::Named(i) => return NAMED[i].1,
+            Self::Rgb(r, g, b) => (r, g, b),
+        };
+        match depth {
+            Depth::TrueColor => style::Color::Rgb { r, g, b },
+            Depth::Ansi256 => style::Color::AnsiValue(ansi256(r, g, b)),
+            Depth::Ansi16 => {
+                let named = NAMED
+                    .iter()
+                    .min_by_key(|&&(_, _, rgb)| distance((r, g, b), rgb));
+                named.unwrap().1
+            }
+        }
+    }
+}
+
+/// The closest of the 6x6x6 color cube and the 24 greys of the 256 color palette
+fn ansi256(r: u8, g: u8, b: u8) -> u8 {
+    // Levels of the cube are 0, then 95 to 255 in steps of 40
+    const LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+    let level = |c: u8| {
+        (0..6)
+            .min_by_key(|&i| (LEVELS[i] as i32 - c as i32).abs())
+            .unwrap()
+    };
+    let (i, j, k) = (level(r), level(g), level(b));
+    let cube = (LEVELS[i], LEVELS[j], LEVELS[k]);
+    // Greys are 8 to 238 in steps of 10
+    let grey_index = ((r as u32 + g as u32 + b as u32) / 3).saturating_sub(3) / 10;
+    let grey_index = grey_index.min(23) as u8;
+    let grey = 8 + 10 * grey_index;
+    if distance((r, g, b), (grey, grey, grey)) < distance((r, g, b), cube) {
+        232 + grey_index
+    } else {
+        16 + 36 * i as u8 + 6 * j as u8 + k as u8
+    }
+}
+
+/// Squared distance between two colors
+fn distance((r, g, b): (u8, u8, u8), (other_r, other_g, other_b): (u8, u8, u8)) -> i32 {
+    let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2);
+    d(r, other_r) + d(g, other_g) + d(b, other_b)
+}
+
+/// The color of a bucket of ages, blended between the two closest colors of the gradient
+fn age_color(bucket: usize) -> Color {
+    let position = bucket as f64 * (AGE_GRADIENT.len() - 1) as f64 / (AGE_BUCKETS - 1) as f64;
+    let (i, t) = (position as usize, position.fract());
+    let from = AGE_GRADIENT[i];
+    let to = AGE_GRADIENT[(i + 1).min(AGE_GRADIENT.len() - 1)];
+    let [r, g, b] =
+        [0, 1, 2].map(|c| (from[c] as f64 * (1.0 - t) + to[c] as f64 * t).round() as u8);
+    Color::Rgb(r, g, b)
+}
+
+/// Colors of the cells by state in pictures, which show them whatever the terminal supports:
+/// dead cells on the background, then live and dying ones in the colors of `theme`, or white
+/// and gray without one, and the states of Wireworld in its own colors
+pub fn states(
+    theme: Theme,
+    alive: Option<Color>,
+    dead: Option<Color>,
+    wireworld: bool,
+) -> Vec<[u8; 3]> {
+    let (theme_alive, theme_dead, background) = theme.colors().or(Theme::White.colors()).unwrap();
+    let rgb = |color: Color| {
+        let (r, g, b) = color.rgb();
+        [r, g, b]
+    };
+    let mut colors = vec![rgb(background)];
+    if wireworld {
+        colors.extend(WIREWORLD.map(rgb));
+    } else {
+        colors.push(rgb(alive.unwrap_or(theme_alive)));
+        colors.push(rgb(dead.unwrap_or(theme_dead)));
+    }
+    colors
+}
+
+/// Colors of the live cells of Immigration in pictures, red for the first side and blue for
+/// the second
+pub fn sides() -> [[u8; 3]; 2] {
+    SIDES.map(|color| {
+        let (r, g, b) = color.rgb();
+        [r, g, b]
+    })
+}
+
+/// Colors of the cells as the terminal shows them
+#[derive(Clone, Copy)]
+pub struct Palette {
+    pub alive: style::Color,
+    pub dead: style::Color,
+    pub background: style::Color,
+    /// Colors of live cells by the bucket of their age, when coloring them by age
+    ages: Option<[style::Color; AGE_BUCKETS]>,
+    /// Colors of the glyphs of trails, fading from that of live cells to that of dead ones
+    pub trails: [style::Color; trail::GLYPHS.len()],
+    /// Colors of blocks of cells from empty to full, from that of dead cells to that of live
+    /// ones, for the shades of a zoomed out view or the heatmap drawn in solid colors
+    pub shades: [style::Color; SHADES],
+    /// Colors of the live cells of Immigration, whatever the theme
+    sides: [style::Color; 2],
+    /// Colors of the electron heads, their tails and the conductors of Wireworld, whatever
+    /// the theme
+    pub wireworld: [style::Color; 3],
+}
+
+impl Palette {
+    /// The colors of `theme`, with those of live or dead cells replaced if given, or no colors
+    /// at all for `Theme::Never`
+    pub fn new(theme: Theme, alive: Option<Color>, dead: Option<Color>) -> Option<Self> {
+        let (theme_alive, theme_dead, background) = theme.colors()?;
+        let depth = Depth::detect();
+        let (alive, dead) = (alive.unwrap_or(theme_alive), dead.unwrap_or(theme_dead));
+        let trails = std::array::from_fn(|i| {
+            let t = (i + 1) as f64 / (trail::GLYPHS.len() + 1) as f64;
+            alive.blend(dead, t).downsample(depth)
+        });
+        let shades = std::array::from_fn(|i| {
+            let t = i as f64 / (SHADES - 1) as f64;
+            dead.blend(alive, t).downsample(depth)
+        });
+        Some(Self {
+            alive: alive.downsample(depth),
+            dead: dead.downsample(depth),
+            background: background.downsample(depth),
+            ages: (theme == Theme::Age).then(|| {
+                let gradient: [_; AGE_BUCKETS] = std::array::from_fn(age_color);
+                gradient.map(|color| color.downsample(depth))
+            }),
+            trails,
+            shades,
+            sides: SIDES.map(|color| color.downsample(depth)),
+            wireworld: WIREWORLD.map(|color| color.downsample(depth)),
+        })
+    }
+
+    pub fn side(&self, side: Side) -> style::Color {
+        match side {
+            Side::First => self.sides[0],
+            Side::Second => self.sides[1],
+        }
+    }
+
+    /// The color of a live cell of the given age, when coloring them by age
+    pub fn age(&self, age: u16) -> Option<style::Color> {
+        let bucket = (age.max(1).ilog2() as usize).min(AGE_BUCKETS - 1);
+        Some(self.ages?[bucket])
+    }
+}
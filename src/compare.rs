@@ -0,0 +1,321 @@
+//! Two simulations of the same field in lockstep, the second with a cell flipped, to see how
+//! far the difference it makes spreads
+
+use std::{
+    collections::HashMap,
+    io::{self, BufWriter, Write},
+    iter,
+    time::Instant,
+};
+
+use crossterm::{
+    cursor, style,
+    terminal::{self, ClearType},
+};
+
+use game_of_life::{engine::Engine, variant::Side, Life};
+
+use crate::{
+    charset::Glyphs,
+    color::{Palette, Theme},
+    controls::{self, Command, Mode},
+    error::Error,
+    render::TerminalGuard,
+    stats::Stats,
+    Config, Display,
+};
+
+pub struct Comparison {
+    first: Life,
+    second: Life,
+}
+
+impl Comparison {
+    /// Sets up a second simulation of the field `life` starts from, with the cell at
+    /// `perturb` flipped if there is one
+    pub fn new(life: Life, perturb: Option<(i64, i64)>, config: &Config) -> Result<Self, Error> {
+        let mut second = Life::new(
+            life.snapshot(),
+            life.rules.clone(),
+            life.edges.is_none(),
+            config.engine,
+            config.threads.get(),
+        )
+        .map_err(Error::Usage)?;
+        if let Some((x, y)) = perturb {
+            if life.resolve(x, y).is_none() {
+                return Err(Error::Usage(format!(
+                    "--perturb {x},{y} is outside of the {}x{} field",
+                    life.width, life.height
+                )));
+            }
+            second.toggle(x, y);
+        }
+        Ok(Self {
+            first: life,
+            second,
+        })
+    }
+
+    fn step(&mut self) {
+        self.first.step();
+        self.second.step();
+    }
+
+    /// Cells in a different state in the two simulations
+    pub fn divergence(&self) -> u64 {
+        diverging(&*self.first.engine, &*self.second.engine)
+    }
+
+    /// Runs both simulations until the --generations or until quitting, shown the way the
+    /// configuration says, and gives back the code to exit with
+    pub fn run(mut self, config: &Config, glyphs: &Glyphs) -> Result<i32, Error> {
+        let mut stats = config
+            .stats
+            .map(|format| Stats::new(format, config.stats_file.as_deref()))
+            .transpose()
+            .map_err(Error::Io)?;
+        let display = Display::of(config);
+        let mut record = |comparison: &Self| match &mut stats {
+            Some(stats) => stats
+                .record_divergence(&comparison.first, comparison.divergence())
+                .map_err(Error::Io),
+            None => Ok(()),
+        };
+        let done = |comparison: &Self| {
+            config
+                .generations
+                .is_some_and(|limit| comparison.first.generation() >= limit)
+        };
+
+        record(&self)?;
+        let quit = match display {
+            Display::Headless => {
+                while !done(&self) {
+                    self.step();
+                    record(&self)?;
+                }
+                false
+            }
+            Display::Plain => {
+                let write_error = |err| Error::Io(format!("failed to write to stdout: {err}"));
+                let mut stdout = BufWriter::new(io::stdout().lock());
+                let frame_time = crate::pace::frame_time(config);
+                let mut next_frame = Instant::now();
+                loop {
+                    std::thread::sleep(next_frame.saturating_duration_since(Instant::now()));
+                    next_frame += frame_time;
+                    self.write_plain(&mut stdout)
+                        .and_then(|()| stdout.flush())
+                        .map_err(write_error)?;
+                    if done(&self) {
+                        break false;
+                    }
+                    for _ in 0..config.render_every {
+                        self.step();
+                        record(&self)?;
+                    }
+                }
+            }
+            Display::Screen => self.show(config, glyphs, &mut record)?,
+        };
+
+        let summary = format!(
+            "{} after {} generations, {} and {} live cells, {} cells differ",
+            if quit { "stopped" } else { "reached the limit" },
+            self.first.generation(),
+            self.first.population(),
+            self.second.population(),
+            self.divergence()
+        );
+        // Keeps stdout to the stats alone, and clean of what the screen was for
+        if display == Display::Screen || (stats.is_some() && config.stats_file.is_none()) {
+            eprintln!("{summary}");
+        } else {
+            println!("{summary}");
+        }
+        Ok(0)
+    }
+
+    /// Prints both fields beside each other, like a run without a terminal prints one
+    fn write_plain(&self, out: &mut impl Write) -> io::Result<()> {
+        let width = self.first.width;
+        let (first, second) = (self.first.board(), self.second.board());
+        for (left, right) in first.lines().zip(second.lines()) {
+            writeln!(out, "{left:<width$} | {right}")?;
+        }
+        writeln!(
+            out,
+            "-- gen {} pop {} {} diverging {}",
+            self.first.generation(),
+            self.first.population(),
+            self.second.population(),
+            self.divergence()
+        )
+    }
+
+    /// Draws both fields on the screen, the cells that differ in a color of their own, until
+    /// the --generations or until quitting, which is what the result tells
+    fn show(
+        &mut self,
+        config: &Config,
+        glyphs: &Glyphs,
+        record: &mut impl FnMut(&Self) -> Result<(), Error>,
+    ) -> Result<bool, Error> {
+        let terminal_error = |err| Error::Io(format!("failed to draw on the terminal: {err}"));
+        let palette = Palette::new(
+            config.color.unwrap_or(Theme::White),
+            config.alive_color,
+            config.dead_color,
+        )
+        .unwrap();
+        let frame_time = crate::pace::frame_time(config);
+        let _guard = TerminalGuard::enter().map_err(terminal_error)?;
+        let mut stdout = BufWriter::new(io::stdout().lock());
+        let mut terminal = terminal::size().map_err(terminal_error)?;
+        let mut mode = Mode::Running;
+        let mut next_frame = Instant::now() + frame_time;
+        let mut frame = Vec::new();
+        loop {
+            frame.clear();
+            self.draw(&mut frame, terminal, glyphs, &palette)
+                .map_err(terminal_error)?;
+            stdout
+                .write_all(&frame)
+                .and_then(|()| stdout.flush())
+                .map_err(terminal_error)?;
+            if mode == Mode::Running
+                && self.first.generation() >= config.generations.unwrap_or(u64::MAX)
+            {
+                return Ok(false);
+            }
+
+            let mut steps = 0;
+            loop {
+                let deadline = (mode == Mode::Running).then_some(next_frame);
+                match controls::next_command(mode, deadline).map_err(terminal_error)? {
+                    None => {
+                        next_frame = (next_frame + frame_time).max(Instant::now());
+                        steps = config.render_every;
+                    }
+                    Some(Command::TogglePause) => {
+                        mode = match mode {
+                            Mode::Running => Mode::Paused,
+                            _ => Mode::Running,
+                        };
+                        next_frame = Instant::now() + frame_time;
+                    }
+                    Some(Command::Step) => steps = 1,
+                    Some(Command::Resize { columns, rows }) => terminal = (columns, rows),
+                    Some(Command::Quit) => return Ok(true),
+                    Some(_) => continue,
+                }
+                break;
+            }
+            for _ in 0..steps {
+                self.step();
+                record(self)?;
+            }
+        }
+    }
+
+    /// Appends to `frame` the two fields as far as they fit into the terminal, beside each
+    /// other or one above the other, whichever shows more of them, and the status line
+    fn draw(
+        &self,
+        frame: &mut Vec<u8>,
+        (columns, rows): (u16, u16),
+        glyphs: &Glyphs,
+        palette: &Palette,
+    ) -> io::Result<()> {
+        crossterm::queue!(frame, terminal::Clear(ClearType::All))?;
+        let cells = usize::from(columns / glyphs.width.max(1));
+        let lines = usize::from(rows.saturating_sub(1));
+        let beside = beside((columns / glyphs.width.max(1), rows.saturating_sub(1)));
+        let (width, height) = if beside {
+            ((cells.saturating_sub(1) / 2), lines)
+        } else {
+            (cells, lines.saturating_sub(1) / 2)
+        };
+        let (width, height) = (width.min(self.first.width), height.min(self.first.height));
+
+        let put = |frame: &mut Vec<u8>, glyph: char| match glyph {
+            '*' => frame.extend_from_slice(glyphs.alive.as_bytes()),
+            ' ' => frame.extend_from_slice(glyphs.dead.as_bytes()),
+            glyph => {
+                let mut utf8 = [0; 4];
+                frame.extend_from_slice(glyph.encode_utf8(&mut utf8).as_bytes());
+                frame.extend(iter::repeat_n(b' ', usize::from(glyphs.width) - 1));
+            }
+        };
+        let row = |frame: &mut Vec<u8>, life: &Life, other: &Life, y: usize| {
+            let mut last = None;
+            for x in 0..width as i64 {
+                let glyph = life.glyph(x, y as i64);
+                let color = if life.engine.state(x, y as i64) != other.engine.state(x, y as i64) {
+                    palette.side(Side::First)
+                } else if glyph == ' ' {
+                    palette.dead
+                } else {
+                    palette.alive
+                };
+                if last != Some(color) {
+                    crossterm::queue!(frame, style::SetForegroundColor(color))?;
+                    last = Some(color);
+                }
+                put(frame, glyph);
+            }
+            io::Result::Ok(())
+        };
+        for y in 0..height {
+            crossterm::queue!(frame, cursor::MoveTo(0, y as u16))?;
+            row(frame, &self.first, &self.second, y)?;
+            if beside {
+                crossterm::queue!(frame, style::SetForegroundColor(palette.dead))?;
+                frame.extend_from_slice("│".as_bytes());
+                frame.extend(iter::repeat_n(b' ', usize::from(glyphs.width) - 1));
+                row(frame, &self.second, &self.first, y)?;
+            }
+        }
+        if !beside {
+            crossterm::queue!(frame, cursor::MoveTo(0, height as u16))?;
+            crossterm::queue!(frame, style::SetForegroundColor(palette.dead))?;
+            frame.extend(iter::repeat_n(b'-', width * usize::from(glyphs.width)));
+            for y in 0..height {
+                crossterm::queue!(frame, cursor::MoveTo(0, (height + 1 + y) as u16))?;
+                row(frame, &self.second, &self.first, y)?;
+            }
+        }
+        crossterm::queue!(
+            frame,
+            cursor::MoveTo(0, rows.saturating_sub(1)),
+            style::ResetColor
+        )?;
+        write!(
+            frame,
+            "gen {} pop {} {} diverging {}",
+            self.first.generation(),
+            self.first.population(),
+            self.second.population(),
+            self.divergence()
+        )
+    }
+}
+
+/// Whether two fields fit better beside each other than one above the other into a terminal
+/// of so many cells across and lines down
+pub fn beside((cells, lines): (u16, u16)) -> bool {
+    (cells / 2).min(lines) >= cells.min(lines / 2)
+}
+
+/// Cells in a different state in two engines, counting only those that are not dead in
+/// either
+fn diverging(first: &dyn Engine, second: &dyn Engine) -> u64 {
+    let first: HashMap<_, _> = first.cell_states().into_iter().collect();
+    let second = second.cell_states();
+    let same = second
+        .iter()
+        .filter(|(cell, state)| first.get(cell) == Some(state))
+        .count();
+    (first.len() + second.len() - 2 * same) as u64
+}
@@ -0,0 +1,615 @@
+//! Keys and clicks that steer the simulation while it is shown
+
+use std::{io, time::Instant};
+
+use crossterm::event::{
+    self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseButton, MouseEventKind,
+};
+
+/// What the keys do at the moment
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// Drawing the field by hand, before the simulation starts
+    Editing,
+    Running,
+    Paused,
+    /// Aiming a built-in pattern or the copied cells, while editing or paused
+    Stamping,
+    /// Moving the corner of a rectangle of cells away from the one it started at, while
+    /// editing or paused
+    Selecting,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    /// Space, while running or paused
+    TogglePause,
+    /// `n` or `.`, while paused
+    Step,
+    /// `*` or `/`, while running or paused: makes the generations go faster or slower by a
+    /// step
+    Speed { faster: bool },
+    /// A digit, while running: makes the generations go at the speed of that number
+    Preset(usize),
+    /// Left or right arrow, while paused: goes this many generations back or forth through
+    /// the history, computing the next generation past the end of it
+    Travel(isize),
+    /// `r`: starts over from a new random soup
+    Reseed,
+    /// `]` or `[`, while editing, running or paused: switches to the next or the previous
+    /// rules of the list
+    SwitchRules(isize),
+    /// `g` for the glider or a digit for the built-in pattern with that number, while editing,
+    /// paused or stamping: picks up a stamp
+    Arm(usize),
+    /// `]` or `[`, while stamping: turns the stamp clockwise by this many quarter turns
+    Rotate(u8),
+    /// Enter, while stamping
+    Place,
+    /// Esc, while stamping or selecting
+    Cancel,
+    /// `v`, while editing or paused: starts selecting a rectangle of cells at the cursor
+    Select,
+    /// `y`, while selecting: copies the live cells of the rectangle
+    Copy,
+    /// `d`, while selecting: kills the cells of the rectangle
+    Clear,
+    /// `p`, while editing or paused: picks up the copied cells to paste them like a stamp,
+    /// over the cells under them
+    Paste,
+    /// Arrows or `hjkl`, while editing, stamping or selecting: moves the cursor by a cell
+    Move { dx: i16, dy: i16 },
+    /// `hjkl`, or `HJKL` for ten cells at a time, the up and down arrows and while running
+    /// the others as well: moves the viewport over the field
+    Pan { dx: i16, dy: i16 },
+    /// Space, while editing: flips the cell under the cursor
+    Flip,
+    /// `x`, while editing, running or paused: brings dead cells to life at random, around the
+    /// cursor while editing and all over the field otherwise
+    Poke,
+    /// `u`, while editing or paused: undoes the last edit made to the generation shown
+    Undo,
+    /// `U` or Ctrl-R, while editing or paused: makes the edit undone last again
+    Redo,
+    /// `s`, while editing: saves the field
+    Save,
+    /// `w`, while running or paused: saves the whole state of the simulation
+    SaveState,
+    /// `p` while running or `P`, while running or paused: saves a picture of the field
+    Snapshot,
+    /// `s`, while running or paused: saves the live cells to a file of their own
+    Dump,
+    /// `S`: saves the field as an SVG
+    SaveSvg,
+    /// `c`, while running or paused: takes a census of the objects on the field
+    Census,
+    /// Enter, while editing: starts the simulation
+    Start,
+    /// `q`, Esc or Ctrl-C, which raw mode turns into a key press
+    Quit,
+    /// A left click at a position of the terminal
+    Click { column: u16, row: u16 },
+    /// The mouse moved to a position of the terminal, while stamping or selecting
+    Hover { column: u16, row: u16 },
+    /// `-` or `+`: doubles or halves the size of the blocks of cells shown as one
+    Zoom { out: bool },
+    /// `f`: starts or stops moving the viewport after the cells that change
+    Follow,
+    /// `v` while running or stamping, or `V`: switches between showing the cells and the
+    /// heatmap
+    View,
+    /// `>` or `<`, while editing, running or paused: grows or shrinks the field by a tenth
+    ResizeField { grow: bool },
+    /// `?`: shows the keys of the mode until the next key press
+    Help,
+    /// The terminal changed size, and likely lost its contents with it
+    Resize { columns: u16, rows: u16 },
+}
+
+/// Every mode there is, for the keys that do the same in all of them
+const ALL: &[Mode] = &[
+    Mode::Editing,
+    Mode::Running,
+    Mode::Paused,
+    Mode::Stamping,
+    Mode::Selecting,
+];
+
+/// The modes with a cursor to move
+const AIMING: &[Mode] = &[Mode::Editing, Mode::Stamping, Mode::Selecting];
+
+/// Keys that do the same in some of the modes
+struct Binding {
+    keys: &'static [KeyCode],
+    modes: &'static [Mode],
+    /// What the keys do, as the help has it
+    help: &'static str,
+    command: fn(KeyCode) -> Command,
+}
+
+/// The keys of each mode, the first binding of a key in a mode being the one it has
+const BINDINGS: &[Binding] = &[
+    Binding {
+        keys: &[KeyCode::Esc],
+        modes: &[Mode::Stamping],
+        help: "put the stamp back",
+        command: |_| Command::Cancel,
+    },
+    Binding {
+        keys: &[KeyCode::Esc],
+        modes: &[Mode::Selecting],
+        help: "stop selecting",
+        command: |_| Command::Cancel,
+    },
+    Binding {
+        keys: &[KeyCode::Char('y')],
+        modes: &[Mode::Selecting],
+        help: "copy the live cells of the rectangle",
+        command: |_| Command::Copy,
+    },
+    Binding {
+        keys: &[KeyCode::Char('d')],
+        modes: &[Mode::Selecting],
+        help: "kill the cells of the rectangle",
+        command: |_| Command::Clear,
+    },
+    Binding {
+        keys: &[KeyCode::Char('q'), KeyCode::Esc],
+        modes: ALL,
+        help: "quit",
+        command: |_| Command::Quit,
+    },
+    Binding {
+        keys: &[KeyCode::Char('?')],
+        modes: ALL,
+        help: "show these keys",
+        command: |_| Command::Help,
+    },
+    Binding {
+        keys: &[KeyCode::Char('g')],
+        modes: &[Mode::Editing, Mode::Paused, Mode::Stamping],
+        help: "pick up the glider",
+        command: |_| Command::Arm(0),
+    },
+    Binding {
+        keys: &[
+            KeyCode::Char('1'),
+            KeyCode::Char('2'),
+            KeyCode::Char('3'),
+            KeyCode::Char('4'),
+            KeyCode::Char('5'),
+            KeyCode::Char('6'),
+            KeyCode::Char('7'),
+            KeyCode::Char('8'),
+            KeyCode::Char('9'),
+        ],
+        modes: &[Mode::Editing, Mode::Paused, Mode::Stamping],
+        help: "pick up the built-in pattern of that number",
+        command: |key| match key {
+            KeyCode::Char(digit) => Command::Arm(digit as usize - '1' as usize),
+            _ => Command::Arm(0),
+        },
+    },
+    Binding {
+        keys: &[
+            KeyCode::Char('1'),
+            KeyCode::Char('2'),
+            KeyCode::Char('3'),
+            KeyCode::Char('4'),
+            KeyCode::Char('5'),
+            KeyCode::Char('6'),
+            KeyCode::Char('7'),
+            KeyCode::Char('8'),
+            KeyCode::Char('9'),
+        ],
+        modes: &[Mode::Running],
+        help: "go at 1, 2, 5, 10, 15, 20, 30, 60 or 120 generations a second",
+        command: |key| match key {
+            KeyCode::Char(digit) => Command::Preset(digit as usize - '1' as usize),
+            _ => Command::Preset(0),
+        },
+    },
+    Binding {
+        keys: &[KeyCode::Char('*')],
+        modes: &[Mode::Running, Mode::Paused],
+        help: "go faster",
+        command: |_| Command::Speed { faster: true },
+    },
+    Binding {
+        keys: &[KeyCode::Char('/')],
+        modes: &[Mode::Running, Mode::Paused],
+        help: "go slower",
+        command: |_| Command::Speed { faster: false },
+    },
+    Binding {
+        keys: &[KeyCode::Char(']')],
+        modes: &[Mode::Stamping],
+        help: "turn the stamp clockwise",
+        command: |_| Command::Rotate(1),
+    },
+    Binding {
+        keys: &[KeyCode::Char('[')],
+        modes: &[Mode::Stamping],
+        help: "turn the stamp counterclockwise",
+        command: |_| Command::Rotate(3),
+    },
+    Binding {
+        keys: &[KeyCode::Char(']')],
+        modes: &[Mode::Editing, Mode::Running, Mode::Paused],
+        help: "switch to the next of the --rules",
+        command: |_| Command::SwitchRules(1),
+    },
+    Binding {
+        keys: &[KeyCode::Char('[')],
+        modes: &[Mode::Editing, Mode::Running, Mode::Paused],
+        help: "switch to the previous of the --rules",
+        command: |_| Command::SwitchRules(-1),
+    },
+    Binding {
+        keys: &[KeyCode::Enter],
+        modes: &[Mode::Stamping],
+        help: "place the stamp",
+        command: |_| Command::Place,
+    },
+    Binding {
+        keys: &[KeyCode::Left, KeyCode::Char('h')],
+        modes: AIMING,
+        help: "move the cursor left",
+        command: |_| Command::Move { dx: -1, dy: 0 },
+    },
+    Binding {
+        keys: &[KeyCode::Right, KeyCode::Char('l')],
+        modes: AIMING,
+        help: "move the cursor right",
+        command: |_| Command::Move { dx: 1, dy: 0 },
+    },
+    Binding {
+        keys: &[KeyCode::Up, KeyCode::Char('k')],
+        modes: AIMING,
+        help: "move the cursor up",
+        command: |_| Command::Move { dx: 0, dy: -1 },
+    },
+    Binding {
+        keys: &[KeyCode::Down, KeyCode::Char('j')],
+        modes: AIMING,
+        help: "move the cursor down",
+        command: |_| Command::Move { dx: 0, dy: 1 },
+    },
+    Binding {
+        keys: &[KeyCode::Left, KeyCode::Char('h')],
+        modes: &[Mode::Running],
+        help: "pan left",
+        command: |_| Command::Pan { dx: -1, dy: 0 },
+    },
+    Binding {
+        keys: &[KeyCode::Char('h')],
+        modes: &[Mode::Paused],
+        help: "pan left",
+        command: |_| Command::Pan { dx: -1, dy: 0 },
+    },
+    Binding {
+        keys: &[KeyCode::Right, KeyCode::Char('l')],
+        modes: &[Mode::Running],
+        help: "pan right",
+        command: |_| Command::Pan { dx: 1, dy: 0 },
+    },
+    Binding {
+        keys: &[KeyCode::Char('l')],
+        modes: &[Mode::Paused],
+        help: "pan right",
+        command: |_| Command::Pan { dx: 1, dy: 0 },
+    },
+    Binding {
+        keys: &[KeyCode::Up, KeyCode::Char('k')],
+        modes: &[Mode::Running, Mode::Paused],
+        help: "pan up",
+        command: |_| Command::Pan { dx: 0, dy: -1 },
+    },
+    Binding {
+        keys: &[KeyCode::Down, KeyCode::Char('j')],
+        modes: &[Mode::Running, Mode::Paused],
+        help: "pan down",
+        command: |_| Command::Pan { dx: 0, dy: 1 },
+    },
+    Binding {
+        keys: &[KeyCode::Char('f')],
+        modes: ALL,
+        help: "follow the cells that change, or stop",
+        command: |_| Command::Follow,
+    },
+    Binding {
+        keys: &[KeyCode::Char('v')],
+        modes: &[Mode::Editing, Mode::Paused],
+        help: "start selecting a rectangle at the cursor",
+        command: |_| Command::Select,
+    },
+    Binding {
+        keys: &[KeyCode::Char('p')],
+        modes: &[Mode::Editing, Mode::Paused],
+        help: "paste the copied cells",
+        command: |_| Command::Paste,
+    },
+    Binding {
+        keys: &[KeyCode::Char('v'), KeyCode::Char('V')],
+        modes: ALL,
+        help: "switch between the cells and the heatmap",
+        command: |_| Command::View,
+    },
+    Binding {
+        keys: &[KeyCode::Char('-')],
+        modes: ALL,
+        help: "zoom out",
+        command: |_| Command::Zoom { out: true },
+    },
+    Binding {
+        keys: &[KeyCode::Char('+'), KeyCode::Char('=')],
+        modes: ALL,
+        help: "zoom in",
+        command: |_| Command::Zoom { out: false },
+    },
+    Binding {
+        keys: &[KeyCode::Char('H')],
+        modes: &[Mode::Running, Mode::Paused],
+        help: "pan left by ten cells",
+        command: |_| Command::Pan { dx: -10, dy: 0 },
+    },
+    Binding {
+        keys: &[KeyCode::Char('L')],
+        modes: &[Mode::Running, Mode::Paused],
+        help: "pan right by ten cells",
+        command: |_| Command::Pan { dx: 10, dy: 0 },
+    },
+    Binding {
+        keys: &[KeyCode::Char('K')],
+        modes: &[Mode::Running, Mode::Paused],
+        help: "pan up by ten cells",
+        command: |_| Command::Pan { dx: 0, dy: -10 },
+    },
+    Binding {
+        keys: &[KeyCode::Char('J')],
+        modes: &[Mode::Running, Mode::Paused],
+        help: "pan down by ten cells",
+        command: |_| Command::Pan { dx: 0, dy: 10 },
+    },
+    Binding {
+        keys: &[KeyCode::Char(' ')],
+        modes: &[Mode::Editing],
+        help: "flip the cell under the cursor",
+        command: |_| Command::Flip,
+    },
+    Binding {
+        keys: &[KeyCode::Char('x')],
+        modes: &[Mode::Editing],
+        help: "bring cells around the cursor to life at random",
+        command: |_| Command::Poke,
+    },
+    Binding {
+        keys: &[KeyCode::Char('x')],
+        modes: &[Mode::Running, Mode::Paused],
+        help: "bring cells all over the field to life at random",
+        command: |_| Command::Poke,
+    },
+    Binding {
+        keys: &[KeyCode::Char('u')],
+        modes: &[Mode::Editing, Mode::Paused],
+        help: "undo the last edit of the generation shown",
+        command: |_| Command::Undo,
+    },
+    Binding {
+        keys: &[KeyCode::Char('U')],
+        modes: &[Mode::Editing, Mode::Paused],
+        help: "redo the edit undone last, as Ctrl-R does",
+        command: |_| Command::Redo,
+    },
+    Binding {
+        keys: &[KeyCode::Char('s')],
+        modes: &[Mode::Editing],
+        help: "save the field to --output",
+        command: |_| Command::Save,
+    },
+    Binding {
+        keys: &[KeyCode::Char('w')],
+        modes: &[Mode::Running, Mode::Paused],
+        help: "save the state to --save-state",
+        command: |_| Command::SaveState,
+    },
+    Binding {
+        keys: &[KeyCode::Char('p'), KeyCode::Char('P')],
+        modes: &[Mode::Running, Mode::Paused],
+        help: "save a picture of the field",
+        command: |_| Command::Snapshot,
+    },
+    Binding {
+        keys: &[KeyCode::Char('s')],
+        modes: &[Mode::Running, Mode::Paused],
+        help: "save the live cells to the --save-dir",
+        command: |_| Command::Dump,
+    },
+    Binding {
+        keys: &[KeyCode::Char('S')],
+        modes: ALL,
+        help: "save the field to --svg",
+        command: |_| Command::SaveSvg,
+    },
+    Binding {
+        keys: &[KeyCode::Char('c')],
+        modes: &[Mode::Running, Mode::Paused],
+        help: "take a census of the objects",
+        command: |_| Command::Census,
+    },
+    Binding {
+        keys: &[KeyCode::Enter],
+        modes: &[Mode::Editing],
+        help: "start the simulation",
+        command: |_| Command::Start,
+    },
+    Binding {
+        keys: &[KeyCode::Char(' ')],
+        modes: &[Mode::Running, Mode::Paused],
+        help: "pause or resume",
+        command: |_| Command::TogglePause,
+    },
+    Binding {
+        keys: &[KeyCode::Char('n'), KeyCode::Char('.')],
+        modes: &[Mode::Paused],
+        help: "step a generation",
+        command: |_| Command::Step,
+    },
+    Binding {
+        keys: &[KeyCode::Left],
+        modes: &[Mode::Paused],
+        help: "go a generation back through the --history",
+        command: |_| Command::Travel(-1),
+    },
+    Binding {
+        keys: &[KeyCode::Right],
+        modes: &[Mode::Paused],
+        help: "go a generation forth through the --history",
+        command: |_| Command::Travel(1),
+    },
+    Binding {
+        keys: &[KeyCode::Char('r')],
+        modes: &[Mode::Editing, Mode::Running, Mode::Paused],
+        help: "start over from a new random soup",
+        command: |_| Command::Reseed,
+    },
+    Binding {
+        keys: &[KeyCode::Char('>')],
+        modes: &[Mode::Editing, Mode::Running, Mode::Paused],
+        help: "grow the field by a tenth",
+        command: |_| Command::ResizeField { grow: true },
+    },
+    Binding {
+        keys: &[KeyCode::Char('<')],
+        modes: &[Mode::Editing, Mode::Running, Mode::Paused],
+        help: "shrink the field by a tenth",
+        command: |_| Command::ResizeField { grow: false },
+    },
+];
+
+impl Command {
+    fn from_key(key: KeyEvent, mode: Mode) -> Option<Self> {
+        if key.kind == KeyEventKind::Release {
+            return None;
+        }
+        if key.modifiers.contains(KeyModifiers::CONTROL) {
+            return match (key.code, mode) {
+                (KeyCode::Char('c'), _) => Some(Self::Quit),
+                (KeyCode::Char('r'), Mode::Editing | Mode::Paused) => Some(Self::Redo),
+                _ => None,
+            };
+        }
+        BINDINGS
+            .iter()
+            .find(|binding| binding.modes.contains(&mode) && binding.keys.contains(&key.code))
+            .map(|binding| (binding.command)(key.code))
+    }
+}
+
+/// The keys of `mode` and what they do, leaving out those that an earlier binding took
+pub fn help(mode: Mode) -> Vec<(String, &'static str)> {
+    let mut taken = Vec::new();
+    let mut help = Vec::new();
+    for binding in BINDINGS
+        .iter()
+        .filter(|binding| binding.modes.contains(&mode))
+    {
+        let keys: Vec<_> = binding
+            .keys
+            .iter()
+            .filter(|key| !taken.contains(*key))
+            .collect();
+        taken.extend_from_slice(binding.keys);
+        if !keys.is_empty() {
+            help.push((label(&keys), binding.help));
+        }
+    }
+    help
+}
+
+/// The names of keys, with a run of three or more characters one after another, like the
+/// digits, given as its first and last
+fn label(keys: &[&KeyCode]) -> String {
+    let mut names: Vec<String> = Vec::new();
+    let mut i = 0;
+    while i < keys.len() {
+        let mut run = 1;
+        while let (KeyCode::Char(first), Some(KeyCode::Char(next))) = (keys[i], keys.get(i + run)) {
+            if (*first as u32 + run as u32) != *next as u32 {
+                break;
+            }
+            run += 1;
+        }
+        names.push(match (keys[i], run) {
+            (KeyCode::Char(first), 3..) => format!("{first}-{}", name(keys[i + run - 1])),
+            _ => {
+                run = 1;
+                name(keys[i])
+            }
+        });
+        i += run;
+    }
+    names.join(" ")
+}
+
+fn name(key: &KeyCode) -> String {
+    match key {
+        KeyCode::Char(' ') => "space".to_string(),
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::Left => "left".to_string(),
+        KeyCode::Right => "right".to_string(),
+        KeyCode::Up => "up".to_string(),
+        KeyCode::Down => "down".to_string(),
+        KeyCode::Enter => "Enter".to_string(),
+        KeyCode::Esc => "Esc".to_string(),
+        key => format!("{key:?}"),
+    }
+}
+
+/// Waits for the next command in `mode`, giving up at `deadline` if there is one. Other input
+/// is skipped without moving the deadline, so that pressing keys doesn't change the pace.
+pub fn next_command(mode: Mode, deadline: Option<Instant>) -> io::Result<Option<Command>> {
+    loop {
+        if let Some(deadline) = deadline {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            if !event::poll(timeout)? {
+                return Ok(None);
+            }
+        }
+        let command = match event::read()? {
+            Event::Key(key) => Command::from_key(key, mode),
+            Event::Mouse(mouse) if mouse.kind == MouseEventKind::Down(MouseButton::Left) => {
+                Some(Command::Click {
+                    column: mouse.column,
+                    row: mouse.row,
+                })
+            }
+            Event::Mouse(mouse)
+                if mouse.kind == MouseEventKind::Moved
+                    && matches!(mode, Mode::Stamping | Mode::Selecting) =>
+            {
+                Some(Command::Hover {
+                    column: mouse.column,
+                    row: mouse.row,
+                })
+            }
+            Event::Resize(columns, rows) => Some(Command::Resize { columns, rows }),
+            _ => None,
+        };
+        if command.is_some() {
+            return Ok(command);
+        }
+    }
+}
+
+/// Waits for any key to be pressed, giving back the new size of the terminal instead if it
+/// changes size first
+pub fn any_key() -> io::Result<Option<(u16, u16)>> {
+    loop {
+        match event::read()? {
+            Event::Key(key) if key.kind != KeyEventKind::Release => return Ok(None),
+            Event::Resize(columns, rows) => return Ok(Some((columns, rows))),
+            _ => {}
+        }
+    }
+}
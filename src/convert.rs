@@ -0,0 +1,70 @@
+//! Turning a pattern file into another format, without running it
+
+use std::{
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+};
+
+use clap::Args;
+
+use game_of_life::{formats::Format, pattern::Pattern};
+
+use crate::error::Error;
+
+/// Convert a pattern file from one format to another, cell for cell
+#[derive(Args)]
+pub struct Convert {
+    /// Pattern file to read, or `-` for stdin
+    input: PathBuf,
+    /// Pattern file to write, or `-` for stdout
+    output: PathBuf,
+    /// Format of the input, detected from its extension by default
+    #[arg(long, value_enum)]
+    from: Option<Format>,
+    /// Format of the output, detected from its extension by default
+    #[arg(long, value_enum)]
+    to: Option<Format>,
+    /// Crop the pattern to the bounding box of its live cells
+    #[arg(long)]
+    trim: bool,
+}
+
+impl Convert {
+    pub fn run(&self) -> Result<(), Error> {
+        let pattern = if is_standard(&self.input) {
+            let format = self
+                .from
+                .ok_or_else(|| Error::Usage("reading stdin needs --from".to_string()))?;
+            let mut text = String::new();
+            io::stdin()
+                .read_to_string(&mut text)
+                .map_err(|err| Error::Io(format!("failed to read stdin: {err}")))?;
+            format
+                .parse(&text)
+                .map_err(|err| Error::Pattern(format!("stdin: {err}")))?
+        } else {
+            Pattern::load(&self.input, self.from)?
+        };
+        let pattern = if self.trim {
+            pattern.trimmed()
+        } else {
+            pattern
+        };
+
+        if is_standard(&self.output) {
+            let format = self
+                .to
+                .ok_or_else(|| Error::Usage("writing to stdout needs --to".to_string()))?;
+            io::stdout()
+                .write_all(format.write(&pattern).as_bytes())
+                .map_err(|err| Error::Io(format!("failed to write to stdout: {err}")))
+        } else {
+            let format = Format::detect(&self.output, self.to).map_err(Error::Usage)?;
+            pattern.save(&self.output, format).map_err(Error::Io)
+        }
+    }
+}
+
+fn is_standard(path: &Path) -> bool {
+    path == Path::new("-")
+}
@@ -0,0 +1,117 @@
+//! Telling when the generations start repeating
+
+use std::collections::{HashMap, VecDeque};
+
+use crate::engine::Engine;
+
+/// Positions and states of cells that are not dead
+type Cells = Vec<((i64, i64), u8)>;
+
+/// Generations that repeat over and over
+#[derive(Clone, Copy)]
+pub struct Cycle {
+    pub period: u64,
+    /// The first generation of the cycle
+    pub start: u64,
+}
+
+/// Hashes of the cells that are not dead, with their states, of the last generations, to find
+/// one that came before. A repeated hash is only taken for a cycle once the generation a
+/// period later turns out to have the very same cells, in case two generations happen to
+/// hash the same. Under rules taking turns, the same cells only repeat a generation if the
+/// same rule comes next, so the turn is hashed as well.
+pub struct Cycles {
+    /// Generations to look back over, which is the longest period found
+    window: u64,
+    /// Rules taking turns
+    turns: u64,
+    /// Generation each hash in the window was last seen at
+    seen: HashMap<u64, u64>,
+    /// Hashes in the window with their generations, oldest first
+    order: VecDeque<(u64, u64)>,
+    /// Sorted cells of the generation a hash repeated at, with the cycle that would make
+    candidate: Option<(Cells, Cycle)>,
+    found: Option<Cycle>,
+}
+
+impl Cycles {
+    /// Starts out with the generation the engine is at
+    pub fn new(window: u64, turns: u64, generation: u64, engine: &dyn Engine) -> Self {
+        let mut cycles = Self {
+            window,
+            turns,
+            seen: HashMap::new(),
+            order: VecDeque::new(),
+            candidate: None,
+            found: None,
+        };
+        cycles.update(generation, engine);
+        cycles
+    }
+
+    /// Forgets the generations before the one the engine is at, after the cells changed other
+    /// than by computing it
+    pub fn reset(&mut self, generation: u64, engine: &dyn Engine) {
+        self.seen.clear();
+        self.order.clear();
+        self.candidate = None;
+        self.found = None;
+        self.update(generation, engine);
+    }
+
+    pub fn found(&self) -> Option<Cycle> {
+        self.found
+    }
+
+    /// Looks for the generation the engine is at among the ones before
+    pub fn update(&mut self, generation: u64, engine: &dyn Engine) {
+        if self.found.is_some() {
+            return;
+        }
+        let mut cells = engine.cell_states();
+        // Summing up the hashes of the cells doesn't depend on the order they come in
+        let hash = cells.iter().fold(0u64, |hash, &((x, y), state)| {
+            hash.wrapping_add(hash_cell((x ^ i64::from(state) << 48, y)))
+        }) ^ (generation % self.turns).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+
+        if let Some((candidate, cycle)) = &self.candidate {
+            if generation == cycle.start + 2 * cycle.period {
+                cells.sort_unstable();
+                if cells == *candidate {
+                    self.found = Some(*cycle);
+                    return;
+                }
+                self.candidate = None;
+            }
+        }
+        if let (None, Some(&before)) = (&self.candidate, self.seen.get(&hash)) {
+            cells.sort_unstable();
+            let cycle = Cycle {
+                period: generation - before,
+                start: before,
+            };
+            self.candidate = Some((cells, cycle));
+        }
+
+        self.seen.insert(hash, generation);
+        self.order.push_back((hash, generation));
+        while let Some(&(hash, seen)) = self.order.front() {
+            if generation - seen < self.window {
+                break;
+            }
+            self.order.pop_front();
+            // A later generation with the same hash is still in the window
+            if self.seen.get(&hash) == Some(&seen) {
+                self.seen.remove(&hash);
+            }
+        }
+    }
+}
+
+/// Scatters the bits of a position, as SplitMix64 does
+pub(crate) fn hash_cell((x, y): (i64, i64)) -> u64 {
+    let mut z = (x as u64).rotate_left(32) ^ y as u64;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
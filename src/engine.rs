@@ -0,0 +1,479 @@
+//! Ways of storing and evolving the cells
+
+mod hashlife;
+mod packed;
+mod sparse;
+mod turmites;
+
+use std::{
+    mem,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    thread,
+};
+
+use clap::ValueEnum;
+
+pub use hashlife::HashLife;
+pub use packed::Packed;
+pub use sparse::SparseField;
+pub use turmites::Turmites;
+
+use crate::{
+    boundary::{Boundary, Topology},
+    pattern::Pattern,
+    rule::{elementary, table, wireworld, Neighborhood, Rule, ALIVE, DEAD},
+    Anchor, Field,
+};
+
+/// Cells that evolve generation by generation, addressed in the coordinates of the field the
+/// simulation started from
+pub trait Engine {
+    fn state(&self, x: i64, y: i64) -> u8;
+
+    /// Changes a cell between generations. Cells beyond the edges of a bounded field are left
+    /// alone.
+    fn set(&mut self, x: i64, y: i64, state: u8);
+
+    /// Changes the size of a bounded field, keeping the cells at `anchor` where they are along
+    /// with what the last generation changed. An unbounded plane has no size to change.
+    fn resize(&mut self, _width: usize, _height: usize, _anchor: Anchor) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Whether the engine can compute generations under a rule
+    fn runs(&self, _rule: Rule) -> bool {
+        true
+    }
+
+    fn advance(&mut self, rule: Rule, generations: u64);
+
+    /// Counts the cells that are not dead in a rectangle
+    fn population(&self, left: i64, top: i64, width: u64, height: u64) -> u64 {
+        count_cells(self, left, top, width, height)
+    }
+
+    /// The cells that changed in the last generation computed, or over all the generations
+    /// of the last call to `advance` for engines that skip the ones in between
+    fn changes(&self) -> Changes;
+
+    /// Positions of all the live cells, in no particular order
+    fn live_cells(&self) -> Vec<(i64, i64)>;
+
+    /// Positions and states of all the cells that are not dead, in no particular order. Only
+    /// engines of rules with more states than two need to tell dying cells apart.
+    fn cell_states(&self) -> Vec<((i64, i64), u8)> {
+        let live = self.live_cells().into_iter();
+        live.map(|cell| (cell, ALIVE)).collect()
+    }
+
+    /// Captures the live cells, together with the rule they evolve under
+    fn to_pattern(&self, rule: Rule) -> Result<Pattern, String>;
+}
+
+/// Cells that changed, summed up to find their centroid, along with how many were born and
+/// died
+#[derive(Clone, Copy, Default)]
+pub struct Changes {
+    count: u64,
+    x: i128,
+    y: i128,
+    births: u64,
+    deaths: u64,
+}
+
+impl Changes {
+    /// Adds a cell that changed, which `growth` is 1 for if it came to life, -1 if it was
+    /// alive before and 0 otherwise
+    fn add(&mut self, x: i64, y: i64, growth: i64) {
+        self.count += 1;
+        self.x += x as i128;
+        self.y += y as i128;
+        match growth {
+            1 => self.births += 1,
+            -1 => self.deaths += 1,
+            _ => {}
+        }
+    }
+
+    /// The same changes after a resize moved the cells by `dx` and `dy`
+    fn shifted(self, dx: i64, dy: i64) -> Self {
+        let count = self.count as i128;
+        Self {
+            x: self.x + dx as i128 * count,
+            y: self.y + dy as i128 * count,
+            ..self
+        }
+    }
+
+    fn merge(&mut self, other: Self) {
+        self.count += other.count;
+        self.x += other.x;
+        self.y += other.y;
+        self.births += other.births;
+        self.deaths += other.deaths;
+    }
+
+    /// Cells that came to life
+    pub fn births(&self) -> u64 {
+        self.births
+    }
+
+    /// Cells that were alive before and no longer are
+    pub fn deaths(&self) -> u64 {
+        self.deaths
+    }
+
+    /// How many more live cells there are than before
+    pub fn growth(&self) -> i64 {
+        self.births as i64 - self.deaths as i64
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// The cell closest to the centroid, unless nothing changed
+    pub fn centroid(&self) -> Option<(i64, i64)> {
+        let count = self.count as i128;
+        (count > 0).then(|| ((self.x / count) as i64, (self.y / count) as i64))
+    }
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum EngineKind {
+    /// Computes every cell of every generation
+    #[default]
+    Naive,
+    /// Memoizes recurring parts of the plane, which is unbounded, to skip ahead quickly.
+    /// Two-state Moore rules only.
+    Hashlife,
+}
+
+/// A fixed-size field that is computed into a second buffer of the same size every generation
+pub struct Bounded {
+    current: Field,
+    next: Field,
+    threads: usize,
+    /// Set up on the first generation, when the rule and thus the reach of the neighborhood
+    /// is known
+    active: Option<ActiveRows>,
+    /// Buffers for the neighbor counts of Larger than Life rules
+    sums: Vec<u32>,
+    counts: Vec<u32>,
+    changes: Changes,
+}
+
+impl Bounded {
+    /// Computes the generations with `threads` threads, each taking a band of rows
+    pub fn new(current: Field, threads: usize) -> Self {
+        let mut next = Field::new(current.width, current.height);
+        next.boundary = current.boundary;
+        next.topology = current.topology;
+        Self {
+            current,
+            next,
+            threads,
+            active: None,
+            sums: Vec::new(),
+            counts: Vec::new(),
+            changes: Changes::default(),
+        }
+    }
+
+    fn step(&mut self, rule: Rule) {
+        let reach = match rule {
+            Rule::OuterTotalistic(_)
+            | Rule::Wireworld
+            | Rule::Table(_)
+            | Rule::Ant(_)
+            | Rule::Elementary(_) => 1,
+            Rule::LargerThanLife(rule) => rule.radius(),
+        };
+        let current = &self.current;
+        if self
+            .active
+            .as_ref()
+            .is_none_or(|active| active.reach != reach)
+        {
+            self.active = Some(ActiveRows::new(
+                current.width,
+                current.height,
+                current.boundary,
+                current.topology,
+                reach,
+            ));
+        }
+        let active_rows = self.active.as_mut().unwrap();
+        active_rows.update();
+        let active_rows = &*active_rows;
+
+        if let Rule::LargerThanLife(rule) = rule {
+            current.square_counts(rule.radius(), &mut self.sums, &mut self.counts);
+        }
+        let (width, counts) = (current.width, &self.counts);
+        let changes = Mutex::new(Changes::default());
+        in_bands(
+            &mut self.next.cells,
+            width,
+            self.threads,
+            |first_row, rows| {
+                let mut band_changes = Changes::default();
+                for (y, row) in (first_row..).zip(rows.chunks_exact_mut(width)) {
+                    // The row did not change in the previous generation either, so it already
+                    // holds the same cells as the current one
+                    if !active_rows.active[y] {
+                        continue;
+                    }
+                    for (x, cell) in row.iter_mut().enumerate() {
+                        *cell = match rule {
+                            Rule::OuterTotalistic(rule) => current.next(x as i64, y as i64, &rule),
+                            Rule::LargerThanLife(rule) => {
+                                let count = counts[y * width + x];
+                                rule.next(current.state(x as i64, y as i64), count)
+                            }
+                            Rule::Wireworld => {
+                                let (x, y) = (x as i64, y as i64);
+                                let heads = current.live_neighbors(x, y, Neighborhood::Moore);
+                                wireworld::next(current.state(x, y), heads)
+                            }
+                            Rule::Table(rule) => {
+                                let (x, y) = (x as i64, y as i64);
+                                let counts = table::counts(|x, y| current.state(x, y), x, y);
+                                rule.next(current.state(x, y), counts)
+                            }
+                            Rule::Ant(_) => unreachable!("turmites have an engine of their own"),
+                            Rule::Elementary(rule) => elementary::next(
+                                rule,
+                                |x, y| current.state(x, y),
+                                x as i64,
+                                y as i64,
+                                current.height,
+                            ),
+                        };
+                    }
+                    let before = &current.cells[y * width..][..width];
+                    if row != before {
+                        active_rows.mark_changed(y);
+                        for (x, (&after, &before)) in (0..)
+                            .zip(row.iter().zip(before))
+                            .filter(|(_, (a, b))| a != b)
+                        {
+                            let growth = i64::from(after == ALIVE) - i64::from(before == ALIVE);
+                            band_changes.add(x, y as i64, growth);
+                        }
+                    }
+                }
+                changes.lock().unwrap().merge(band_changes);
+            },
+        );
+
+        self.changes = changes.into_inner().unwrap();
+        mem::swap(&mut self.current, &mut self.next);
+    }
+}
+
+/// Keeps track of the rows to compute in a generation: only those whose cells read a row,
+/// their own or one across the boundary, that changed in the previous generation. All the
+/// other ones are bound to stay the same.
+struct ActiveRows {
+    /// How many rows and columns away the neighbors of a cell can be
+    reach: u8,
+    /// The rows read by the cells of each row
+    sources: Vec<Vec<usize>>,
+    /// Rows that changed in the generation being computed, marked from any thread
+    changed: Vec<AtomicBool>,
+    /// Rows to compute in the current generation
+    active: Vec<bool>,
+}
+
+impl ActiveRows {
+    /// Starts out with every row changed, so that the first generation is computed in full
+    fn new(width: usize, height: usize, boundary: Boundary, topology: Topology, reach: u8) -> Self {
+        let (w, h, r) = (width as i64, height as i64, i64::from(reach));
+        let row_of = |x, y| {
+            boundary
+                .resolve(topology, x, y, width, height)
+                .map(|(_, y)| y)
+        };
+        let sources = (0..h)
+            .map(|y| {
+                let mut rows = Vec::new();
+                for y in y - r..=y + r {
+                    let inside = (0..h).contains(&y);
+                    if inside {
+                        rows.push(y as usize);
+                    }
+                    // Within the field only the columns beyond the edges can lead to other
+                    // rows, outside of it any column can
+                    let columns = if inside {
+                        (-r..0).chain(w..w + r)
+                    } else {
+                        (-r..w + r).chain(0..0)
+                    };
+                    rows.extend(columns.filter_map(|x| row_of(x, y)));
+                }
+                rows.sort_unstable();
+                rows.dedup();
+                rows
+            })
+            .collect();
+
+        Self {
+            reach,
+            sources,
+            changed: (0..h).map(|_| AtomicBool::new(true)).collect(),
+            active: vec![false; height],
+        }
+    }
+
+    /// Works out the rows to compute in the next generation, clearing the changes of the
+    /// last one
+    fn update(&mut self) {
+        for (active, rows) in self.active.iter_mut().zip(&self.sources) {
+            *active = rows
+                .iter()
+                .any(|&y| self.changed[y].load(Ordering::Relaxed));
+        }
+        for changed in &mut self.changed {
+            *changed.get_mut() = false;
+        }
+    }
+
+    fn mark_changed(&self, y: usize) {
+        self.changed[y].store(true, Ordering::Relaxed);
+    }
+}
+
+/// Splits `rows`, made of rows of `row_len` items each, into bands of consecutive rows and
+/// fills them in on up to `threads` threads, passing `fill` the index of each band's first
+/// row. The bands are disjoint, so the result is the same for any number of threads.
+fn in_bands<T: Send>(
+    rows: &mut [T],
+    row_len: usize,
+    threads: usize,
+    fill: impl Fn(usize, &mut [T]) + Sync,
+) {
+    let row_count = rows.len() / row_len.max(1);
+    if threads <= 1 || row_count <= 1 {
+        fill(0, rows);
+        return;
+    }
+
+    let band_rows = row_count.div_ceil(threads);
+    thread::scope(|scope| {
+        for (band, rows) in rows.chunks_mut(band_rows * row_len).enumerate() {
+            let fill = &fill;
+            scope.spawn(move || fill(band * band_rows, rows));
+        }
+    });
+}
+
+impl Engine for Bounded {
+    fn state(&self, x: i64, y: i64) -> u8 {
+        self.current.state(x, y)
+    }
+
+    fn set(&mut self, x: i64, y: i64, state: u8) {
+        let (Ok(x), Ok(y)) = (usize::try_from(x), usize::try_from(y)) else {
+            return;
+        };
+        if self.current.set_state(x, y, state).is_ok() {
+            if let Some(active) = &self.active {
+                active.mark_changed(y);
+            }
+        }
+    }
+
+    fn advance(&mut self, rule: Rule, generations: u64) {
+        for _ in 0..generations {
+            self.step(rule);
+        }
+    }
+
+    fn resize(&mut self, width: usize, height: usize, anchor: Anchor) -> Result<(), String> {
+        let (dx, dy) = anchor.offset(
+            (self.current.width(), self.current.height()),
+            (width, height),
+        );
+        self.current.resize(width, height, anchor)?;
+        let current = mem::replace(&mut self.current, Field::new(0, 0));
+        // What the last generation changed still tells whether the cells are stable
+        let changes = self.changes.shifted(dx, dy);
+        *self = Self::new(current, self.threads);
+        self.changes = changes;
+        Ok(())
+    }
+
+    fn changes(&self) -> Changes {
+        self.changes
+    }
+
+    fn live_cells(&self) -> Vec<(i64, i64)> {
+        let width = self.current.width.max(1);
+        let cells = self.current.cells.iter().enumerate();
+        cells
+            .filter(|&(_, &state)| state == ALIVE)
+            .map(|(i, _)| ((i % width) as i64, (i / width) as i64))
+            .collect()
+    }
+
+    fn cell_states(&self) -> Vec<((i64, i64), u8)> {
+        let width = self.current.width.max(1);
+        let cells = self.current.cells.iter().enumerate();
+        cells
+            .filter(|&(_, &state)| state != DEAD)
+            .map(|(i, &state)| (((i % width) as i64, (i / width) as i64), state))
+            .collect()
+    }
+
+    fn to_pattern(&self, rule: Rule) -> Result<Pattern, String> {
+        Ok(Pattern::from_field(&self.current, rule))
+    }
+}
+
+/// Counts the cells that are not dead in a rectangle one by one
+fn count_cells<E: Engine + ?Sized>(
+    engine: &E,
+    left: i64,
+    top: i64,
+    width: u64,
+    height: u64,
+) -> u64 {
+    let (right, bottom) = (left + width as i64, top + height as i64);
+    (top..bottom)
+        .flat_map(|y| (left..right).map(move |x| (x, y)))
+        .filter(|&(x, y)| engine.state(x, y) != DEAD)
+        .count() as u64
+}
+
+/// Builds a pattern out of the bounding box of live cells anywhere on the plane
+fn crop(live: Vec<(i64, i64)>, rule: Rule) -> Result<Pattern, String> {
+    let left = live.iter().map(|&(x, _)| x).min().unwrap_or(0);
+    let top = live.iter().map(|&(_, y)| y).min().unwrap_or(0);
+    let right = live.iter().map(|&(x, _)| x + 1).max().unwrap_or(0);
+    let bottom = live.iter().map(|&(_, y)| y + 1).max().unwrap_or(0);
+    let (Ok(width), Ok(height)) = (usize::try_from(right - left), usize::try_from(bottom - top))
+    else {
+        return Err(format!(
+            "the pattern has grown to {}x{} cells, too large to be saved",
+            right - left,
+            bottom - top
+        ));
+    };
+
+    let mut cells: Vec<(usize, usize)> = live
+        .into_iter()
+        .map(|(x, y)| ((x - left) as usize, (y - top) as usize))
+        .collect();
+    cells.sort_unstable_by_key(|&(x, y)| (y, x));
+    Ok(Pattern {
+        width,
+        height,
+        cells,
+        rule: Some(rule),
+        comments: Vec::new(),
+    })
+}
@@ -0,0 +1,451 @@
+//! HashLife: the plane as a quadtree whose identical nodes are shared and remember their
+//! futures, which lets regular patterns run for huge numbers of generations
+
+use std::{collections::HashMap, mem};
+
+use super::{Changes, Engine};
+use crate::{
+    pattern::Pattern,
+    rule::{OuterTotalistic, Rule, ALIVE, DEAD},
+    Field,
+};
+
+/// Number of nodes after which the ones no longer reachable from the root are dropped
+const NODE_LIMIT: usize = 1 << 22;
+
+struct Node {
+    /// Indices of the north-west, north-east, south-west and south-east quadrants
+    children: [u32; 4],
+    /// The node is a square of `2^level` cells on a side
+    level: u8,
+    population: u64,
+}
+
+pub struct HashLife {
+    /// Dead and live single cells first, then every node made out of them so far
+    nodes: Vec<Node>,
+    ids: HashMap<[u32; 4], u32>,
+    /// Centers of nodes advanced by `2^j` generations, keyed by the node and `j`
+    results: HashMap<(u32, u8), u32>,
+    /// Empty nodes by level
+    empty: Vec<u32>,
+    /// Square centered on the origin of the plane
+    root: u32,
+    /// The root before the last call to `advance`, of the same size as the root, to tell
+    /// which cells changed
+    previous: u32,
+    rule: OuterTotalistic,
+}
+
+impl HashLife {
+    /// Takes over the live cells of a field, which becomes the part of the plane at the origin.
+    /// Only two-state rules in the Moore neighborhood are supported.
+    pub fn from_field(field: &Field, rule: Rule) -> Result<Self, String> {
+        let rule = match rule {
+            Rule::OuterTotalistic(rule) if Rule::OuterTotalistic(rule).is_life_like() => rule,
+            _ => {
+                return Err(
+                    "HashLife only runs two-state rules in the Moore neighborhood".to_string(),
+                )
+            }
+        };
+
+        let mut hashlife = Self {
+            nodes: Vec::new(),
+            ids: HashMap::new(),
+            results: HashMap::new(),
+            empty: Vec::new(),
+            root: 0,
+            previous: 0,
+            rule,
+        };
+        hashlife.add_leaves();
+        // The root spans twice the field in each direction, since it is centered on the
+        // origin at the field's top-left corner
+        let size = field.width.max(field.height).max(1) as u64;
+        let level = (size.next_power_of_two().trailing_zeros() as u8 + 1).max(3);
+        let half = 1i64 << (level - 1);
+        hashlife.root = hashlife.build(field, level, -half, -half);
+        hashlife.previous = hashlife.root;
+        Ok(hashlife)
+    }
+
+    fn add_leaves(&mut self) {
+        for population in [0, 1] {
+            self.nodes.push(Node {
+                children: [0; 4],
+                level: 0,
+                population,
+            });
+        }
+        self.empty.push(0);
+    }
+
+    fn build(&mut self, field: &Field, level: u8, x: i64, y: i64) -> u32 {
+        let size = 1i64 << level;
+        let (width, height) = (field.width as i64, field.height as i64);
+        if x >= width || y >= height || x + size <= 0 || y + size <= 0 {
+            return self.empty(level);
+        }
+        if level == 0 {
+            return u32::from(field.cells[(y * width + x) as usize] == ALIVE);
+        }
+
+        let half = size / 2;
+        let nw = self.build(field, level - 1, x, y);
+        let ne = self.build(field, level - 1, x + half, y);
+        let sw = self.build(field, level - 1, x, y + half);
+        let se = self.build(field, level - 1, x + half, y + half);
+        self.join([nw, ne, sw, se])
+    }
+
+    fn join(&mut self, children: [u32; 4]) -> u32 {
+        if let Some(&id) = self.ids.get(&children) {
+            return id;
+        }
+        let id = self.nodes.len() as u32;
+        self.nodes.push(Node {
+            children,
+            level: self.nodes[children[0] as usize].level + 1,
+            population: children
+                .iter()
+                .map(|&child| self.nodes[child as usize].population)
+                .sum(),
+        });
+        self.ids.insert(children, id);
+        id
+    }
+
+    fn empty(&mut self, level: u8) -> u32 {
+        while self.empty.len() <= level as usize {
+            let empty = *self.empty.last().unwrap();
+            let bigger = self.join([empty; 4]);
+            self.empty.push(bigger);
+        }
+        self.empty[level as usize]
+    }
+
+    fn children(&self, node: u32) -> [u32; 4] {
+        self.nodes[node as usize].children
+    }
+
+    /// The square half the size of `node` at its center
+    fn centered(&mut self, node: u32) -> u32 {
+        let [nw, ne, sw, se] = self.children(node);
+        self.join([
+            self.children(nw)[3],
+            self.children(ne)[2],
+            self.children(sw)[1],
+            self.children(se)[0],
+        ])
+    }
+
+    /// Doubles the root's size, keeping it centered on the origin, and that of the previous
+    /// root along with it
+    fn expand(&mut self) {
+        self.root = self.expanded(self.root);
+        self.previous = self.expanded(self.previous);
+    }
+
+    /// A square twice the size of `node` with it at the center
+    fn expanded(&mut self, node: u32) -> u32 {
+        let [nw, ne, sw, se] = self.children(node);
+        let empty = self.empty(self.nodes[nw as usize].level);
+        let children = [
+            self.join([empty, empty, empty, nw]),
+            self.join([empty, empty, ne, empty]),
+            self.join([empty, sw, empty, empty]),
+            self.join([se, empty, empty, empty]),
+        ];
+        self.join(children)
+    }
+
+    /// Adds the cells that differ between `a` and `b`, squares of `2^level` cells on a side
+    /// with their top left corner at (x, y), skipping the parts they share
+    fn diff(&self, [a, b]: [u32; 2], level: u8, (x, y): (i64, i64), changes: &mut Changes) {
+        if a == b {
+            return;
+        }
+        if level == 0 {
+            // Leaves are 0 for a dead cell and 1 for a live one
+            changes.add(x, y, b as i64 - a as i64);
+            return;
+        }
+        let half = 1i64 << (level - 1);
+        let (a, b) = (self.children(a), self.children(b));
+        for quadrant in 0..4 {
+            let (dx, dy) = (quadrant as i64 % 2 * half, quadrant as i64 / 2 * half);
+            let pair = [a[quadrant], b[quadrant]];
+            self.diff(pair, level - 1, (x + dx, y + dy), changes);
+        }
+    }
+
+    /// Counts the live cells of `node`, a square of `2^level` cells on a side with its top
+    /// left corner at (x, y), that lie in the rectangle from (left, top) up to (right, bottom)
+    fn count(&self, node: u32, level: u8, (x, y): (i64, i64), rect: [i64; 4]) -> u64 {
+        let [left, top, right, bottom] = rect;
+        let size = 1i64 << level;
+        let population = self.nodes[node as usize].population;
+        if population == 0 || x >= right || y >= bottom || x + size <= left || y + size <= top {
+            return 0;
+        }
+        if left <= x && top <= y && x + size <= right && y + size <= bottom {
+            return population;
+        }
+        let half = size / 2;
+        let [nw, ne, sw, se] = self.children(node);
+        [(nw, 0, 0), (ne, half, 0), (sw, 0, half), (se, half, half)]
+            .into_iter()
+            .map(|(child, dx, dy)| self.count(child, level - 1, (x + dx, y + dy), rect))
+            .sum()
+    }
+
+    /// `node`, a square of `2^level` cells, with the cell at (x, y) inside of it set to
+    /// `state`
+    fn with_cell(&mut self, node: u32, level: u8, x: u64, y: u64, state: u8) -> u32 {
+        if level == 0 {
+            return u32::from(state == ALIVE);
+        }
+        let mut children = self.children(node);
+        let quadrant = ((y >> (level - 1) & 1) * 2 + (x >> (level - 1) & 1)) as usize;
+        children[quadrant] = self.with_cell(children[quadrant], level - 1, x, y, state);
+        self.join(children)
+    }
+
+    /// The center half of `node` after `2^j` generations, where `j` is at most the node's
+    /// level minus 2
+    fn result(&mut self, node: u32, j: u8) -> u32 {
+        if let Some(&result) = self.results.get(&(node, j)) {
+            return result;
+        }
+
+        let level = self.nodes[node as usize].level;
+        let result = if self.nodes[node as usize].population == 0 {
+            self.empty(level - 1)
+        } else if level == 2 {
+            self.step_4x4(node)
+        } else {
+            let [nw, ne, sw, se] = self.children(node);
+            let [_, nw_ne, nw_sw, nw_se] = self.children(nw);
+            let [ne_nw, _, ne_sw, ne_se] = self.children(ne);
+            let [sw_nw, sw_ne, _, sw_se] = self.children(sw);
+            let [se_nw, se_ne, se_sw, _] = self.children(se);
+            // Nine overlapping squares of half the size, in rows
+            let squares = [
+                nw,
+                self.join([nw_ne, ne_nw, nw_se, ne_sw]),
+                ne,
+                self.join([nw_sw, nw_se, sw_nw, sw_ne]),
+                self.join([nw_se, ne_sw, sw_ne, se_nw]),
+                self.join([ne_sw, ne_se, se_nw, se_ne]),
+                sw,
+                self.join([sw_ne, se_nw, sw_se, se_sw]),
+                se,
+            ];
+
+            // The largest possible step is taken in two halves, smaller ones only advance
+            // in the second phase
+            let full = j == level - 2;
+            let mut centers = [0; 9];
+            for (center, square) in centers.iter_mut().zip(squares) {
+                *center = if full {
+                    self.result(square, j - 1)
+                } else {
+                    self.centered(square)
+                };
+            }
+            let j = if full { j - 1 } else { j };
+            let mut quadrants = [0; 4];
+            for (quadrant, [x, y]) in quadrants.iter_mut().zip([[0, 0], [1, 0], [0, 1], [1, 1]]) {
+                let at = |dx: usize, dy: usize| centers[(y + dy) * 3 + x + dx];
+                let square = self.join([at(0, 0), at(1, 0), at(0, 1), at(1, 1)]);
+                *quadrant = self.result(square, j);
+            }
+            self.join(quadrants)
+        };
+
+        self.results.insert((node, j), result);
+        result
+    }
+
+    /// Whether the root can be advanced by `2^j` generations without losing any cells: it is
+    /// big enough, and the live cells are all within its center quarter
+    fn fits_step(&mut self, j: u8) -> bool {
+        if self.nodes[self.root as usize].level < j + 3 {
+            return false;
+        }
+        let center = self.centered(self.root);
+        let center = self.centered(center);
+        self.nodes[center as usize].population == self.nodes[self.root as usize].population
+    }
+
+    /// Advances the center 2x2 cells of a 4x4 node by a single generation
+    fn step_4x4(&mut self, node: u32) -> u32 {
+        let mut cells = [[DEAD; 4]; 4];
+        for (quadrant, child) in self.children(node).into_iter().enumerate() {
+            for (leaf, cell) in self.children(child).into_iter().enumerate() {
+                let x = quadrant % 2 * 2 + leaf % 2;
+                let y = quadrant / 2 * 2 + leaf / 2;
+                cells[y][x] = cell as u8;
+            }
+        }
+
+        let mut next = [0; 4];
+        for (leaf, next) in next.iter_mut().enumerate() {
+            let (x, y) = (1 + leaf % 2, 1 + leaf / 2);
+            let neighbors = cells[y - 1..=y + 1]
+                .iter()
+                .map(|row| row[x - 1..=x + 1].iter().sum::<u8>())
+                .sum::<u8>()
+                - cells[y][x];
+            *next = u32::from(self.rule.next(cells[y][x], neighbors));
+        }
+        self.join(next)
+    }
+
+    /// Copies the tree under the root into fresh storage, dropping everything else
+    fn collect_garbage(&mut self) {
+        let mut old = Self {
+            nodes: Vec::new(),
+            ids: HashMap::new(),
+            results: HashMap::new(),
+            empty: Vec::new(),
+            root: 0,
+            previous: 0,
+            rule: self.rule,
+        };
+        old.add_leaves();
+        mem::swap(self, &mut old);
+        let mut copies = HashMap::new();
+        self.root = self.copy(&old, old.root, &mut copies);
+        self.previous = self.copy(&old, old.previous, &mut copies);
+    }
+
+    fn copy(&mut self, old: &Self, node: u32, copies: &mut HashMap<u32, u32>) -> u32 {
+        if node < 2 {
+            return node;
+        }
+        if let Some(&copy) = copies.get(&node) {
+            return copy;
+        }
+        let children = old
+            .children(node)
+            .map(|child| self.copy(old, child, copies));
+        let copy = self.join(children);
+        copies.insert(node, copy);
+        copy
+    }
+
+    fn collect_live(&self, node: u32, x: i64, y: i64, live: &mut Vec<(i64, i64)>) {
+        let Node {
+            children,
+            level,
+            population,
+        } = self.nodes[node as usize];
+        match (level, population) {
+            (_, 0) => {}
+            (0, _) => live.push((x, y)),
+            _ => {
+                let half = 1i64 << (level - 1);
+                for (quadrant, child) in children.into_iter().enumerate() {
+                    let (dx, dy) = (quadrant as i64 % 2 * half, quadrant as i64 / 2 * half);
+                    self.collect_live(child, x + dx, y + dy, live);
+                }
+            }
+        }
+    }
+}
+
+impl Engine for HashLife {
+    fn state(&self, x: i64, y: i64) -> u8 {
+        let level = self.nodes[self.root as usize].level;
+        let half = 1i64 << (level - 1);
+        let (Ok(x), Ok(y)) = (u64::try_from(x + half), u64::try_from(y + half)) else {
+            return DEAD;
+        };
+        if x >= 1 << level || y >= 1 << level {
+            return DEAD;
+        }
+
+        let mut node = self.root;
+        for level in (0..level).rev() {
+            let quadrant = (y >> level & 1) * 2 + (x >> level & 1);
+            node = self.children(node)[quadrant as usize];
+        }
+        node as u8
+    }
+
+    fn population(&self, left: i64, top: i64, width: u64, height: u64) -> u64 {
+        let level = self.nodes[self.root as usize].level;
+        let half = 1i64 << (level - 1);
+        let rect = [left, top, left + width as i64, top + height as i64];
+        self.count(self.root, level, (-half, -half), rect)
+    }
+
+    fn set(&mut self, x: i64, y: i64, state: u8) {
+        loop {
+            let level = self.nodes[self.root as usize].level;
+            let half = 1i64 << (level - 1);
+            if (-half..half).contains(&x) && (-half..half).contains(&y) {
+                let (x, y) = ((x + half) as u64, (y + half) as u64);
+                self.root = self.with_cell(self.root, level, x, y, state);
+                return;
+            }
+            self.expand();
+        }
+    }
+
+    /// Splits the generations into powers of two and takes each as a single step
+    fn runs(&self, rule: Rule) -> bool {
+        rule.is_life_like()
+    }
+
+    fn advance(&mut self, rule: Rule, generations: u64) {
+        if let Rule::OuterTotalistic(rule) = rule {
+            if rule != self.rule {
+                self.rule = rule;
+                self.results.clear();
+            }
+        }
+
+        self.previous = self.root;
+        for j in (0..64u8).filter(|j| generations >> j & 1 != 0) {
+            // After the step only the center half of the root is left, so the live cells
+            // must be far enough inside for their growth of up to a cell per generation
+            while !self.fits_step(j) {
+                self.expand();
+            }
+            self.root = self.result(self.root, j);
+            // Which loses none of the cells, since they fit the step
+            self.previous = self.centered(self.previous);
+        }
+
+        if self.nodes.len() > NODE_LIMIT {
+            self.collect_garbage();
+        }
+    }
+
+    fn changes(&self) -> Changes {
+        let level = self.nodes[self.root as usize].level;
+        let half = 1i64 << (level - 1);
+        let mut changes = Changes::default();
+        self.diff(
+            [self.previous, self.root],
+            level,
+            (-half, -half),
+            &mut changes,
+        );
+        changes
+    }
+
+    fn live_cells(&self) -> Vec<(i64, i64)> {
+        let mut live = Vec::new();
+        let half = 1i64 << (self.nodes[self.root as usize].level - 1);
+        self.collect_live(self.root, -half, -half, &mut live);
+        live
+    }
+
+    fn to_pattern(&self, rule: Rule) -> Result<Pattern, String> {
+        super::crop(self.live_cells(), rule)
+    }
+}
@@ -0,0 +1,324 @@
+//! Two-state Moore rules on a field stored as a bit per cell, computing 64 cells at a time
+
+use std::{mem, sync::Mutex};
+
+use super::{ActiveRows, Changes, Engine};
+use crate::{
+    boundary::{Boundary, Topology},
+    pattern::Pattern,
+    rule::{Rule, ALIVE, DEAD},
+    Anchor, Field,
+};
+
+pub struct Packed {
+    /// Bit `x % 64` of word `y * words + x / 64` is set if the cell at (x, y) is alive
+    cells: Vec<u64>,
+    /// Words per row
+    words: usize,
+    width: usize,
+    height: usize,
+    boundary: Boundary,
+    topology: Topology,
+    threads: usize,
+    active: ActiveRows,
+    /// The rows above, on and below the field, each with a cell from across the boundary on
+    /// either side, so that bit `x + 1` is the cell in column `x`. Kept between generations
+    /// to reuse the allocation.
+    extended: Vec<u64>,
+    changes: Changes,
+}
+
+impl Packed {
+    /// Takes over the live cells and the boundary of a field, computing the generations with
+    /// `threads` threads
+    pub fn from_field(field: &Field, threads: usize) -> Self {
+        let words = field.width.div_ceil(64);
+        let mut cells = vec![0; words * field.height];
+        for (i, &state) in field.cells.iter().enumerate() {
+            let (x, y) = (i % field.width, i / field.width);
+            if state == ALIVE {
+                cells[y * words + x / 64] |= 1 << (x % 64);
+            }
+        }
+
+        Self {
+            cells,
+            words,
+            width: field.width,
+            height: field.height,
+            boundary: field.boundary,
+            topology: field.topology,
+            threads,
+            active: ActiveRows::new(field.width, field.height, field.boundary, field.topology, 1),
+            extended: Vec::new(),
+            changes: Changes::default(),
+        }
+    }
+
+    fn is_alive(&self, x: usize, y: usize) -> bool {
+        self.cells[y * self.words + x / 64] >> (x % 64) & 1 != 0
+    }
+
+    /// Whether the cell at possibly out of range coordinates is alive, looking across the
+    /// boundary
+    fn resolve(&self, x: i64, y: i64) -> bool {
+        self.boundary
+            .resolve(self.topology, x, y, self.width, self.height)
+            .is_some_and(|(x, y)| self.is_alive(x, y))
+    }
+
+    /// Fills in the rows of `self.extended` read by the active rows of the field, the other
+    /// ones being unchanged since they were last filled in
+    fn extend_rows(&mut self, stride: usize) {
+        let active = &self.active.active;
+        let (width, height) = (self.width as i64, self.height as i64);
+        let mut extended = mem::take(&mut self.extended);
+        extended.resize(stride * (self.height + 2), 0);
+
+        let rows = extended.chunks_exact_mut(stride).zip(-1..=height);
+        for (extended_y, (row, y)) in rows.enumerate() {
+            // Field rows read the extended rows from the one above to the one below them
+            let read =
+                (extended_y.saturating_sub(2)..=extended_y).any(|y| active.get(y) == Some(&true));
+            if !read {
+                continue;
+            }
+            row.fill(0);
+            if (0..height).contains(&y) {
+                // Shifting the row one column over makes room for the cell on the left
+                let cells = &self.cells[y as usize * self.words..][..self.words];
+                let mut carry = 0;
+                for (extended, &word) in row.iter_mut().zip(cells) {
+                    *extended = word << 1 | carry;
+                    carry = word >> 63;
+                }
+                row[self.words] |= carry;
+                row[0] |= u64::from(self.resolve(-1, y));
+                let right = self.width + 1;
+                row[right / 64] |= u64::from(self.resolve(width, y)) << (right % 64);
+            } else {
+                // The rows beyond the top and bottom may be mirrored or missing altogether
+                for x in -1..=width {
+                    let bit = (x + 1) as usize;
+                    row[bit / 64] |= u64::from(self.resolve(x, y)) << (bit % 64);
+                }
+            }
+        }
+        self.extended = extended;
+    }
+
+    /// Counts the neighbors of 64 cells at once with bit-sliced adders, then applies the rule
+    /// to all of them
+    fn step(&mut self, rule: Rule) {
+        let Rule::OuterTotalistic(rule) = rule else {
+            unreachable!("packed fields only run Life-like rules");
+        };
+        // Bit n is set if the state follows from n live neighbors
+        let counts = |state| {
+            (0..=8)
+                .filter(|&n| rule.next(state, n) == ALIVE)
+                .fold(0u16, |counts, n| counts | 1 << n)
+        };
+        let (births, survivals) = (counts(DEAD), counts(ALIVE));
+
+        self.active.update();
+        // One spare word so that reading a word shifted into the next one never runs out
+        let stride = (self.width + 2).div_ceil(64) + 1;
+        self.extend_rows(stride);
+        let last_word_mask = match self.width % 64 {
+            0 => !0,
+            bits => (1 << bits) - 1,
+        };
+
+        let (words, extended, active_rows) = (self.words, &self.extended, &self.active);
+        let changes = Mutex::new(Changes::default());
+        super::in_bands(&mut self.cells, words, self.threads, |first_row, rows| {
+            let mut band_changes = Changes::default();
+            for (y, cells) in (first_row..).zip(rows.chunks_exact_mut(words)) {
+                if !active_rows.active[y] {
+                    continue;
+                }
+                let rows = &extended[y * stride..][..3 * stride];
+                let (above, rest) = rows.split_at(stride);
+                let (row, below) = rest.split_at(stride);
+                for (k, cell) in cells.iter_mut().enumerate() {
+                    // Bit x of a shift by s is the cell in column x + s - 1, for s from 0 to 2
+                    let shifted = |row: &[u64], s: u32| match s {
+                        0 => row[k],
+                        s => row[k] >> s | row[k + 1] << (64 - s),
+                    };
+                    // planes[i] holds bit i of the neighbor count of each cell
+                    let mut planes = [0u64; 4];
+                    let neighbors = [
+                        shifted(above, 0),
+                        shifted(above, 1),
+                        shifted(above, 2),
+                        shifted(row, 0),
+                        shifted(row, 2),
+                        shifted(below, 0),
+                        shifted(below, 1),
+                        shifted(below, 2),
+                    ];
+                    for neighbor in neighbors {
+                        let mut carry = neighbor;
+                        for plane in &mut planes {
+                            let overflow = *plane & carry;
+                            *plane ^= carry;
+                            carry = overflow;
+                        }
+                    }
+
+                    let count_is = |n: u32| {
+                        (0..4).fold(!0, |cells, bit| match n >> bit & 1 {
+                            0 => cells & !planes[bit],
+                            _ => cells & planes[bit],
+                        })
+                    };
+                    let any_of = |counts: u16| {
+                        (0..=8)
+                            .filter(|n| counts >> n & 1 != 0)
+                            .fold(0, |cells, n| cells | count_is(n))
+                    };
+                    let alive = shifted(row, 1);
+                    let mut next = alive & any_of(survivals) | !alive & any_of(births);
+                    if k == words - 1 {
+                        next &= last_word_mask;
+                    }
+                    if *cell != next {
+                        active_rows.mark_changed(y);
+                        let mut changed = *cell ^ next;
+                        while changed != 0 {
+                            let bit = changed.trailing_zeros();
+                            let x = k * 64 + bit as usize;
+                            let growth = if next >> bit & 1 != 0 { 1 } else { -1 };
+                            band_changes.add(x as i64, y as i64, growth);
+                            changed &= changed - 1;
+                        }
+                    }
+                    *cell = next;
+                }
+            }
+            changes.lock().unwrap().merge(band_changes);
+        });
+        self.changes = changes.into_inner().unwrap();
+    }
+}
+
+impl Engine for Packed {
+    fn state(&self, x: i64, y: i64) -> u8 {
+        if self.resolve(x, y) {
+            ALIVE
+        } else {
+            DEAD
+        }
+    }
+
+    fn set(&mut self, x: i64, y: i64, state: u8) {
+        let (Ok(x), Ok(y)) = (usize::try_from(x), usize::try_from(y)) else {
+            return;
+        };
+        if x >= self.width || y >= self.height {
+            return;
+        }
+        let (bit, word) = (x % 64, y * self.words + x / 64);
+        self.cells[word] = self.cells[word] & !(1 << bit) | u64::from(state == ALIVE) << bit;
+        self.active.mark_changed(y);
+    }
+
+    /// Counts the bits of whole words at a time within the field, and cell by cell across
+    /// the boundary
+    fn population(&self, left: i64, top: i64, width: u64, height: u64) -> u64 {
+        let (right, bottom) = (left + width as i64, top + height as i64);
+        let inside =
+            left >= 0 && top >= 0 && right <= self.width as i64 && bottom <= self.height as i64;
+        if !inside {
+            return super::count_cells(self, left, top, width, height);
+        }
+
+        let (left, right) = (left as usize, right as usize);
+        let mut count = 0;
+        for y in top as usize..bottom as usize {
+            let row = &self.cells[y * self.words..][..self.words];
+            for (k, &word) in row
+                .iter()
+                .enumerate()
+                .take(right.div_ceil(64))
+                .skip(left / 64)
+            {
+                let (from, to) = (left.max(k * 64) - k * 64, right.min(k * 64 + 64) - k * 64);
+                let mask = match to - from {
+                    64 => !0,
+                    bits => ((1u64 << bits) - 1) << from,
+                };
+                count += (word & mask).count_ones() as u64;
+            }
+        }
+        count
+    }
+
+    fn runs(&self, rule: Rule) -> bool {
+        rule.is_life_like()
+    }
+
+    fn advance(&mut self, rule: Rule, generations: u64) {
+        for _ in 0..generations {
+            self.step(rule);
+        }
+    }
+
+    fn resize(&mut self, width: usize, height: usize, anchor: Anchor) -> Result<(), String> {
+        let mut field = Field::new(self.width, self.height);
+        field.boundary = self.boundary;
+        field.topology = self.topology;
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if self.is_alive(x, y) {
+                    let _ = field.set_state(x, y, ALIVE);
+                }
+            }
+        }
+        field.resize(width, height, anchor)?;
+        // What the last generation changed still tells whether the cells are stable
+        let (dx, dy) = anchor.offset((self.width, self.height), (width, height));
+        let changes = self.changes.shifted(dx, dy);
+        *self = Self::from_field(&field, self.threads);
+        self.changes = changes;
+        Ok(())
+    }
+
+    fn changes(&self) -> Changes {
+        self.changes
+    }
+
+    fn live_cells(&self) -> Vec<(i64, i64)> {
+        let mut live = Vec::new();
+        for (y, row) in self.cells.chunks_exact(self.words.max(1)).enumerate() {
+            for (k, &word) in row.iter().enumerate() {
+                let mut word = word;
+                while word != 0 {
+                    live.push(((k * 64) as i64 + word.trailing_zeros() as i64, y as i64));
+                    word &= word - 1;
+                }
+            }
+        }
+        live
+    }
+
+    fn to_pattern(&self, rule: Rule) -> Result<Pattern, String> {
+        let mut cells = Vec::new();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if self.is_alive(x, y) {
+                    cells.push((x, y));
+                }
+            }
+        }
+        Ok(Pattern {
+            width: self.width,
+            height: self.height,
+            cells,
+            rule: Some(rule),
+            comments: Vec::new(),
+        })
+    }
+}
@@ -0,0 +1,175 @@
+//! An unbounded plane that only stores the cells that are not dead
+
+use std::{
+    collections::{HashMap, HashSet},
+    mem,
+};
+
+use super::{Changes, Engine};
+use crate::{
+    pattern::Pattern,
+    rule::{table, wireworld, Rule, RuleTable, ALIVE, DEAD},
+    Field,
+};
+
+pub struct SparseField {
+    /// States of all the cells that are not dead. Dying cells of Generations rules need their
+    /// state as well, so this is a map rather than a set of the live cells.
+    cells: HashMap<(i64, i64), u8>,
+    /// The cells of the generation before, to tell which ones changed
+    previous: HashMap<(i64, i64), u8>,
+}
+
+impl SparseField {
+    /// Takes over the cells of a field, which becomes the part of the plane at the origin
+    pub fn from_field(field: &Field) -> Self {
+        let mut cells = HashMap::new();
+        for (i, &state) in field.cells.iter().enumerate() {
+            let (x, y) = (i % field.width, i / field.width);
+            if state != DEAD {
+                cells.insert((x as i64, y as i64), state);
+            }
+        }
+        Self {
+            cells,
+            previous: HashMap::new(),
+        }
+    }
+
+    /// Only visits the cells around live ones, relying on the rule not giving birth to cells
+    /// without live neighbors
+    fn step(&mut self, rule: Rule) {
+        if let Rule::Table(table) = rule {
+            self.step_table(table);
+            return;
+        }
+        // Each live cell adds itself to the counts of the cells it is a neighbor of
+        let mut counts = HashMap::<(i64, i64), u32>::new();
+        let live = self.cells.iter().filter(|&(_, &state)| state == ALIVE);
+        for (&(x, y), _) in live {
+            match rule {
+                Rule::OuterTotalistic(_) | Rule::Wireworld => {
+                    // Neighborhoods are symmetric, so the cells this one counts as neighbors
+                    // are the ones that count it
+                    for &[i, j] in rule.neighborhood().offsets(y.rem_euclid(2) == 1) {
+                        *counts.entry((x + i, y + j)).or_default() += 1;
+                    }
+                }
+                Rule::Table(_) => unreachable!("computed apart"),
+                Rule::Ant(_) => unreachable!("turmites have an engine of their own"),
+                Rule::Elementary(_) => unreachable!("elementary automata need a bounded field"),
+                Rule::LargerThanLife(rule) => {
+                    let r = rule.radius() as i64;
+                    for j in -r..=r {
+                        for i in -r..=r {
+                            *counts.entry((x + i, y + j)).or_default() += 1;
+                        }
+                    }
+                }
+            }
+        }
+
+        let next_state = |state, count| match rule {
+            Rule::OuterTotalistic(rule) => rule.next(state, count as u8),
+            Rule::LargerThanLife(rule) => rule.next(state, count),
+            Rule::Wireworld => wireworld::next(state, count as u8),
+            Rule::Table(_) => unreachable!("computed apart"),
+            Rule::Ant(_) => unreachable!("turmites have an engine of their own"),
+            Rule::Elementary(_) => unreachable!("elementary automata need a bounded field"),
+        };
+        let mut next = mem::take(&mut self.previous);
+        next.clear();
+        for (&cell, &count) in &counts {
+            let state = next_state(self.state(cell.0, cell.1), count);
+            if state != DEAD {
+                next.insert(cell, state);
+            }
+        }
+        // Cells not next to any live one, which can still survive or be dying
+        for (&cell, &state) in &self.cells {
+            if !counts.contains_key(&cell) {
+                let state = next_state(state, 0);
+                if state != DEAD {
+                    next.insert(cell, state);
+                }
+            }
+        }
+        self.previous = mem::replace(&mut self.cells, next);
+    }
+
+    /// Rule tables read the states of all the neighbors rather than counting the live ones,
+    /// so that the cells around any that are not dead are visited
+    fn step_table(&mut self, table: &RuleTable) {
+        let mut next = mem::take(&mut self.previous);
+        next.clear();
+        let mut visited = HashSet::new();
+        for &(x, y) in self.cells.keys() {
+            for (i, j) in (-1..=1).flat_map(|j| (-1..=1).map(move |i| (i, j))) {
+                let (x, y) = (x + i, y + j);
+                if !visited.insert((x, y)) {
+                    continue;
+                }
+                let counts = table::counts(|x, y| self.state(x, y), x, y);
+                let state = table.next(self.state(x, y), counts);
+                if state != DEAD {
+                    next.insert((x, y), state);
+                }
+            }
+        }
+        self.previous = mem::replace(&mut self.cells, next);
+    }
+}
+
+impl Engine for SparseField {
+    fn state(&self, x: i64, y: i64) -> u8 {
+        self.cells.get(&(x, y)).copied().unwrap_or(DEAD)
+    }
+
+    fn set(&mut self, x: i64, y: i64, state: u8) {
+        if state == DEAD {
+            self.cells.remove(&(x, y));
+        } else {
+            self.cells.insert((x, y), state);
+        }
+    }
+
+    fn advance(&mut self, rule: Rule, generations: u64) {
+        for _ in 0..generations {
+            self.step(rule);
+        }
+    }
+
+    fn changes(&self) -> Changes {
+        let mut changes = Changes::default();
+        for (&(x, y), &state) in &self.cells {
+            let before = self.previous.get(&(x, y));
+            if before != Some(&state) {
+                let growth = i64::from(state == ALIVE) - i64::from(before == Some(&ALIVE));
+                changes.add(x, y, growth);
+            }
+        }
+        for (&(x, y), &state) in &self.previous {
+            if !self.cells.contains_key(&(x, y)) {
+                changes.add(x, y, -i64::from(state == ALIVE));
+            }
+        }
+        changes
+    }
+
+    fn live_cells(&self) -> Vec<(i64, i64)> {
+        let live = self.cells.iter().filter(|&(_, &state)| state == ALIVE);
+        live.map(|(&cell, _)| cell).collect()
+    }
+
+    fn cell_states(&self) -> Vec<((i64, i64), u8)> {
+        self.cells
+            .iter()
+            .map(|(&cell, &state)| (cell, state))
+            .collect()
+    }
+
+    /// Crops the pattern to the bounding box of the live cells
+    fn to_pattern(&self, rule: Rule) -> Result<Pattern, String> {
+        super::crop(self.live_cells(), rule)
+    }
+}
@@ -0,0 +1,244 @@
+//! Ants walking over the colors of an otherwise still field, bounded or not
+
+use std::collections::HashMap;
+
+use super::{Changes, Engine};
+use crate::{
+    boundary::{Boundary, Topology},
+    pattern::Pattern,
+    rule::{
+        ant::{Turmite, STEPS},
+        Rule, ALIVE, DEAD,
+    },
+    Anchor, Field,
+};
+
+/// An ant on the field, facing a way from up clockwise
+#[derive(Clone, Copy)]
+struct Ant {
+    x: i64,
+    y: i64,
+    heading: u8,
+}
+
+/// The size and edges of a bounded field
+#[derive(Clone, Copy)]
+struct Edges {
+    width: usize,
+    height: usize,
+    boundary: Boundary,
+    topology: Topology,
+}
+
+/// Keeps the ants apart from the colors they walk over, so that any number of them can share
+/// a cell and a generation only visits the cells they are on
+pub struct Turmites {
+    /// Colors of the cells not of the first one
+    colors: HashMap<(i64, i64), u8>,
+    /// In the order they were placed in
+    ants: Vec<Ant>,
+    /// The heading of the first ant on each cell with any, which its state shows
+    headings: HashMap<(i64, i64), u8>,
+    /// How the colors and headings make up the states of the cells, updated by every rule
+    /// computed
+    turmite: Turmite,
+    /// None on an unbounded plane
+    edges: Option<Edges>,
+    changes: Changes,
+}
+
+impl Turmites {
+    /// Takes over the colors and ants of a field, which becomes the part of the plane at the
+    /// origin unless the field is `bounded`
+    pub fn from_field(field: &Field, turmite: Turmite, bounded: bool) -> Self {
+        let edges = bounded.then_some(Edges {
+            width: field.width,
+            height: field.height,
+            boundary: field.boundary,
+            topology: field.topology,
+        });
+        let mut turmites = Self {
+            colors: HashMap::new(),
+            ants: Vec::new(),
+            headings: HashMap::new(),
+            turmite,
+            edges,
+            changes: Changes::default(),
+        };
+        for (i, &state) in field.cells.iter().enumerate() {
+            let (x, y) = ((i % field.width) as i64, (i / field.width) as i64);
+            if state != DEAD {
+                turmites.set(x, y, state);
+            }
+        }
+        turmites
+    }
+
+    /// The cell the given position refers to, if any
+    fn resolve(&self, x: i64, y: i64) -> Option<(i64, i64)> {
+        match self.edges {
+            Some(edges) => edges
+                .boundary
+                .resolve(edges.topology, x, y, edges.width, edges.height)
+                .map(|(x, y)| (x as i64, y as i64)),
+            None => Some((x, y)),
+        }
+    }
+
+    fn color(&self, x: i64, y: i64) -> u8 {
+        self.colors.get(&(x, y)).copied().unwrap_or(0)
+    }
+
+    /// Every ant turns by the color its cell had at the start of the generation, moves the
+    /// cell on by a color and steps forward, leaving the field if there is nothing beyond
+    /// the edge
+    fn step(&mut self) {
+        let colors = self.turmite.colors();
+        let moved: Vec<Option<Ant>> = self
+            .ants
+            .iter()
+            .map(|ant| {
+                let heading = self.turmite.turn(self.color(ant.x, ant.y), ant.heading);
+                let [dx, dy] = STEPS[heading as usize];
+                let (x, y) = self.resolve(ant.x + dx, ant.y + dy)?;
+                Some(Ant { x, y, heading })
+            })
+            .collect();
+
+        let mut touched: Vec<(i64, i64)> = self.ants.iter().map(|ant| (ant.x, ant.y)).collect();
+        touched.extend(moved.iter().flatten().map(|ant| (ant.x, ant.y)));
+        touched.sort_unstable();
+        touched.dedup();
+        let before: Vec<u8> = touched.iter().map(|&(x, y)| self.state(x, y)).collect();
+
+        for ant in &self.ants {
+            let color = self.colors.entry((ant.x, ant.y)).or_default();
+            *color = (*color + 1) % colors;
+            if *color == 0 {
+                self.colors.remove(&(ant.x, ant.y));
+            }
+        }
+        self.ants = moved.into_iter().flatten().collect();
+        self.place();
+
+        self.changes = Changes::default();
+        for (&(x, y), before) in touched.iter().zip(before) {
+            let after = self.state(x, y);
+            if after != before {
+                let growth = i64::from(after == ALIVE) - i64::from(before == ALIVE);
+                self.changes.add(x, y, growth);
+            }
+        }
+    }
+
+    /// Finds the cells the ants are on again after they moved
+    fn place(&mut self) {
+        self.headings.clear();
+        for ant in &self.ants {
+            self.headings.entry((ant.x, ant.y)).or_insert(ant.heading);
+        }
+    }
+}
+
+impl Engine for Turmites {
+    /// The color of a cell, with the heading of the first ant on it if there are any
+    fn state(&self, x: i64, y: i64) -> u8 {
+        let Some((x, y)) = self.resolve(x, y) else {
+            return DEAD;
+        };
+        let color = self.color(x, y);
+        match self.headings.get(&(x, y)) {
+            Some(&heading) => self.turmite.with_ant(color, heading),
+            None => color,
+        }
+    }
+
+    /// Keeps all the ants on a cell if its state stays the same
+    fn set(&mut self, x: i64, y: i64, state: u8) {
+        if self.edges.is_some() && self.resolve(x, y) != Some((x, y)) || self.state(x, y) == state {
+            return;
+        }
+        match self.turmite.color(state) {
+            0 => self.colors.remove(&(x, y)),
+            color => self.colors.insert((x, y), color),
+        };
+        self.ants.retain(|ant| (ant.x, ant.y) != (x, y));
+        self.headings.remove(&(x, y));
+        if let Some(heading) = self.turmite.heading(state) {
+            self.ants.push(Ant { x, y, heading });
+            self.headings.insert((x, y), heading);
+        }
+    }
+
+    fn resize(&mut self, width: usize, height: usize, anchor: Anchor) -> Result<(), String> {
+        let Some(edges) = &mut self.edges else {
+            return Ok(());
+        };
+        let (dx, dy) = anchor.offset((edges.width, edges.height), (width, height));
+        (edges.width, edges.height) = (width, height);
+        let inside =
+            |x: i64, y: i64| (0..width as i64).contains(&x) && (0..height as i64).contains(&y);
+        self.colors = self
+            .colors
+            .drain()
+            .map(|((x, y), color)| ((x + dx, y + dy), color))
+            .filter(|&((x, y), _)| inside(x, y))
+            .collect();
+        for ant in &mut self.ants {
+            (ant.x, ant.y) = (ant.x + dx, ant.y + dy);
+        }
+        self.ants.retain(|ant| inside(ant.x, ant.y));
+        self.place();
+        // What the last generation changed still tells whether the cells are stable
+        self.changes = self.changes.shifted(dx, dy);
+        Ok(())
+    }
+
+    fn runs(&self, rule: Rule) -> bool {
+        matches!(rule, Rule::Ant(_))
+    }
+
+    fn advance(&mut self, rule: Rule, generations: u64) {
+        let Rule::Ant(turmite) = rule else {
+            unreachable!("only turmites walk over the colors")
+        };
+        self.turmite = turmite;
+        for _ in 0..generations {
+            self.step();
+        }
+    }
+
+    fn changes(&self) -> Changes {
+        self.changes
+    }
+
+    fn live_cells(&self) -> Vec<(i64, i64)> {
+        let cells = self.cell_states().into_iter();
+        cells
+            .filter(|&(_, state)| state == ALIVE)
+            .map(|(cell, _)| cell)
+            .collect()
+    }
+
+    fn cell_states(&self) -> Vec<((i64, i64), u8)> {
+        let ants = self
+            .headings
+            .keys()
+            .filter(|cell| !self.colors.contains_key(cell));
+        let cells = self.colors.keys().chain(ants);
+        cells.map(|&(x, y)| ((x, y), self.state(x, y))).collect()
+    }
+
+    fn to_pattern(&self, rule: Rule) -> Result<Pattern, String> {
+        let Some(edges) = self.edges else {
+            return super::crop(self.live_cells(), rule);
+        };
+        let mut field = Field::new(edges.width, edges.height);
+        for (x, y) in self.live_cells() {
+            field
+                .set(x as usize, y as usize, true)
+                .map_err(|err| err.to_string())?;
+        }
+        Ok(Pattern::from_field(&field, rule))
+    }
+}
@@ -0,0 +1,44 @@
+//! What can go wrong before the simulation stops by itself, and the codes the program exits
+//! with for each, which are those of sysexits.h
+
+use std::fmt;
+
+use game_of_life::LoadError;
+
+pub enum Error {
+    /// Options that are wrong or don't go together, including those the parser rejects
+    Usage(String),
+    /// A pattern, circuit, image or state that isn't what it should be
+    Pattern(String),
+    /// Reading or writing a file, the terminal or a connection failed
+    Io(String),
+}
+
+impl Error {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Self::Usage(_) => 64,
+            Self::Pattern(_) => 65,
+            Self::Io(_) => 74,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Usage(message) | Self::Pattern(message) | Self::Io(message) => {
+                f.write_str(message)
+            }
+        }
+    }
+}
+
+impl From<LoadError> for Error {
+    fn from(err: LoadError) -> Self {
+        match err {
+            LoadError::Read(message) => Self::Io(message),
+            LoadError::Parse(message) => Self::Pattern(message),
+        }
+    }
+}
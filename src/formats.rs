@@ -0,0 +1,55 @@
+pub mod ascii;
+pub mod life106;
+pub mod plaintext;
+pub mod rle;
+
+use std::path::Path;
+
+use clap::ValueEnum;
+
+use crate::pattern::Pattern;
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Format {
+    /// Run-length encoded (`.rle`)
+    Rle,
+    /// Plaintext rows of `.` and `O` (`.cells`)
+    Plaintext,
+    /// Life 1.06 coordinate list (`.lif`, `.life`)
+    Life106,
+}
+
+impl Format {
+    pub fn from_extension(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "rle" => Some(Self::Rle),
+            "cells" => Some(Self::Plaintext),
+            "lif" | "life" => Some(Self::Life106),
+            _ => None,
+        }
+    }
+
+    /// Picks `format` if given, falling back to detection by the extension of `path`
+    pub fn detect(path: &Path, format: Option<Self>) -> Result<Self, String> {
+        format.or_else(|| Self::from_extension(path)).ok_or(format!(
+            "cannot detect the format of {} from its extension",
+            path.display()
+        ))
+    }
+
+    pub fn parse(self, text: &str) -> Result<Pattern, String> {
+        match self {
+            Self::Rle => rle::parse(text),
+            Self::Plaintext => plaintext::parse(text),
+            Self::Life106 => life106::parse(text),
+        }
+    }
+
+    pub fn write(self, pattern: &Pattern) -> String {
+        match self {
+            Self::Rle => rle::write(pattern),
+            Self::Plaintext => plaintext::write(pattern),
+            Self::Life106 => life106::write(pattern),
+        }
+    }
+}
@@ -0,0 +1,35 @@
+//! Hand-drawn boards using the same glyphs as the terminal output
+
+use crate::pattern::Pattern;
+
+/// Every character is a cell: `*`, `#` and `O` are alive, anything else is dead. All rows
+/// count towards the bounding box, so a frame printed by this program reads back unchanged.
+pub fn parse(text: &str) -> Result<Pattern, String> {
+    let text = text.replace("\r\n", "\n").replace('\r', "\n");
+    let rows: Vec<&str> = text
+        .strip_suffix('\n')
+        .unwrap_or(&text)
+        .split('\n')
+        .collect();
+    if rows.iter().all(|row| row.is_empty()) {
+        return Err("the board is empty".to_string());
+    }
+
+    let mut pattern = Pattern {
+        width: 0,
+        height: rows.len(),
+        cells: Vec::new(),
+        rule: None,
+        comments: Vec::new(),
+    };
+    for (y, row) in (0..).zip(rows) {
+        pattern.width = pattern.width.max(row.chars().count());
+        pattern.cells.extend(
+            (0..)
+                .zip(row.chars())
+                .filter(|&(_, c)| matches!(c, '*' | '#' | 'O'))
+                .map(|(x, _)| (x, y)),
+        );
+    }
+    Ok(pattern)
+}
@@ -0,0 +1,70 @@
+//! The Life 1.06 format: a `#Life 1.06` header followed by one `x y` pair per live cell
+
+use std::fmt::Write;
+
+use crate::pattern::Pattern;
+
+const HEADER: &str = "#Life 1.06";
+
+pub fn parse(text: &str) -> Result<Pattern, String> {
+    let mut lines = text
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()));
+    match lines.next() {
+        Some((_, HEADER)) => {}
+        _ => return Err(format!("missing `{HEADER}` header")),
+    }
+
+    let mut coords = Vec::new();
+    for (line_number, line) in lines {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let mut parts = line.split_whitespace().map(str::parse::<i64>);
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some(Ok(x)), Some(Ok(y)), None) => coords.push((x, y)),
+            _ => {
+                return Err(format!(
+                    "line {line_number}: expected `x y`, found `{line}`"
+                ))
+            }
+        }
+    }
+
+    // Translate the bounding box to start at (0, 0)
+    let min_x = coords.iter().map(|&(x, _)| x).min().unwrap_or(0);
+    let min_y = coords.iter().map(|&(_, y)| y).min().unwrap_or(0);
+    let mut pattern = Pattern {
+        width: 0,
+        height: 0,
+        cells: Vec::with_capacity(coords.len()),
+        rule: None,
+        comments: Vec::new(),
+    };
+    for (x, y) in coords {
+        let (Ok(x), Ok(y)) = (usize::try_from(x - min_x), usize::try_from(y - min_y)) else {
+            return Err("pattern is too large".to_string());
+        };
+        pattern.width = pattern.width.max(x + 1);
+        pattern.height = pattern.height.max(y + 1);
+        pattern.cells.push((x, y));
+    }
+    Ok(pattern)
+}
+
+pub fn write(pattern: &Pattern) -> String {
+    let mut cells = pattern.cells.clone();
+    cells.sort_unstable_by_key(|&(x, y)| (y, x));
+    cells.dedup();
+
+    let mut out = format!("{HEADER}\n");
+    for comment in &pattern.comments {
+        writeln!(out, "#D {comment}").unwrap();
+    }
+    for (x, y) in cells {
+        writeln!(out, "{x} {y}").unwrap();
+    }
+    out
+}
@@ -0,0 +1,56 @@
+//! The plaintext `.cells` format: rows of `.` and `O`, with `!` comment lines
+
+use crate::pattern::Pattern;
+
+pub fn parse(text: &str) -> Result<Pattern, String> {
+    let mut rows: Vec<(usize, &str)> = text
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim_end_matches('\r')))
+        .filter(|(_, line)| !line.starts_with('!'))
+        .collect();
+    while rows.last().is_some_and(|(_, row)| row.trim().is_empty()) {
+        rows.pop();
+    }
+
+    let height = rows.len();
+    let mut cells = Vec::new();
+    let mut width = 0;
+    for (y, &(line_number, row)) in (0..).zip(&rows) {
+        for (x, c) in row.chars().enumerate() {
+            match c {
+                'O' | 'o' | '*' => cells.push((x, y)),
+                '.' | ' ' => {}
+                c => return Err(format!("line {line_number}: unexpected character `{c}`")),
+            }
+            width = width.max(x + 1);
+        }
+    }
+
+    Ok(Pattern {
+        width,
+        height,
+        cells,
+        rule: None,
+        comments: Vec::new(),
+    })
+}
+
+/// Writes every row out to the full width, so that the size of the pattern is kept
+pub fn write(pattern: &Pattern) -> String {
+    let mut grid = vec![vec!['.'; pattern.width]; pattern.height];
+    for &(x, y) in &pattern.cells {
+        grid[y][x] = 'O';
+    }
+
+    let mut out: String = pattern
+        .comments
+        .iter()
+        .map(|comment| format!("!{comment}\n"))
+        .collect();
+    for row in grid {
+        out.extend(row);
+        out.push('\n');
+    }
+    out
+}
@@ -0,0 +1,190 @@
+//! The run-length encoded format used by Golly and the LifeWiki
+
+use crate::{
+    pattern::{Comment, Pattern},
+    rule::Rule,
+};
+
+pub fn parse(text: &str) -> Result<Pattern, String> {
+    let comments = text
+        .lines()
+        .filter_map(|line| parse_comment(line.trim()))
+        .collect();
+    let mut lines = text
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'));
+
+    let (header_line, header) = lines.next().ok_or("missing `x = .., y = ..` header")?;
+    let (width, height, rule) =
+        parse_header(header).map_err(|err| format!("line {header_line}: {err}"))?;
+
+    let mut cells = Vec::new();
+    let (mut x, mut y) = (0usize, 0usize);
+    let mut run: Option<usize> = None;
+    'body: for (line_number, line) in lines {
+        for c in line.chars() {
+            if let Some(digit) = c.to_digit(10) {
+                run = Some(
+                    run.unwrap_or(0)
+                        .checked_mul(10)
+                        .and_then(|n| n.checked_add(digit as usize))
+                        .ok_or(format!("line {line_number}: run count is too large"))?,
+                );
+                continue;
+            }
+
+            let count = run.take().unwrap_or(1);
+            match c {
+                'b' | '.' => x = advance(x, count, line_number)?,
+                '$' => {
+                    x = 0;
+                    y = advance(y, count, line_number)?;
+                }
+                '!' => break 'body,
+                'o' => {
+                    let end = advance(x, count, line_number)?;
+                    // A body that doesn't fit the header is a broken file rather than a
+                    // bigger pattern
+                    if end > width {
+                        return Err(format!(
+                            "line {line_number}: row {} is wider than x = {width}",
+                            y + 1
+                        ));
+                    }
+                    if y >= height {
+                        return Err(format!(
+                            "line {line_number}: row {} is past y = {height}",
+                            y + 1
+                        ));
+                    }
+                    cells.extend((x..end).map(|x| (x, y)));
+                    x = end;
+                }
+                c if c.is_whitespace() => {}
+                c => return Err(format!("line {line_number}: unexpected character `{c}`")),
+            }
+        }
+    }
+
+    Ok(Pattern {
+        width,
+        height,
+        cells,
+        rule,
+        comments,
+    })
+}
+
+/// The name, author and notes, leaving out the lines of positions and rules that Golly and
+/// older programs write, which the header and the cells already tell
+fn parse_comment(line: &str) -> Option<Comment> {
+    let mut chars = line.strip_prefix('#')?.chars();
+    let kind = chars.next()?;
+    let text = chars.as_str().trim().to_string();
+    match kind {
+        'N' => Some(Comment::Name(text)),
+        'O' => Some(Comment::Author(text)),
+        'C' | 'c' => Some(Comment::Note(text)),
+        _ => None,
+    }
+}
+
+fn advance(coord: usize, count: usize, line_number: usize) -> Result<usize, String> {
+    coord
+        .checked_add(count)
+        .ok_or(format!("line {line_number}: pattern is too large"))
+}
+
+fn parse_header(header: &str) -> Result<(usize, usize, Option<Rule>), String> {
+    let (mut width, mut height, mut rule) = (None, None, None);
+    for item in header.split(',') {
+        let (key, value) = item
+            .split_once('=')
+            .ok_or(format!("malformed header entry `{}`", item.trim()))?;
+        let value = value.trim();
+        match key.trim() {
+            "x" => width = Some(parse_dimension(value)?),
+            "y" => height = Some(parse_dimension(value)?),
+            // Rules this program can't run must not get in the way of loading the pattern
+            "rule" => rule = value.parse().ok(),
+            _ => {}
+        }
+    }
+
+    match (width, height) {
+        (Some(width), Some(height)) => Ok((width, height, rule)),
+        _ => Err("header must specify both `x` and `y`".to_string()),
+    }
+}
+
+fn parse_dimension(value: &str) -> Result<usize, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid dimension `{value}`"))
+}
+
+/// Golly keeps encoded lines within this many characters
+const LINE_LENGTH: usize = 70;
+
+pub fn write(pattern: &Pattern) -> String {
+    let mut grid = vec![vec![false; pattern.width]; pattern.height];
+    for &(x, y) in &pattern.cells {
+        grid[y][x] = true;
+    }
+
+    let mut runs: Vec<(usize, char)> = Vec::new();
+    let mut push = |count: usize, tag: char| match runs.last_mut() {
+        Some((n, last)) if *last == tag => *n += count,
+        _ => runs.push((count, tag)),
+    };
+    for row in &grid {
+        // Trailing dead cells are implied by the end of the row
+        let len = row.iter().rposition(|&alive| alive).map_or(0, |x| x + 1);
+        for &alive in &row[..len] {
+            push(1, if alive { 'o' } else { 'b' });
+        }
+        push(1, '$');
+    }
+    // So are trailing empty rows
+    if runs.last().is_some_and(|&(_, tag)| tag == '$') {
+        runs.pop();
+    }
+    runs.push((1, '!'));
+
+    let mut out: String = pattern
+        .comments
+        .iter()
+        .map(|comment| {
+            let kind = match comment {
+                Comment::Name(_) => 'N',
+                Comment::Author(_) => 'O',
+                Comment::Note(_) => 'C',
+            };
+            format!("#{kind} {}", comment.text()).trim_end().to_string() + "\n"
+        })
+        .collect();
+    out += &format!(
+        "x = {}, y = {}, rule = {}\n",
+        pattern.width,
+        pattern.height,
+        pattern.rule.unwrap_or_default()
+    );
+    let mut line_length = 0;
+    for (count, tag) in runs {
+        let run = if count == 1 {
+            tag.to_string()
+        } else {
+            format!("{count}{tag}")
+        };
+        if line_length + run.len() > LINE_LENGTH {
+            out.push('\n');
+            line_length = 0;
+        }
+        line_length += run.len();
+        out.push_str(&run);
+    }
+    out.push('\n');
+    out
+}
@@ -0,0 +1,109 @@
+//! A picture per generation, for other tools to make videos of
+
+use std::{
+    fs,
+    io::{self, BufWriter, Stdout, Write},
+    path::{Path, PathBuf},
+};
+
+use clap::ValueEnum;
+
+use game_of_life::{
+    image::{render_pbm, render_ppm},
+    Life,
+};
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum FrameFormat {
+    /// Binary PBM, with live cells in black on white
+    Pbm,
+    /// Binary PPM, in the colors of --color
+    Ppm,
+}
+
+impl FrameFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Pbm => "pbm",
+            Self::Ppm => "ppm",
+        }
+    }
+}
+
+enum Output {
+    /// One picture after another, flushed after each so that they can be followed as they come
+    Stdout(BufWriter<Stdout>),
+    /// A file per picture, numbered from 0 in the order they were written
+    Dir(PathBuf),
+}
+
+/// Writes out the generations, each cell a square block of `scale` pixels on a side
+pub struct Frames {
+    format: FrameFormat,
+    out: Output,
+    scale: u16,
+    /// Colors of the cells by state, for PPM
+    colors: Vec<[u8; 3]>,
+    /// Pictures written so far
+    count: u64,
+    /// The generation of the last picture, which isn't written again while paused
+    last: Option<u64>,
+}
+
+impl Frames {
+    /// Writes the pictures into the directory at `dir`, which is created if need be, or to
+    /// stdout if there is none
+    pub fn new(
+        format: FrameFormat,
+        dir: Option<&Path>,
+        scale: u16,
+        colors: Vec<[u8; 3]>,
+    ) -> Result<Self, String> {
+        let out = match dir {
+            Some(dir) => {
+                fs::create_dir_all(dir)
+                    .map_err(|err| format!("failed to create {}: {err}", dir.display()))?;
+                Output::Dir(dir.to_path_buf())
+            }
+            None => Output::Stdout(BufWriter::new(io::stdout())),
+        };
+        Ok(Self {
+            format,
+            out,
+            scale,
+            colors,
+            count: 0,
+            last: None,
+        })
+    }
+
+    /// Writes out the generation `life` is at, unless it is the one of the last picture
+    pub fn record(&mut self, life: &Life) -> Result<(), String> {
+        if self.last == Some(life.generation()) {
+            return Ok(());
+        }
+        self.last = Some(life.generation());
+        let field = life.snapshot();
+        let picture = match self.format {
+            FrameFormat::Pbm => render_pbm(&field, self.scale),
+            FrameFormat::Ppm => render_ppm(&field, self.scale, &self.colors),
+        };
+        match &mut self.out {
+            Output::Stdout(out) => out
+                .write_all(&picture)
+                .and_then(|()| out.flush())
+                .map_err(|err| format!("failed to write a frame to stdout: {err}"))?,
+            Output::Dir(dir) => {
+                let path = dir.join(format!(
+                    "frame-{:06}.{}",
+                    self.count,
+                    self.format.extension()
+                ));
+                fs::write(&path, picture)
+                    .map_err(|err| format!("failed to write {}: {err}", path.display()))?;
+            }
+        }
+        self.count += 1;
+        Ok(())
+    }
+}
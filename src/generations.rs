@@ -0,0 +1,106 @@
+//! Going through the generations of a simulation with iterators, which compute each one only
+//! once it is taken
+
+use crate::{Field, Life};
+
+/// Where a simulation is at, without its cells
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Generation {
+    pub generation: u64,
+    pub population: u64,
+}
+
+/// The generation a simulation is at, and then each one after it, without end
+///
+/// ```
+/// use game_of_life::{engine::EngineKind, library, Field, Life, Rule};
+///
+/// let mut field = Field::new(40, 40);
+/// library::get("r-pentomino")
+///     .unwrap()
+///     .place_centered(&mut field)
+///     .unwrap();
+/// let mut life = Life::new(field, Rule::default(), false, EngineKind::Naive, 1).unwrap();
+/// let curve: Vec<u64> = life.generations().take(6).map(|g| g.population).collect();
+/// assert_eq!(curve, [5, 6, 7, 9, 8, 9]);
+/// ```
+pub struct Generations<'a> {
+    life: &'a mut Life,
+    /// Whether the generation the simulation is at was given back already
+    started: bool,
+}
+
+impl<'a> Generations<'a> {
+    pub(crate) fn new(life: &'a mut Life) -> Self {
+        Self {
+            life,
+            started: false,
+        }
+    }
+
+    /// The simulation at the generation last given back, to look at its cells without
+    /// copying them
+    pub fn life(&self) -> &Life {
+        self.life
+    }
+
+    fn current(&mut self) -> Generation {
+        self.started = true;
+        Generation {
+            generation: self.life.generation(),
+            population: self.life.population(),
+        }
+    }
+}
+
+impl Iterator for Generations<'_> {
+    type Item = Generation;
+
+    fn next(&mut self) -> Option<Generation> {
+        if self.started {
+            self.life.step();
+        }
+        Some(self.current())
+    }
+
+    /// Computes the generations skipped all at once, which engines that can skip the ones in
+    /// between do in far fewer steps than one after another
+    fn nth(&mut self, n: usize) -> Option<Generation> {
+        if n == 0 {
+            return self.next();
+        }
+        self.life.fast_forward(n as u64 + u64::from(self.started));
+        Some(self.current())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (usize::MAX, None)
+    }
+}
+
+/// Like `Generations`, the part of the field that is shown at each generation, copied out of
+/// the simulation
+pub struct Fields<'a>(pub(crate) Generations<'a>);
+
+impl Fields<'_> {
+    /// The simulation at the generation of the field last given back
+    pub fn life(&self) -> &Life {
+        self.0.life()
+    }
+}
+
+impl Iterator for Fields<'_> {
+    type Item = Field;
+
+    fn next(&mut self) -> Option<Field> {
+        self.0.next().map(|_| self.0.life.snapshot())
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Field> {
+        self.0.nth(n).map(|_| self.0.life.snapshot())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
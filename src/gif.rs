@@ -0,0 +1,159 @@
+//! Writing animated GIF files a frame at a time, so that only the frame being written is
+//! kept in memory
+
+use std::io::{self, Write};
+
+/// Codes of the LZW compression take up to this many bits
+const MAX_BITS: u8 = 12;
+
+/// An animation that loops forever, in the colors of a palette shared by all its frames
+pub struct Encoder<W: Write> {
+    out: W,
+    width: u16,
+    height: u16,
+    /// Bits of a palette index, with room for all the colors
+    bits: u8,
+}
+
+impl<W: Write> Encoder<W> {
+    /// Starts an animation of `width` by `height` pixels, with up to 256 colors
+    pub fn new(mut out: W, width: u16, height: u16, palette: &[[u8; 3]]) -> io::Result<Self> {
+        assert!(
+            (1..=256).contains(&palette.len()),
+            "a GIF palette has from 1 to 256 colors"
+        );
+        let bits = (usize::BITS - (palette.len() - 1).leading_zeros()).max(1) as u8;
+
+        out.write_all(b"GIF89a")?;
+        out.write_all(&width.to_le_bytes())?;
+        out.write_all(&height.to_le_bytes())?;
+        // A global color table of 2^bits colors, with as many bits per primary color
+        out.write_all(&[0x80 | (bits - 1) << 4 | (bits - 1), 0, 0])?;
+        for i in 0..1 << bits {
+            out.write_all(&palette.get(i).copied().unwrap_or_default())?;
+        }
+        // The Netscape extension, repeating the frames 0 times, which is forever
+        out.write_all(b"\x21\xff\x0bNETSCAPE2.0\x03\x01\x00\x00\x00")?;
+        Ok(Self {
+            out,
+            width,
+            height,
+            bits,
+        })
+    }
+
+    /// Adds a frame of a palette index per pixel, row by row, shown for `delay` hundredths
+    /// of a second
+    pub fn frame(&mut self, pixels: &[u8], delay: u16) -> io::Result<()> {
+        assert_eq!(
+            pixels.len(),
+            self.width as usize * self.height as usize,
+            "a frame has a pixel for each of the animation"
+        );
+        let [delay_low, delay_high] = delay.to_le_bytes();
+        self.out
+            .write_all(&[0x21, 0xf9, 4, 0, delay_low, delay_high, 0, 0])?;
+        self.out.write_all(&[0x2c, 0, 0, 0, 0])?;
+        self.out.write_all(&self.width.to_le_bytes())?;
+        self.out.write_all(&self.height.to_le_bytes())?;
+        self.out.write_all(&[0])?;
+
+        // Codes start at one bit more than the indices, which take at least two
+        let min_bits = self.bits.max(2);
+        self.out.write_all(&[min_bits])?;
+        for block in compress(pixels, min_bits).chunks(255) {
+            self.out.write_all(&[block.len() as u8])?;
+            self.out.write_all(block)?;
+        }
+        self.out.write_all(&[0])
+    }
+
+    /// Ends the animation, giving back where it was written
+    pub fn finish(mut self) -> io::Result<W> {
+        self.out.write_all(&[0x3b])?;
+        self.out.flush()?;
+        Ok(self.out)
+    }
+}
+
+/// Packs variable-length codes into bytes, least significant bits first
+struct Codes {
+    bytes: Vec<u8>,
+    pending: u32,
+    pending_bits: u8,
+    /// Bits of the codes written next
+    size: u8,
+}
+
+impl Codes {
+    fn write(&mut self, code: u16) {
+        self.pending |= (code as u32) << self.pending_bits;
+        self.pending_bits += self.size;
+        while self.pending_bits >= 8 {
+            self.bytes.push(self.pending as u8);
+            self.pending >>= 8;
+            self.pending_bits -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.pending_bits > 0 {
+            self.bytes.push(self.pending as u8);
+        }
+        self.bytes
+    }
+}
+
+/// Compresses the indices with the variant of LZW that GIF uses: the codes grow a bit wider
+/// each time the table of strings outgrows them, and the table starts over when full
+fn compress(pixels: &[u8], min_bits: u8) -> Vec<u8> {
+    let clear = 1u16 << min_bits;
+    let end = clear + 1;
+    let symbols = clear as usize;
+    // The code of each string that is a string of the table followed by an index, or 0 for
+    // strings that aren't in it
+    let mut table = vec![0u16; (1 << MAX_BITS) * symbols];
+    let mut next = end + 1;
+    let mut codes = Codes {
+        bytes: Vec::new(),
+        pending: 0,
+        pending_bits: 0,
+        size: min_bits + 1,
+    };
+
+    codes.write(clear);
+    let Some((&first, rest)) = pixels.split_first() else {
+        codes.write(end);
+        return codes.finish();
+    };
+    let mut string = first as u16;
+    for &pixel in rest {
+        let entry = string as usize * symbols + pixel as usize;
+        if table[entry] != 0 {
+            string = table[entry];
+            continue;
+        }
+        codes.write(string);
+        // Wider codes are needed once the next one doesn't fit, which the decoder finds out
+        // a code later, when it adds the string to its own table
+        if next >= 1 << codes.size && codes.size < MAX_BITS {
+            codes.size += 1;
+        }
+        if next < 1 << MAX_BITS {
+            table[entry] = next;
+            next += 1;
+        } else {
+            codes.write(clear);
+            table.fill(0);
+            next = end + 1;
+            codes.size = min_bits + 1;
+        }
+        string = pixel as u16;
+    }
+    codes.write(string);
+    if next >= 1 << codes.size && codes.size < MAX_BITS {
+        codes.size += 1;
+    }
+    codes.write(end);
+    codes.finish()
+}
@@ -0,0 +1,184 @@
+//! Drawing the field as a bitmap, for the terminals that can show one in place of characters
+
+use std::{env, io};
+
+use crossterm::cursor;
+
+use game_of_life::{
+    image::{render_png, render_sixel},
+    Field,
+};
+
+use crate::{
+    color::{self, Color, Theme},
+    render::Renderer,
+};
+
+/// Cells of the field across and down each character, which are square in the usual fonts
+/// about twice as tall as they are wide
+pub const CELLS: (u16, u16) = (4, 8);
+
+/// Pixels on a side of each cell of a sixel image, which the terminal shows as they are, so
+/// that a character of 8x16 pixels holds its cells
+const SIXEL_SCALE: u16 = 2;
+
+/// Kitty's id of the image shown, which each frame replaces
+const KITTY_IMAGE: u32 = 1;
+
+/// Longest payload of a single escape sequence of the Kitty protocol
+const KITTY_CHUNK: usize = 4096;
+
+/// Indices into the palette of the pictures drawn
+pub const BACKGROUND: u8 = 0;
+pub const ALIVE: u8 = 1;
+pub const DYING: u8 = 2;
+/// The two sides of Immigration
+pub const SIDES: u8 = 3;
+/// Electron heads, their tails and the conductors of Wireworld
+pub const WIREWORLD: u8 = 5;
+/// Shades from cold to hot of the heatmap, the coldest one past the background
+pub const HEAT: u8 = 8;
+pub const HEAT_SHADES: u8 = 4;
+
+/// A protocol of showing pictures in the terminal
+pub trait Graphics {
+    /// Appends to `frame` what shows `picture`, of a color index of the palette per pixel,
+    /// over the `columns` by `rows` characters at the top left corner of the terminal
+    fn draw_frame(
+        &mut self,
+        picture: &Field,
+        columns: u16,
+        rows: u16,
+        frame: &mut Vec<u8>,
+    ) -> io::Result<()>;
+}
+
+/// The bitmaps of DEC terminals, which xterm, foot, mlterm and WezTerm show as well
+struct Sixel {
+    palette: Vec<[u8; 3]>,
+}
+
+impl Graphics for Sixel {
+    fn draw_frame(
+        &mut self,
+        picture: &Field,
+        _columns: u16,
+        _rows: u16,
+        frame: &mut Vec<u8>,
+    ) -> io::Result<()> {
+        crossterm::queue!(frame, cursor::MoveTo(0, 0))?;
+        frame.extend_from_slice(&render_sixel(picture, SIXEL_SCALE, &self.palette));
+        Ok(())
+    }
+}
+
+/// Kitty's graphics protocol, which Ghostty and WezTerm speak too. The picture has a pixel per
+/// cell, which the terminal scales to fit the characters it is placed over.
+struct Kitty {
+    palette: Vec<[u8; 3]>,
+}
+
+impl Graphics for Kitty {
+    fn draw_frame(
+        &mut self,
+        picture: &Field,
+        columns: u16,
+        rows: u16,
+        frame: &mut Vec<u8>,
+    ) -> io::Result<()> {
+        crossterm::queue!(frame, cursor::MoveTo(0, 0))?;
+        let payload = base64(&render_png(picture, 1, &self.palette));
+        let mut chunks = payload.as_bytes().chunks(KITTY_CHUNK).peekable();
+        let mut first = true;
+        while let Some(chunk) = chunks.next() {
+            let more = u8::from(chunks.peek().is_some());
+            frame.extend_from_slice(b"\x1b_G");
+            if first {
+                // A PNG replacing the image shown, without replies or moving the cursor
+                frame.extend_from_slice(
+                    format!("a=T,f=100,i={KITTY_IMAGE},p=1,q=2,C=1,c={columns},r={rows},")
+                        .as_bytes(),
+                );
+                first = false;
+            }
+            frame.extend_from_slice(format!("m={more};").as_bytes());
+            frame.extend_from_slice(chunk);
+            frame.extend_from_slice(b"\x1b\\");
+        }
+        Ok(())
+    }
+}
+
+/// The protocol of a renderer drawing bitmaps, in the colors of `theme` with those of live or
+/// dead cells replaced if given, or none for those drawing characters
+pub fn new(
+    renderer: Renderer,
+    theme: Theme,
+    alive: Option<Color>,
+    dead: Option<Color>,
+) -> Option<Box<dyn Graphics>> {
+    let palette = palette(theme, alive, dead);
+    match renderer {
+        Renderer::Sixel => Some(Box::new(Sixel { palette })),
+        Renderer::Kitty => Some(Box::new(Kitty { palette })),
+        _ => None,
+    }
+}
+
+/// Whether the terminal says it shows the bitmaps of a renderer, as far as its environment
+/// tells, which renderers of characters always are
+pub fn supported(renderer: Renderer) -> bool {
+    let var = |name| env::var(name).unwrap_or_default();
+    let (term, program) = (var("TERM"), var("TERM_PROGRAM"));
+    match renderer {
+        Renderer::Sixel => {
+            ["foot", "mlterm", "contour"]
+                .iter()
+                .any(|name| term.starts_with(name))
+                || term.contains("sixel")
+                || matches!(program.as_str(), "WezTerm" | "mintty")
+        }
+        Renderer::Kitty => {
+            env::var_os("KITTY_WINDOW_ID").is_some()
+                || matches!(term.as_str(), "xterm-kitty" | "xterm-ghostty")
+                || matches!(program.as_str(), "WezTerm" | "ghostty")
+        }
+        _ => true,
+    }
+}
+
+/// The colors of the indices of the pictures, which are there whatever the theme
+fn palette(theme: Theme, alive: Option<Color>, dead: Option<Color>) -> Vec<[u8; 3]> {
+    let mut palette = color::states(theme, alive, dead, false);
+    palette.extend(color::sides());
+    palette.extend_from_slice(&color::states(theme, alive, dead, true)[1..]);
+    let (background, hot) = (palette[BACKGROUND as usize], palette[ALIVE as usize]);
+    for shade in 1..=HEAT_SHADES {
+        let t = f64::from(shade) / f64::from(HEAT_SHADES);
+        palette.push(std::array::from_fn(|c| {
+            (f64::from(background[c]) * (1.0 - t) + f64::from(hot[c]) * t).round() as u8
+        }));
+    }
+    palette
+}
+
+fn base64(data: &[u8]) -> String {
+    const DIGITS: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let bits = u32::from(bytes[0]) << 16 | u32::from(bytes[1]) << 8 | u32::from(bytes[2]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(DIGITS[(bits >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
@@ -0,0 +1,103 @@
+//! Running without showing the generations
+
+use game_of_life::{engine::EngineKind, session::Event, state, Life};
+
+use crate::{
+    outputs::{output, replay, Outputs},
+    setup::{reseed, track},
+    soups,
+    stop::{self, Stop},
+    Config,
+};
+
+/// Computes the generations one after another as fast as it can, without showing them, until
+/// the simulation stops, and saves the last one
+pub fn run(life: &mut Life, config: &Config, outputs: &mut Outputs) -> Result<Stop, String> {
+    outputs.snapshots.record(life)?;
+    if skips(config, outputs) {
+        skip_ahead(life, config)?;
+    }
+    let stop = loop {
+        if let Some(stats) = &mut outputs.stats {
+            stats.record(life)?;
+        }
+        if let Some(tape) = &mut outputs.tape {
+            tape.record(life)?;
+        }
+        if let Some(recording) = &mut outputs.recording {
+            recording.record(life)?;
+        }
+        if let Some(frames) = &mut outputs.frames {
+            frames.record(life)?;
+        }
+        if let Some(server) = &mut outputs.server {
+            server.record(life);
+        }
+        replay(life, config, outputs, None)?;
+        if let Some(stop) = stop::check(life, config, outputs.replay.as_ref(), true) {
+            match &mut outputs.soups {
+                Some(soups) => {
+                    soups.finish(life, stop);
+                    let generation = life.generation();
+                    reseed(life, config, soups::next_seed(life))?;
+                    outputs.edit(generation, || Event::Reseed(state::write(life)))?;
+                    outputs.snapshots.record(life)?;
+                    continue;
+                }
+                None => break stop,
+            }
+        }
+        life.step();
+        outputs.snapshots.record(life)?;
+        if let Some(checkpoints) = &mut outputs.checkpoints {
+            if let Some(err) = checkpoints.record(life)? {
+                eprintln!("warning: {err}");
+            }
+        }
+    };
+    if let Some((path, format)) = output(config) {
+        life.save(path, format)?;
+    }
+    Ok(stop)
+}
+
+/// Whether HashLife can jump straight to the --generations, with nothing needing the ones
+/// in between
+fn skips(config: &Config, outputs: &Outputs) -> bool {
+    config.engine == EngineKind::Hashlife
+        && config.generations.is_some()
+        && outputs.stats.is_none()
+        && outputs.checkpoints.is_none()
+        && outputs.recording.is_none()
+        && outputs.tape.is_none()
+        && !outputs.snapshots.chosen()
+        && outputs.frames.is_none()
+        && outputs.server.is_none()
+        && outputs.soups.is_none()
+        && outputs.replay.is_none()
+        && config.stop_population_below.is_none()
+        && config.stop_population_above.is_none()
+        && config.stop_if_escapes.is_none()
+        && !config.stop_on_cycle
+}
+
+/// Jumps to the --generations, but for the last one, which is stepped to for what it changed.
+/// Cells that died out or stopped changing by then did so at some generation on the way, which
+/// only going one generation at a time from where the run was finds.
+fn skip_ahead(life: &mut Life, config: &Config) -> Result<(), String> {
+    let left = config
+        .generations
+        .map_or(0, |limit| limit.saturating_sub(life.generation()));
+    if left < 2 {
+        return Ok(());
+    }
+    let start = state::write(life);
+    life.fast_forward(left - 1);
+    life.step();
+    let settled = life.population() == 0 || life.engine.changes().is_empty();
+    if settled && !config.keep_running {
+        *life = state::parse(&start, config.engine, config.threads.get())?;
+        track(life, config)?;
+    }
+    Ok(())
+}
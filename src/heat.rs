@@ -0,0 +1,109 @@
+//! How active the cells have been lately, for the heatmap view
+
+use std::{
+    collections::{HashMap, HashSet},
+    mem,
+};
+
+use crate::engine::Engine;
+
+/// Heat a cell gains each time it is born or dies
+const GAIN: u8 = 64;
+
+/// Fraction of its heat a cell loses each generation, as a power of two, on top of a degree
+/// so that cells that stay still get cold at last
+const COOLING: u32 = 4;
+
+/// Heat of the cells that were born or died lately, each decaying exponentially. The cells
+/// that are cold aren't kept.
+pub struct Heat {
+    heat: HashMap<(i64, i64), u8>,
+    /// The live cells of the generation before, to tell which ones changed since
+    alive: HashSet<(i64, i64)>,
+}
+
+impl Heat {
+    /// Starts out with all the cells cold
+    pub fn new(engine: &dyn Engine) -> Self {
+        Self {
+            heat: HashMap::new(),
+            alive: engine.live_cells().into_iter().collect(),
+        }
+    }
+
+    /// Cools the cells down by a generation, and heats up the ones that changed in the
+    /// generation the engine is at
+    pub fn update(&mut self, engine: &dyn Engine) {
+        let alive: HashSet<_> = engine.live_cells().into_iter().collect();
+        self.heat.retain(|_, heat| {
+            *heat -= (*heat >> COOLING) + 1;
+            *heat > 0
+        });
+        for &cell in self.alive.symmetric_difference(&alive) {
+            let heat = self.heat.entry(cell).or_default();
+            *heat = heat.saturating_add(GAIN);
+        }
+        self.alive = alive;
+    }
+
+    /// Keeps up with cells that changed by hand, without heating them up
+    pub fn edited(&mut self, engine: &dyn Engine) {
+        self.alive.clear();
+        self.alive.extend(engine.live_cells());
+    }
+
+    /// Moves the heat along with the cells, which moved by `dx` and `dy`
+    pub(crate) fn shift(&mut self, dx: i64, dy: i64) {
+        let heat = mem::take(&mut self.heat).into_iter();
+        self.heat = heat
+            .map(|((x, y), heat)| ((x + dx, y + dy), heat))
+            .collect();
+        let alive = mem::take(&mut self.alive).into_iter();
+        self.alive = alive.map(|(x, y)| (x + dx, y + dy)).collect();
+    }
+
+    /// Takes the heat of the cells as it was, for picking up a simulation that was left off
+    pub(crate) fn restore(
+        heat: impl IntoIterator<Item = ((i64, i64), u8)>,
+        engine: &dyn Engine,
+    ) -> Self {
+        Self {
+            heat: heat.into_iter().collect(),
+            alive: engine.live_cells().into_iter().collect(),
+        }
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = ((i64, i64), u8)> + '_ {
+        self.heat.iter().map(|(&cell, &heat)| (cell, heat))
+    }
+
+    pub fn get(&self, x: i64, y: i64) -> u8 {
+        self.heat.get(&(x, y)).copied().unwrap_or(0)
+    }
+
+    /// The heat of the hottest of the cells in a rectangle, going through the hot cells
+    /// instead of the rectangle when there are fewer of them
+    pub fn hottest(
+        &self,
+        (left, top): (i64, i64),
+        (width, height): (i64, i64),
+        resolve: impl Fn(i64, i64) -> Option<(i64, i64)>,
+    ) -> u8 {
+        if (width * height) as usize <= self.heat.len() {
+            (top..top + height)
+                .flat_map(|y| (left..left + width).map(move |x| (x, y)))
+                .filter_map(|(x, y)| resolve(x, y))
+                .map(|(x, y)| self.get(x, y))
+                .max()
+                .unwrap_or(0)
+        } else {
+            let (right, bottom) = (left + width, top + height);
+            self.heat
+                .iter()
+                .filter(|&(&(x, y), _)| (left..right).contains(&x) && (top..bottom).contains(&y))
+                .map(|(_, &heat)| heat)
+                .max()
+                .unwrap_or(0)
+        }
+    }
+}
@@ -0,0 +1,125 @@
+//! The last generations of a bounded field, to step back through them
+
+use std::collections::VecDeque;
+
+use crate::engine::Engine;
+
+/// Snapshots of consecutive generations, each holding the states of all the cells packed into
+/// just enough bits, so that a history of `capacity` generations of a `width` by `height`
+/// field takes about `capacity * width * height * bits / 8` bytes
+pub struct History {
+    /// Oldest generation first
+    snapshots: VecDeque<Vec<u64>>,
+    capacity: usize,
+    width: usize,
+    height: usize,
+    /// Bits per cell
+    bits: u32,
+    /// Index of the snapshot of the generation that is shown
+    shown: usize,
+}
+
+impl History {
+    /// Starts out with the generation the engine is at
+    pub fn new(
+        capacity: usize,
+        width: usize,
+        height: usize,
+        states: u8,
+        engine: &dyn Engine,
+    ) -> Self {
+        let mut history = Self {
+            snapshots: VecDeque::with_capacity(capacity),
+            capacity: capacity.max(1),
+            width,
+            height,
+            bits: u8::BITS - (states - 1).leading_zeros(),
+            shown: 0,
+        };
+        history.push(engine);
+        history
+    }
+
+    /// Starts over from the generation the engine is at, with a field of a new size
+    pub fn resize(&mut self, width: usize, height: usize, engine: &dyn Engine) {
+        (self.width, self.height) = (width, height);
+        self.snapshots.clear();
+        self.push(engine);
+    }
+
+    /// Forgets the generations after the shown one, which are about to be computed anew
+    pub fn truncate(&mut self) {
+        self.snapshots.truncate(self.shown + 1);
+    }
+
+    /// Adds the generation the engine is at after the shown one, dropping the oldest one if
+    /// the history is full
+    pub fn push(&mut self, engine: &dyn Engine) {
+        let mut snapshot = if self.snapshots.len() == self.capacity {
+            self.snapshots.pop_front().unwrap_or_default()
+        } else {
+            Vec::new()
+        };
+        self.capture(engine, &mut snapshot);
+        self.snapshots.push_back(snapshot);
+        self.shown = self.snapshots.len() - 1;
+    }
+
+    /// Makes the snapshot of the shown generation match the engine again after its cells
+    /// have been edited, dropping the generations after it
+    pub fn replace_shown(&mut self, engine: &dyn Engine) {
+        self.truncate();
+        match self.snapshots.pop_back() {
+            Some(mut snapshot) => {
+                self.capture(engine, &mut snapshot);
+                self.snapshots.push_back(snapshot);
+            }
+            None => self.push(engine),
+        }
+    }
+
+    /// Moves `by` generations back or forth, as far as the history goes, and brings the
+    /// engine to the generation moved to. Returns the number of generations actually moved.
+    pub fn travel(&mut self, by: isize, engine: &mut dyn Engine) -> isize {
+        let last = self.snapshots.len().saturating_sub(1);
+        let target = self.shown.saturating_add_signed(by).min(last);
+        let moved = target as isize - self.shown as isize;
+        if moved != 0 {
+            self.shown = target;
+            self.restore(engine);
+        }
+        moved
+    }
+
+    fn capture(&self, engine: &dyn Engine, snapshot: &mut Vec<u64>) {
+        let cells = self.width * self.height;
+        snapshot.clear();
+        snapshot.resize((cells * self.bits as usize).div_ceil(64), 0);
+        for (i, (x, y)) in self.cells().enumerate() {
+            let (bit, state) = (i * self.bits as usize, engine.state(x, y) as u64);
+            snapshot[bit / 64] |= state << (bit % 64);
+            // Unless the bits per cell divide 64, some cells straddle two words
+            if bit % 64 + self.bits as usize > 64 {
+                snapshot[bit / 64 + 1] |= state >> (64 - bit % 64);
+            }
+        }
+    }
+
+    fn restore(&self, engine: &mut dyn Engine) {
+        let snapshot = &self.snapshots[self.shown];
+        let mask = (1 << self.bits) - 1;
+        for (i, (x, y)) in self.cells().enumerate() {
+            let bit = i * self.bits as usize;
+            let mut state = snapshot[bit / 64] >> (bit % 64);
+            if bit % 64 + self.bits as usize > 64 {
+                state |= snapshot[bit / 64 + 1] << (64 - bit % 64);
+            }
+            engine.set(x, y, (state & mask) as u8);
+        }
+    }
+
+    fn cells(&self) -> impl Iterator<Item = (i64, i64)> {
+        let (width, height) = (self.width as i64, self.height as i64);
+        (0..height).flat_map(move |y| (0..width).map(move |x| (x, y)))
+    }
+}
@@ -0,0 +1,230 @@
+//! Pictures of the field: seeding it from one, with dark pixels becoming live cells, and
+//! drawing it into one
+
+mod bmp;
+mod deflate;
+mod inflate;
+mod png;
+mod pnm;
+mod sixel;
+
+use std::{fmt::Write, fs, path::Path};
+
+use crate::{cell::CellState, pattern::Pattern, Field, LoadError};
+
+/// Grayscale pixels, row by row
+struct Luma {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+/// Loads a PNG or BMP file, downscaling it to fit into `max_width` by `max_height` cells
+pub fn load(
+    path: &Path,
+    max_width: usize,
+    max_height: usize,
+    threshold: u8,
+) -> Result<Pattern, LoadError> {
+    let data = fs::read(path)
+        .map_err(|err| LoadError::Read(format!("failed to read {}: {err}", path.display())))?;
+    let image = if png::is_png(&data) {
+        png::decode(&data)
+    } else if bmp::is_bmp(&data) {
+        bmp::decode(&data)
+    } else {
+        Err("unsupported image format, expected PNG or BMP".to_string())
+    }
+    .map_err(|err| LoadError::Parse(format!("{}: {err}", path.display())))?;
+
+    // Nearest-neighbor downscaling, preserving the aspect ratio
+    let scale = f64::min(
+        1.0,
+        f64::min(
+            max_width as f64 / f64::from(image.width),
+            max_height as f64 / f64::from(image.height),
+        ),
+    );
+    let width = ((f64::from(image.width) * scale) as usize).max(1);
+    let height = ((f64::from(image.height) * scale) as usize).max(1);
+
+    let mut cells = Vec::new();
+    for y in 0..height {
+        let source_y = y as u64 * u64::from(image.height) / height as u64;
+        for x in 0..width {
+            let source_x = x as u64 * u64::from(image.width) / width as u64;
+            let i = source_y * u64::from(image.width) + source_x;
+            if image.pixels[i as usize] < threshold {
+                cells.push((x, y));
+            }
+        }
+    }
+
+    Ok(Pattern {
+        width,
+        height,
+        cells,
+        rule: None,
+        comments: Vec::new(),
+    })
+}
+
+/// A PNG picture of the field, each cell a square block of `scale` pixels on a side in the
+/// color of its state in `palette`, where higher states take the last color
+pub fn render_png(field: &Field, scale: u16, palette: &[[u8; 3]]) -> Vec<u8> {
+    assert!(
+        (1..=256).contains(&palette.len()),
+        "a PNG palette has from 1 to 256 colors"
+    );
+    let last = (palette.len() - 1) as u8;
+    let (width, height, indices) = blocks(field, scale, |state| state.min(last));
+    png::encode(width as u32, height as u32, palette, &indices)
+}
+
+/// A binary PBM picture of the field, each cell a square block of `scale` pixels on a side,
+/// black where it is alive and white elsewhere
+pub fn render_pbm(field: &Field, scale: u16) -> Vec<u8> {
+    let (width, height, bits) = blocks(field, scale, |state| state.is_alive() as u8);
+    pnm::encode_pbm(width, height, &bits)
+}
+
+/// A binary PPM picture of the field, each cell a square block of `scale` pixels on a side in
+/// the color of its state in `palette`, where higher states take the last color
+pub fn render_ppm(field: &Field, scale: u16, palette: &[[u8; 3]]) -> Vec<u8> {
+    assert!(!palette.is_empty(), "a palette has at least a color");
+    let last = (palette.len() - 1).min(u8::MAX as usize) as u8;
+    let (width, height, indices) = blocks(field, scale, |state| state.min(last));
+    pnm::encode_ppm(width, height, palette, &indices)
+}
+
+/// How `render_svg` draws the field
+pub struct SvgOptions {
+    /// Length of a side of each cell
+    pub cell_size: u16,
+    /// Colors of the cells by state, where higher states take the last color, and that of
+    /// dead cells is that of the background
+    pub palette: Vec<[u8; 3]>,
+    /// Color of the lines between the cells, which are only drawn if there is one
+    pub grid: Option<[u8; 3]>,
+}
+
+impl Default for SvgOptions {
+    /// White live cells and gray dying ones on black, 10 to a side, without a grid
+    fn default() -> Self {
+        Self {
+            cell_size: 10,
+            palette: vec![[0x00; 3], [0xff; 3], [0x5f; 3]],
+            grid: None,
+        }
+    }
+}
+
+/// An SVG picture of the field, as a background with the cells that aren't dead on it. Runs
+/// of cells in the same color along a row make a single rectangle, which keeps large fields
+/// to a sane size.
+pub fn render_svg(field: &Field, options: &SvgOptions) -> String {
+    let palette = &options.palette;
+    assert!(!palette.is_empty(), "a palette has at least a color");
+    let hex = |[r, g, b]: [u8; 3]| format!("#{r:02x}{g:02x}{b:02x}");
+    let size = options.cell_size as usize;
+    let (width, height) = (field.width * size, field.height * size);
+    let last = palette.len() - 1;
+
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}" shape-rendering="crispEdges">"#
+    );
+    let _ = writeln!(
+        svg,
+        r#"<rect width="{width}" height="{height}" fill="{}"/>"#,
+        hex(palette[0])
+    );
+    for (color, &fill) in palette.iter().enumerate().skip(1) {
+        let mut runs = String::new();
+        for (y, row) in field.cells.chunks_exact(field.width.max(1)).enumerate() {
+            let mut x = 0;
+            while x < row.len() {
+                let start = x;
+                while x < row.len() && (row[x] as usize).min(last) == color {
+                    x += 1;
+                }
+                if x == start {
+                    x += 1;
+                    continue;
+                }
+                let _ = writeln!(
+                    runs,
+                    r#"<rect x="{}" y="{}" width="{}" height="{size}"/>"#,
+                    start * size,
+                    y * size,
+                    (x - start) * size
+                );
+            }
+        }
+        if !runs.is_empty() {
+            let _ = writeln!(svg, r#"<g fill="{}">"#, hex(fill));
+            svg.push_str(&runs);
+            svg.push_str("</g>\n");
+        }
+    }
+    if let Some(grid) = options.grid {
+        let mut path = String::new();
+        for x in 1..field.width {
+            let _ = write!(path, "M{} 0V{height}", x * size);
+        }
+        for y in 1..field.height {
+            let _ = write!(path, "M0 {}H{width}", y * size);
+        }
+        if !path.is_empty() {
+            let _ = writeln!(
+                svg,
+                r#"<path d="{path}" fill="none" stroke="{}" stroke-width="1"/>"#,
+                hex(grid)
+            );
+        }
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// The field as a sixel image, the bitmaps of DEC terminals that some others show as well,
+/// each cell a square block of `scale` pixels on a side in the color of its state in
+/// `palette`, where higher states take the last color. Dead cells are drawn in the first
+/// color like the others, so that the image covers whatever was there before.
+pub fn render_sixel(field: &Field, scale: u16, palette: &[[u8; 3]]) -> Vec<u8> {
+    assert!(
+        (1..=256).contains(&palette.len()),
+        "a sixel palette has from 1 to 256 colors"
+    );
+    let last = (palette.len() - 1) as u8;
+    let (width, height, indices) = blocks(field, scale, |state| state.min(last));
+    sixel::encode(width, height, palette, &indices)
+}
+
+/// The pixels of the cells of the field, each a square block of `scale` pixels on a side with
+/// the value `pixel` gives its state, row by row, and the width and height they take
+fn blocks(field: &Field, scale: u16, pixel: impl Fn(u8) -> u8) -> (usize, usize, Vec<u8>) {
+    let scale = scale as usize;
+    let (width, height) = (field.width * scale, field.height * scale);
+    let mut pixels = Vec::with_capacity(width * height);
+    for row in field.cells.chunks_exact(field.width.max(1)) {
+        let start = pixels.len();
+        for &state in row {
+            pixels.extend(std::iter::repeat_n(pixel(state), scale));
+        }
+        for _ in 1..scale {
+            pixels.extend_from_within(start..start + width);
+        }
+    }
+    (width, height, pixels)
+}
+
+fn rgb_luma([r, g, b]: [u8; 3]) -> u8 {
+    ((299 * u32::from(r) + 587 * u32::from(g) + 114 * u32::from(b)) / 1000) as u8
+}
+
+/// Composites a pixel onto a white background, transparency being the absence of ink
+fn over_white(luma: u8, alpha: u8) -> u8 {
+    ((u32::from(luma) * u32::from(alpha) + 255 * (255 - u32::from(alpha))) / 255) as u8
+}
@@ -0,0 +1,85 @@
+//! Uncompressed Windows bitmap decoding into grayscale
+
+use super::Luma;
+
+pub fn is_bmp(data: &[u8]) -> bool {
+    data.starts_with(b"BM")
+}
+
+pub fn decode(data: &[u8]) -> Result<Luma, String> {
+    let u16_at = |i: usize| data.get(i..i + 2).map(|b| u16::from_le_bytes([b[0], b[1]]));
+    let u32_at = |i: usize| {
+        data.get(i..i + 4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    };
+    let truncated = || "BMP file is truncated".to_string();
+
+    let pixel_offset = u32_at(10).ok_or_else(truncated)? as usize;
+    let header_size = u32_at(14).ok_or_else(truncated)? as usize;
+    if header_size < 40 {
+        return Err("old OS/2 bitmaps are not supported".to_string());
+    }
+    let width = u32_at(18).ok_or_else(truncated)? as i32;
+    let height = u32_at(22).ok_or_else(truncated)? as i32;
+    let bits_per_pixel = u16_at(28).ok_or_else(truncated)?;
+    let compression = u32_at(30).ok_or_else(truncated)?;
+    let colors_used = u32_at(46).ok_or_else(truncated)? as usize;
+
+    if compression != 0 {
+        return Err("compressed BMP files are not supported".to_string());
+    }
+    if width <= 0 || height == 0 {
+        return Err("BMP image has no pixels".to_string());
+    }
+    let (width, top_down) = (width as usize, height < 0);
+    let height = height.unsigned_abs() as usize;
+
+    let palette: Vec<u8> = match bits_per_pixel {
+        1 | 4 | 8 => {
+            let count = if colors_used == 0 {
+                1 << bits_per_pixel
+            } else {
+                colors_used
+            };
+            let start = 14 + header_size;
+            data.get(start..start + count * 4)
+                .ok_or_else(truncated)?
+                .chunks_exact(4)
+                .map(|bgr| super::rgb_luma([bgr[2], bgr[1], bgr[0]]))
+                .collect()
+        }
+        24 | 32 => Vec::new(),
+        _ => return Err(format!("{bits_per_pixel}-bit BMP files are not supported")),
+    };
+
+    let stride = (width * bits_per_pixel as usize).div_ceil(32) * 4;
+    let mut pixels = vec![0; width * height];
+    for y in 0..height {
+        // Rows are stored bottom-up unless the height is negative
+        let stored_row = if top_down { y } else { height - 1 - y };
+        let start = pixel_offset + stored_row * stride;
+        let row = data.get(start..start + stride).ok_or_else(truncated)?;
+        for x in 0..width {
+            pixels[y * width + x] = match bits_per_pixel {
+                24 | 32 => {
+                    let i = x * bits_per_pixel as usize / 8;
+                    super::rgb_luma([row[i + 2], row[i + 1], row[i]])
+                }
+                depth => {
+                    let bit = x * depth as usize;
+                    let index =
+                        (row[bit / 8] >> (8 - depth as usize - bit % 8)) & ((1 << depth) - 1);
+                    *palette
+                        .get(index as usize)
+                        .ok_or("palette index out of range")?
+                }
+            };
+        }
+    }
+
+    Ok(Luma {
+        width: width as u32,
+        height: height as u32,
+        pixels,
+    })
+}
@@ -0,0 +1,156 @@
+//! Just enough of zlib (RFC 1950) and DEFLATE (RFC 1951) to write PNG image data: a single
+//! block of the fixed Huffman codes, with the repeats found through chains of hashes
+
+use super::inflate::{DISTANCE_BASE, DISTANCE_EXTRA, LENGTH_BASE, LENGTH_EXTRA};
+
+/// How far back repeats are looked for
+const WINDOW: usize = 32 * 1024;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+/// Earlier positions with the same hash tried for each repeat, trading speed for size
+const MAX_TRIES: usize = 64;
+const HASH_BITS: u32 = 15;
+
+pub fn zlib_compress(data: &[u8]) -> Vec<u8> {
+    let mut bits = BitWriter {
+        out: vec![0x78, 0x01],
+        buffer: 0,
+        count: 0,
+    };
+    // The last block, of fixed codes
+    bits.write(1, 1);
+    bits.write(1, 2);
+
+    // The last position of each hash, and the one before with the same hash for each one
+    let mut head = vec![usize::MAX; 1 << HASH_BITS];
+    let mut previous = vec![usize::MAX; data.len()];
+    let insert = |i: usize, head: &mut [usize], previous: &mut [usize]| {
+        if i + MIN_MATCH <= data.len() {
+            let h = hash(data, i);
+            previous[i] = head[h];
+            head[h] = i;
+        }
+    };
+
+    let mut i = 0;
+    while i < data.len() {
+        let (length, distance) = longest_match(data, i, &head, &previous);
+        let step = if length >= MIN_MATCH {
+            bits.length(length);
+            bits.distance(distance);
+            length
+        } else {
+            bits.literal(u16::from(data[i]));
+            1
+        };
+        for j in i..i + step {
+            insert(j, &mut head, &mut previous);
+        }
+        i += step;
+    }
+    bits.literal(256);
+
+    let mut out = bits.finish();
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
+
+/// The longest repeat of what starts at `i` among the positions before with the same hash
+fn longest_match(data: &[u8], i: usize, head: &[usize], previous: &[usize]) -> (usize, usize) {
+    if i + MIN_MATCH > data.len() {
+        return (0, 0);
+    }
+    let limit = MAX_MATCH.min(data.len() - i);
+    let (mut best, mut distance) = (0, 0);
+    let mut candidate = head[hash(data, i)];
+    for _ in 0..MAX_TRIES {
+        if candidate == usize::MAX || i - candidate > WINDOW {
+            break;
+        }
+        let length = (0..limit)
+            .take_while(|&k| data[candidate + k] == data[i + k])
+            .count();
+        if length > best {
+            (best, distance) = (length, i - candidate);
+            if length == limit {
+                break;
+            }
+        }
+        candidate = previous[candidate];
+    }
+    (best, distance)
+}
+
+/// Hashes the bytes of the shortest repeat starting at `i`
+fn hash(data: &[u8], i: usize) -> usize {
+    let key = u32::from(data[i]) << 16 | u32::from(data[i + 1]) << 8 | u32::from(data[i + 2]);
+    (key.wrapping_mul(0x9e37_79b1) >> (32 - HASH_BITS)) as usize
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    // Sums of this many bytes can't overflow before taking them modulo
+    for chunk in data.chunks(5552) {
+        for &byte in chunk {
+            a += u32::from(byte);
+            b += a;
+        }
+        (a, b) = (a % 65521, b % 65521);
+    }
+    b << 16 | a
+}
+
+struct BitWriter {
+    out: Vec<u8>,
+    buffer: u32,
+    count: u32,
+}
+
+impl BitWriter {
+    /// Writes the `n` lowest bits of `value`, least significant first
+    fn write(&mut self, value: u32, n: u32) {
+        self.buffer |= value << self.count;
+        self.count += n;
+        while self.count >= 8 {
+            self.out.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.count -= 8;
+        }
+    }
+
+    /// Writes a Huffman code, which goes most significant bit first
+    fn code(&mut self, code: u32, n: u32) {
+        self.write(code.reverse_bits() >> (32 - n), n);
+    }
+
+    fn literal(&mut self, symbol: u16) {
+        let symbol = u32::from(symbol);
+        match symbol {
+            0..=143 => self.code(0x30 + symbol, 8),
+            144..=255 => self.code(0x190 + symbol - 144, 9),
+            256..=279 => self.code(symbol - 256, 7),
+            _ => self.code(0xc0 + symbol - 280, 8),
+        }
+    }
+
+    fn length(&mut self, length: usize) {
+        let index = LENGTH_BASE.partition_point(|&base| base as usize <= length) - 1;
+        self.literal(257 + index as u16);
+        let extra = length - LENGTH_BASE[index] as usize;
+        self.write(extra as u32, u32::from(LENGTH_EXTRA[index]));
+    }
+
+    fn distance(&mut self, distance: usize) {
+        let index = DISTANCE_BASE.partition_point(|&base| base as usize <= distance) - 1;
+        self.code(index as u32, 5);
+        let extra = distance - DISTANCE_BASE[index] as usize;
+        self.write(extra as u32, u32::from(DISTANCE_EXTRA[index]));
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.count > 0 {
+            self.out.push(self.buffer as u8);
+        }
+        self.out
+    }
+}
@@ -0,0 +1,226 @@
+//! Non-interlaced PNG decoding into grayscale, and encoding of images of a few colors
+
+use super::{deflate, inflate, Luma};
+
+const SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+pub fn is_png(data: &[u8]) -> bool {
+    data.starts_with(SIGNATURE)
+}
+
+/// Writes an image of 8-bit indices into `palette`, row by row
+pub fn encode(width: u32, height: u32, palette: &[[u8; 3]], indices: &[u8]) -> Vec<u8> {
+    let mut raw = Vec::with_capacity(indices.len() + height as usize);
+    for row in indices.chunks_exact(width.max(1) as usize) {
+        // Unfiltered, as the rows of blocks of cells repeat whole anyway
+        raw.push(0);
+        raw.extend_from_slice(row);
+    }
+
+    let mut out = SIGNATURE.to_vec();
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&width.to_be_bytes());
+    header.extend_from_slice(&height.to_be_bytes());
+    // 8 bits of a palette index, without interlacing
+    header.extend_from_slice(&[8, 3, 0, 0, 0]);
+    write_chunk(&mut out, b"IHDR", &header);
+    write_chunk(&mut out, b"PLTE", palette.as_flattened());
+    write_chunk(&mut out, b"IDAT", &deflate::zlib_compress(&raw));
+    write_chunk(&mut out, b"IEND", &[]);
+    out
+}
+
+fn write_chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend_from_slice(kind);
+    out.extend_from_slice(data);
+    let crc = crc32(&out[start..]);
+    out.extend_from_slice(&crc.to_be_bytes());
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                crc >> 1 ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+pub fn decode(data: &[u8]) -> Result<Luma, String> {
+    let mut rest = data.strip_prefix(SIGNATURE).ok_or("not a PNG file")?;
+    let mut header = None;
+    let mut palette: &[u8] = &[];
+    let mut compressed = Vec::new();
+    loop {
+        if rest.len() < 12 {
+            return Err("PNG file is truncated".to_string());
+        }
+        let len = u32::from_be_bytes(rest[..4].try_into().unwrap()) as usize;
+        let kind = &rest[4..8];
+        let chunk = rest.get(8..8 + len).ok_or("PNG file is truncated")?;
+        rest = rest.get(12 + len..).ok_or("PNG file is truncated")?;
+        match kind {
+            b"IHDR" => header = Some(Header::parse(chunk)?),
+            b"PLTE" => palette = chunk,
+            b"IDAT" => compressed.extend_from_slice(chunk),
+            b"IEND" => break,
+            _ => {}
+        }
+    }
+    let header = header.ok_or("PNG file has no IHDR chunk")?;
+
+    let raw = inflate::zlib_decompress(&compressed)?;
+    let stride = (header.width as usize * header.bits_per_pixel()).div_ceil(8);
+    let filter_distance = header.bits_per_pixel().div_ceil(8);
+    if raw.len() < (stride + 1) * header.height as usize {
+        return Err("PNG image data is truncated".to_string());
+    }
+
+    let mut pixels = Vec::with_capacity(header.width as usize * header.height as usize);
+    let mut previous = vec![0u8; stride];
+    for line in raw.chunks_exact(stride + 1).take(header.height as usize) {
+        let mut row = line[1..].to_vec();
+        unfilter(line[0], &mut row, &previous, filter_distance)?;
+        for x in 0..header.width as usize {
+            pixels.push(header.luma(&row, x, palette)?);
+        }
+        previous = row;
+    }
+
+    Ok(Luma {
+        width: header.width,
+        height: header.height,
+        pixels,
+    })
+}
+
+struct Header {
+    width: u32,
+    height: u32,
+    bit_depth: u8,
+    color_type: u8,
+}
+
+impl Header {
+    fn parse(chunk: &[u8]) -> Result<Self, String> {
+        let &[w0, w1, w2, w3, h0, h1, h2, h3, bit_depth, color_type, _, _, interlace] = chunk
+        else {
+            return Err("malformed IHDR chunk".to_string());
+        };
+        if interlace != 0 {
+            return Err("interlaced PNG files are not supported".to_string());
+        }
+        let valid_depths: &[u8] = match color_type {
+            0 => &[1, 2, 4, 8, 16],
+            3 => &[1, 2, 4, 8],
+            2 | 4 | 6 => &[8, 16],
+            _ => return Err(format!("invalid PNG color type {color_type}")),
+        };
+        if !valid_depths.contains(&bit_depth) {
+            return Err(format!(
+                "invalid bit depth {bit_depth} for this PNG color type"
+            ));
+        }
+
+        Ok(Self {
+            width: u32::from_be_bytes([w0, w1, w2, w3]),
+            height: u32::from_be_bytes([h0, h1, h2, h3]),
+            bit_depth,
+            color_type,
+        })
+    }
+
+    fn channels(&self) -> usize {
+        match self.color_type {
+            2 => 3,
+            4 => 2,
+            6 => 4,
+            _ => 1,
+        }
+    }
+
+    fn bits_per_pixel(&self) -> usize {
+        self.channels() * self.bit_depth as usize
+    }
+
+    /// Reads channel `channel` of pixel `x`, keeping only the top byte of 16-bit samples
+    fn sample(&self, row: &[u8], x: usize, channel: usize) -> u8 {
+        let depth = self.bit_depth as usize;
+        let bit = (x * self.channels() + channel) * depth;
+        match depth {
+            8 => row[bit / 8],
+            16 => row[bit / 8],
+            _ => (row[bit / 8] >> (8 - depth - bit % 8)) & ((1 << depth) - 1),
+        }
+    }
+
+    fn luma(&self, row: &[u8], x: usize, palette: &[u8]) -> Result<u8, String> {
+        // Sub-byte grayscale is scaled up to the full 0..=255 range
+        let scale = |v: u8| match self.bit_depth {
+            1 => v * 255,
+            2 => v * 85,
+            4 => v * 17,
+            _ => v,
+        };
+        Ok(match self.color_type {
+            0 => scale(self.sample(row, x, 0)),
+            2 => super::rgb_luma([0, 1, 2].map(|c| self.sample(row, x, c))),
+            3 => {
+                let i = self.sample(row, x, 0) as usize * 3;
+                let rgb = palette.get(i..i + 3).ok_or("palette index out of range")?;
+                super::rgb_luma([rgb[0], rgb[1], rgb[2]])
+            }
+            4 => super::over_white(self.sample(row, x, 0), self.sample(row, x, 1)),
+            _ => super::over_white(
+                super::rgb_luma([0, 1, 2].map(|c| self.sample(row, x, c))),
+                self.sample(row, x, 3),
+            ),
+        })
+    }
+}
+
+fn unfilter(filter: u8, row: &mut [u8], previous: &[u8], distance: usize) -> Result<(), String> {
+    for i in 0..row.len() {
+        let left = if i >= distance { row[i - distance] } else { 0 };
+        let up = previous[i];
+        let up_left = if i >= distance {
+            previous[i - distance]
+        } else {
+            0
+        };
+        let predictor = match filter {
+            0 => 0,
+            1 => left,
+            2 => up,
+            3 => ((u16::from(left) + u16::from(up)) / 2) as u8,
+            4 => paeth(left, up, up_left),
+            _ => return Err(format!("invalid PNG filter type {filter}")),
+        };
+        row[i] = row[i].wrapping_add(predictor);
+    }
+    Ok(())
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = i16::from(a) + i16::from(b) - i16::from(c);
+    let (pa, pb, pc) = (
+        (p - i16::from(a)).abs(),
+        (p - i16::from(b)).abs(),
+        (p - i16::from(c)).abs(),
+    );
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
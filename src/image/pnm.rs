@@ -0,0 +1,27 @@
+//! Binary Netpbm images, which are little more than a header before the pixels
+
+/// Writes an image of a bit per pixel, 1 being black, row by row
+pub fn encode_pbm(width: usize, height: usize, pixels: &[u8]) -> Vec<u8> {
+    let mut out = format!("P4\n{width} {height}\n").into_bytes();
+    // Rows start on a byte of their own, the last one padded with zeros
+    for row in pixels.chunks_exact(width.max(1)).take(height) {
+        for bits in row.chunks(8) {
+            let byte = bits
+                .iter()
+                .enumerate()
+                .fold(0u8, |byte, (i, &bit)| byte | (bit & 1) << (7 - i));
+            out.push(byte);
+        }
+    }
+    out
+}
+
+/// Writes an image of indices into `palette` as the colors themselves, row by row
+pub fn encode_ppm(width: usize, height: usize, palette: &[[u8; 3]], indices: &[u8]) -> Vec<u8> {
+    let mut out = format!("P6\n{width} {height}\n255\n").into_bytes();
+    out.reserve(indices.len() * 3);
+    for &index in indices {
+        out.extend_from_slice(&palette[index as usize]);
+    }
+    out
+}
@@ -0,0 +1,68 @@
+//! Sixel images, which go down the picture in bands of six rows, a character for each column
+//! of a band holding which of its six pixels are in the color being drawn
+
+use std::fmt::Write;
+
+/// Writes an image of indices into `palette`, row by row, as the device control string that
+/// shows it
+pub fn encode(width: usize, height: usize, palette: &[[u8; 3]], indices: &[u8]) -> Vec<u8> {
+    // Square pixels, and the ones of no color left as they are, sized with the raster
+    // attributes so that the terminal knows the whole image from the start
+    let mut out = format!("\x1bP0;1;0q\"1;1;{width};{height}");
+    for (i, &[r, g, b]) in palette.iter().enumerate() {
+        // Channels go from 0 to 100
+        let percent = |channel: u8| (u32::from(channel) * 100 + 127) / 255;
+        let _ = write!(out, "#{i};2;{};{};{}", percent(r), percent(g), percent(b));
+    }
+
+    let mut used = vec![false; palette.len()];
+    for band in indices.chunks(width.max(1) * 6).take(height.div_ceil(6)) {
+        let rows = band.len() / width.max(1);
+        used.fill(false);
+        for &index in band {
+            used[index as usize] = true;
+        }
+        let mut first = true;
+        for color in (0..palette.len()).filter(|&color| used[color]) {
+            // Going back to the start of the band for each color after the first
+            if !first {
+                out.push('$');
+            }
+            first = false;
+            let _ = write!(out, "#{color}");
+            let mut run: Option<(u8, usize)> = None;
+            for x in 0..width {
+                let bits = (0..rows)
+                    .filter(|&y| band[y * width + x] as usize == color)
+                    .fold(0u8, |bits, y| bits | 1 << y);
+                run = match run {
+                    Some((last, count)) if last == bits => Some((last, count + 1)),
+                    Some((last, count)) => {
+                        push_run(&mut out, last, count);
+                        Some((bits, 1))
+                    }
+                    None => Some((bits, 1)),
+                };
+            }
+            if let Some((last, count)) = run {
+                // Columns of no pixels at the end of the band need not be drawn
+                if last != 0 {
+                    push_run(&mut out, last, count);
+                }
+            }
+        }
+        out.push('-');
+    }
+    out.push_str("\x1b\\");
+    out.into_bytes()
+}
+
+/// Writes `count` columns of the same six bits, repeated with a count if that is shorter
+fn push_run(out: &mut String, bits: u8, count: usize) {
+    let sixel = char::from(b'?' + bits);
+    if count > 3 {
+        let _ = write!(out, "!{count}{sixel}");
+    } else {
+        out.extend(std::iter::repeat_n(sixel, count));
+    }
+}
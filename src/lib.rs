@@ -0,0 +1,1045 @@
+//! Conway's Game of Life and the other cellular automata the simulation runs, apart from
+//! the terminal they are shown in
+
+pub mod age;
+pub mod boundary;
+pub mod cell;
+pub mod census;
+pub mod cycle;
+pub mod engine;
+pub mod formats;
+pub mod generations;
+pub mod gif;
+pub mod heat;
+pub mod history;
+pub mod image;
+pub mod library;
+pub mod measure;
+pub mod noise;
+pub mod pattern;
+pub mod placement;
+pub mod rule;
+pub mod session;
+pub mod state;
+pub mod symmetry;
+pub mod tape;
+pub mod trail;
+pub mod undo;
+pub mod variant;
+
+use std::{error::Error, fmt, fs, mem, path::Path};
+
+use clap::ValueEnum;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+pub use boundary::{Boundary, Topology};
+pub use rule::{Rule, Rules};
+
+use age::Ages;
+use cell::CellState;
+use cycle::{Cycle, Cycles};
+use engine::{Bounded, Engine, EngineKind, HashLife, Packed, SparseField, Turmites};
+use formats::Format;
+use generations::{Fields, Generations};
+use heat::Heat;
+use history::History;
+use noise::Noise;
+use pattern::{Comment, Pattern};
+use rule::{ant, wireworld, Neighborhood, OuterTotalistic, ALIVE, DEAD};
+use symmetry::Symmetry;
+use trail::Trails;
+use undo::Edits;
+use variant::Immigration;
+
+/// A cell that was asked for past the edges of a field
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutOfBounds {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl fmt::Display for OutOfBounds {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "({}, {}) is out of the bounds of the {}x{} field",
+            self.x, self.y, self.width, self.height
+        )
+    }
+}
+
+impl Error for OutOfBounds {}
+
+/// Why the cells of a file, such as a pattern, an image or a saved state, couldn't be loaded
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LoadError {
+    /// The file couldn't be read at all
+    Read(String),
+    /// What the file holds isn't what it should be
+    Parse(String),
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Read(message) | Self::Parse(message) => f.write_str(message),
+        }
+    }
+}
+
+impl Error for LoadError {}
+
+impl From<LoadError> for String {
+    fn from(err: LoadError) -> Self {
+        err.to_string()
+    }
+}
+
+/// What stays in place when a field changes size, the cells growing or being cropped away
+/// from it
+#[derive(Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum Anchor {
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    #[default]
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+impl Anchor {
+    /// How far the cells move from a field of size `from` to one of size `to`
+    pub fn offset(self, from: (usize, usize), to: (usize, usize)) -> (i64, i64) {
+        let (column, row) = match self {
+            Self::TopLeft => (0, 0),
+            Self::Top => (1, 0),
+            Self::TopRight => (2, 0),
+            Self::Left => (0, 1),
+            Self::Center => (1, 1),
+            Self::Right => (2, 1),
+            Self::BottomLeft => (0, 2),
+            Self::Bottom => (1, 2),
+            Self::BottomRight => (2, 2),
+        };
+        let shift = |part: i64, from: usize, to: usize| (to as i64 - from as i64) * part / 2;
+        (shift(column, from.0, to.0), shift(row, from.1, to.1))
+    }
+}
+
+/// A rectangle of cells, the ones on its edges included
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Rect {
+    pub left: i64,
+    pub top: i64,
+    pub right: i64,
+    pub bottom: i64,
+}
+
+impl Rect {
+    /// The rectangle between two opposite corners, in either order
+    pub fn new((x0, y0): (i64, i64), (x1, y1): (i64, i64)) -> Self {
+        Self {
+            left: x0.min(x1),
+            top: y0.min(y1),
+            right: x0.max(x1),
+            bottom: y0.max(y1),
+        }
+    }
+
+    pub fn width(self) -> usize {
+        (self.right - self.left + 1) as usize
+    }
+
+    pub fn height(self) -> usize {
+        (self.bottom - self.top + 1) as usize
+    }
+
+    /// Whether any of the cells of the block of `size` cells on a side from (x, y) on is in
+    /// the rectangle
+    pub fn overlaps(self, (x, y): (i64, i64), size: i64) -> bool {
+        x <= self.right && x + size > self.left && y <= self.bottom && y + size > self.top
+    }
+
+    /// Its cells, row by row
+    pub fn cells(self) -> impl Iterator<Item = (i64, i64)> {
+        (self.top..=self.bottom).flat_map(move |y| (self.left..=self.right).map(move |x| (x, y)))
+    }
+}
+
+/// A bounded grid of cells. By default they hold every state of the rule, so that they can
+/// be dying under rules with more than two.
+pub struct Field<C: CellState = u8> {
+    /// States of the cells, row by row
+    cells: Vec<C>,
+    width: usize,
+    height: usize,
+    boundary: Boundary,
+    topology: Topology,
+}
+
+impl<C: CellState> Field<C> {
+    /// A field of dead cells
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            cells: vec![C::dead(); width * height],
+            width,
+            height,
+            boundary: Boundary::default(),
+            topology: Topology::default(),
+        }
+    }
+
+    /// Fills a field with a random soup where each cell is alive with probability `density`,
+    /// the same for the same seed, size, density and symmetry
+    pub fn random(
+        seed: u64,
+        width: usize,
+        height: usize,
+        density: f64,
+        symmetry: Symmetry,
+    ) -> Result<Self, String> {
+        if width == 0 || height == 0 {
+            return Err(format!("a {width}x{height} field has no cells to fill"));
+        }
+        let mut field = Self::new(width, height);
+        let mut rng = StdRng::seed_from_u64(seed);
+        for y in 0..height {
+            for x in 0..width {
+                field.set(x, y, rng.gen_bool(density)).unwrap();
+            }
+        }
+        symmetry.apply(&mut field)?;
+        Ok(field)
+    }
+
+    /// Changes the size of the field, keeping the cells at `anchor` where they are. New cells
+    /// are dead, and the ones beyond the new edges are dropped.
+    pub fn resize(&mut self, width: usize, height: usize, anchor: Anchor) -> Result<(), String> {
+        if width == 0 || height == 0 {
+            return Err(format!("a {width}x{height} field has no cells to keep"));
+        }
+        let (dx, dy) = anchor.offset((self.width, self.height), (width, height));
+        // The columns and rows of the old field that are still there
+        let kept = |shift: i64, old: usize, new: usize| {
+            let start = (-shift).clamp(0, old as i64);
+            start as usize..(new as i64 - shift).clamp(start, old as i64) as usize
+        };
+        let (columns, rows) = (kept(dx, self.width, width), kept(dy, self.height, height));
+        let mut cells = vec![C::dead(); width * height];
+        for y in rows {
+            let (from, to) = (y * self.width, (y as i64 + dy) as usize * width);
+            let to = (to as i64 + columns.start as i64 + dx) as usize;
+            cells[to..to + columns.len()]
+                .copy_from_slice(&self.cells[from + columns.start..from + columns.end]);
+        }
+        (self.cells, self.width, self.height) = (cells, width, height);
+        Ok(())
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Counts the live cells, leaving out dying ones
+    pub fn population(&self) -> u64 {
+        self.cells.iter().filter(|cell| cell.is_alive()).count() as u64
+    }
+
+    /// Sets what lies beyond the edges of the field
+    pub fn set_edges(&mut self, boundary: Boundary, topology: Topology) {
+        (self.boundary, self.topology) = (boundary, topology);
+    }
+
+    /// Whether the cell is alive, as opposed to dead or dying
+    pub fn get(&self, x: usize, y: usize) -> Result<bool, OutOfBounds> {
+        self.check(x, y)?;
+        Ok(self.cells[y * self.width + x].is_alive())
+    }
+
+    /// Brings the cell to life, or kills it
+    pub fn set(&mut self, x: usize, y: usize, val: bool) -> Result<(), OutOfBounds> {
+        self.set_state(x, y, if val { C::alive() } else { C::dead() })
+    }
+
+    /// Sets the state of the cell, which can be one of those of dying cells
+    pub fn set_state(&mut self, x: usize, y: usize, state: C) -> Result<(), OutOfBounds> {
+        self.check(x, y)?;
+        self.cells[y * self.width + x] = state;
+        Ok(())
+    }
+
+    fn check(&self, x: usize, y: usize) -> Result<(), OutOfBounds> {
+        if x >= self.width || y >= self.height {
+            return Err(OutOfBounds {
+                x,
+                y,
+                width: self.width,
+                height: self.height,
+            });
+        }
+        Ok(())
+    }
+
+    fn state(&self, x: i64, y: i64) -> C {
+        match self
+            .boundary
+            .resolve(self.topology, x, y, self.width, self.height)
+        {
+            Some((x, y)) => self.cells[y * self.width + x],
+            None => C::dead(),
+        }
+    }
+
+    fn is_alive(&self, x: i64, y: i64) -> bool {
+        self.state(x, y).is_alive()
+    }
+
+    fn live_neighbors(&self, x: i64, y: i64, neighborhood: Neighborhood) -> u8 {
+        let mut alive_neighbors = 0u8;
+        for &[i, j] in neighborhood.offsets(y % 2 == 1) {
+            if self.is_alive(x + i, y + j) {
+                alive_neighbors += 1;
+            }
+        }
+        alive_neighbors
+    }
+
+    fn next(&self, x: i64, y: i64, rule: &OuterTotalistic) -> C {
+        let alive_neighbors = self.live_neighbors(x, y, rule.neighborhood());
+        self.state(x, y).next(rule, alive_neighbors)
+    }
+
+    /// The next generation of the whole field, one cell after another
+    pub fn step(&self, rule: &OuterTotalistic) -> Self {
+        let mut next = Self::new(self.width, self.height);
+        next.set_edges(self.boundary, self.topology);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                next.cells[y * self.width + x] = self.next(x as i64, y as i64, rule);
+            }
+        }
+        next
+    }
+
+    /// Counts the live cells in the square of the given radius around every cell, the cell
+    /// itself included, in row-major order.
+    ///
+    /// Uses a summed-area table, kept in `sums` to reuse its allocation, over a copy of the
+    /// field padded on each side by `radius` cells from beyond the boundary, so each count
+    /// costs four lookups regardless of the radius.
+    fn square_counts(&self, radius: u8, sums: &mut Vec<u32>, counts: &mut Vec<u32>) {
+        let r = radius as i64;
+        let padded_width = self.width + 2 * r as usize;
+        let padded_height = self.height + 2 * r as usize;
+
+        // sums[(j + 1) * stride + i + 1] is the number of live cells in the padded field
+        // whose columns are at most i and rows at most j
+        let stride = padded_width + 1;
+        sums.clear();
+        sums.resize(stride * (padded_height + 1), 0);
+        for j in 0..padded_height {
+            let mut row_sum = 0;
+            for i in 0..padded_width {
+                row_sum += u32::from(self.is_alive(i as i64 - r, j as i64 - r));
+                sums[(j + 1) * stride + i + 1] = sums[j * stride + i + 1] + row_sum;
+            }
+        }
+
+        let side = 2 * r as usize + 1;
+        counts.clear();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let (top, bottom) = (y * stride, (y + side) * stride);
+                counts.push(
+                    sums[bottom + x + side] + sums[top + x]
+                        - sums[top + x + side]
+                        - sums[bottom + x],
+                );
+            }
+        }
+    }
+}
+
+/// A field going through its generations, along with everything kept about them
+pub struct Life {
+    pub engine: Box<dyn Engine>,
+    pub rules: Rules,
+    /// Seed of the random soup the simulation started from
+    pub seed: Option<u64>,
+    /// Generations computed so far
+    generation: u64,
+    /// Live cells of the generation shown, kept up to date with the cells that change rather
+    /// than counted every generation
+    population: u64,
+    /// Cells the rules brought to life and killed over all the generations computed
+    births: u64,
+    deaths: u64,
+    /// The furthest generation computed, as the births and deaths of the ones recomputed
+    /// after rewinding the history were already counted
+    tallied: u64,
+    /// The most live cells there were, and the first generation there were as many
+    peak: (u64, u64),
+    /// Cells that pokes brought to life at the generation shown, before the next one is
+    /// computed, at the one before it, and at all of them
+    poking: u64,
+    poked: u64,
+    total_poked: u64,
+    /// Size of the part of the field that is shown, starting at the origin
+    pub width: usize,
+    pub height: usize,
+    /// What lies beyond the edges of the field, or nothing on an unbounded plane
+    pub edges: Option<(Boundary, Topology)>,
+    /// Only kept for bounded fields, since the memory it takes grows with the field
+    pub history: Option<History>,
+    /// Only kept when coloring the cells by age
+    pub ages: Option<Ages>,
+    /// Only kept when drawing the trails of cells that died
+    pub trails: Option<Trails>,
+    /// Only kept once the heatmap has been shown
+    pub heat: Option<Heat>,
+    /// Only kept when looking for cycles
+    pub cycles: Option<Cycles>,
+    /// Only kept when playing Immigration
+    pub immigration: Option<Immigration>,
+    /// Only kept when the cells flip at random
+    pub noise: Option<Noise>,
+    /// Only kept when the cells can be edited by hand, the edits being forgotten whenever the
+    /// generation changes
+    pub edits: Option<Edits>,
+    /// Threads the engine of a bounded field computes with, kept for when it is replaced
+    threads: usize,
+}
+
+impl Life {
+    /// Simulates the field in place, or if `unbounded` is set, as the part of an infinite
+    /// plane around the origin, under rules taking turns or a single one. HashLife always runs
+    /// on an unbounded plane.
+    pub fn new(
+        field: Field,
+        rules: impl Into<Rules>,
+        unbounded: bool,
+        kind: EngineKind,
+        threads: usize,
+    ) -> Result<Self, String> {
+        let rules = rules.into();
+        let (width, height) = (field.width, field.height);
+        let edges = (!unbounded && kind != EngineKind::Hashlife)
+            .then_some((field.boundary, field.topology));
+        if (unbounded || kind == EngineKind::Hashlife) && rules.scroll() {
+            return Err(format!(
+                "{rules} scrolls up a bounded field, which an unbounded plane has no bottom row of"
+            ));
+        }
+        if (unbounded || kind == EngineKind::Hashlife) && rules.births_from_nothing() {
+            return Err(format!(
+                "{rules} gives birth to cells without live neighbors, which can't be done on an unbounded plane"
+            ));
+        }
+        let engine: Box<dyn Engine> = match kind {
+            EngineKind::Hashlife => Box::new(HashLife::from_field(&field, rules.first())?),
+            EngineKind::Naive if unbounded => unbounded_engine(&field, &rules),
+            EngineKind::Naive => bounded_engine(field, &rules, threads),
+        };
+        let population = engine.live_cells().len() as u64;
+        Ok(Self {
+            engine,
+            rules,
+            seed: None,
+            generation: 0,
+            population,
+            births: 0,
+            poking: 0,
+            poked: 0,
+            total_poked: 0,
+            deaths: 0,
+            tallied: 0,
+            peak: (population, 0),
+            width,
+            height,
+            edges,
+            history: None,
+            ages: None,
+            trails: None,
+            heat: None,
+            cycles: None,
+            immigration: None,
+            noise: None,
+            edits: None,
+            threads,
+        })
+    }
+
+    /// A random soup where each cell is alive with probability `density`, the same for the
+    /// same seed, on a field that ends at its edges
+    pub fn from_seed(
+        seed: u64,
+        width: usize,
+        height: usize,
+        density: f64,
+        rule: Rule,
+    ) -> Result<Self, String> {
+        let field = Field::random(seed, width, height, density, Symmetry::None)?;
+        let mut life = Self::new(field, rule, false, EngineKind::Naive, 1)?;
+        life.seed = Some(seed);
+        Ok(life)
+    }
+
+    /// The given cells alive on a field that ends at its edges
+    pub fn from_cells(
+        width: usize,
+        height: usize,
+        cells: impl IntoIterator<Item = (usize, usize)>,
+        rule: Rule,
+    ) -> Result<Self, String> {
+        let mut field = Field::new(width, height);
+        for (x, y) in cells {
+            field.set(x, y, true).map_err(|err| err.to_string())?;
+        }
+        Self::new(field, rule, false, EngineKind::Naive, 1)
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    pub fn population(&self) -> u64 {
+        self.population
+    }
+
+    /// Cells the rules brought to life over all the generations computed, not counting the
+    /// ones of the noise. Skipping ahead counts those alive after the generations skipped that
+    /// weren't before, as if they were one.
+    pub fn births(&self) -> u64 {
+        self.births
+    }
+
+    /// Cells the rules killed, counted like the births
+    pub fn deaths(&self) -> u64 {
+        self.deaths
+    }
+
+    /// Cells that pokes brought to life at the generation before the one shown, which the
+    /// births leave out
+    pub fn poked(&self) -> u64 {
+        self.poked
+    }
+
+    /// Cells that pokes brought to life over all the generations
+    pub fn total_poked(&self) -> u64 {
+        self.total_poked
+    }
+
+    /// The most live cells any generation shown had, and the first of them that did
+    pub fn peak(&self) -> (u64, u64) {
+        self.peak
+    }
+
+    /// Captures the current generation along with what is needed to reproduce it
+    pub fn to_pattern(&self) -> Result<Pattern, String> {
+        if self.rules.first() == Rule::Wireworld {
+            return Err(
+                "Wireworld circuits have tails and conductors, which patterns have no room for"
+                    .to_string(),
+            );
+        }
+        let mut pattern = self.engine.to_pattern(self.rules.first())?;
+        // Which the formats have no room for other than the comments
+        if self.rules.turns() > 1 {
+            pattern
+                .comments
+                .push(Comment::Note(format!("rules {}", self.rules)));
+        }
+        if let Some(seed) = self.seed {
+            pattern.comments.push(Comment::Note(format!("seed {seed}")));
+        }
+        Ok(pattern)
+    }
+
+    /// Under Wireworld, the current generation as a circuit to be run again
+    pub fn circuit(&self) -> Option<String> {
+        (self.rules.first() == Rule::Wireworld)
+            .then(|| wireworld::write(self.width, self.height, |x, y| self.engine.state(x, y)))
+    }
+
+    /// Saves the current generation to `path` in `format`, or as a circuit under Wireworld
+    pub fn save(&self, path: &Path, format: Format) -> Result<(), String> {
+        let text = match self.circuit() {
+            Some(circuit) => circuit,
+            None => format.write(&self.to_pattern()?),
+        };
+        fs::write(path, text).map_err(|err| format!("failed to write {}: {err}", path.display()))
+    }
+
+    /// The part of the field that is shown, with the states of its cells
+    pub fn snapshot(&self) -> Field {
+        let mut field = Field::new(self.width, self.height);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let state = self.engine.state(x as i64, y as i64);
+                field.set_state(x, y, state).unwrap();
+            }
+        }
+        if let Some((boundary, topology)) = self.edges {
+            field.set_edges(boundary, topology);
+        }
+        field
+    }
+
+    /// Computes the generation after the shown one, which replaces any that were computed
+    /// before the history was rewound, and flips cells of it if there is noise
+    pub fn step(&mut self) {
+        if let Some(history) = &mut self.history {
+            history.truncate();
+        }
+        if let Some(edits) = &mut self.edits {
+            edits.barrier();
+        }
+        self.poked = mem::take(&mut self.poking);
+        self.engine.advance(self.rules.get(self.generation), 1);
+        self.generation += 1;
+        let changes = self.engine.changes();
+        if self.generation > self.tallied {
+            self.births += changes.births();
+            self.deaths += changes.deaths();
+            self.tallied = self.generation;
+        }
+        self.population = self.population.saturating_add_signed(changes.growth());
+        if let Some(noise) = &mut self.noise {
+            let growth = noise.apply(&mut *self.engine, self.width, self.height, self.generation);
+            self.population = self.population.saturating_add_signed(growth);
+        }
+        self.note_peak();
+        if let Some(history) = &mut self.history {
+            history.push(&*self.engine);
+        }
+        if let Some(ages) = &mut self.ages {
+            ages.update(&*self.engine);
+        }
+        if let Some(trails) = &mut self.trails {
+            trails.update(&*self.engine);
+        }
+        if let Some(heat) = &mut self.heat {
+            heat.update(&*self.engine);
+        }
+        if let Some(cycles) = &mut self.cycles {
+            cycles.update(self.generation, &*self.engine);
+        }
+        if let Some(mut immigration) = self.immigration.take() {
+            immigration.update(&*self.engine, |x, y| self.resolve(x, y));
+            self.immigration = Some(immigration);
+        }
+    }
+
+    /// Computes `generations` generations at once, which engines that skip the ones in
+    /// between, like HashLife, do in far fewer steps. The ages, trails and heat, which are only
+    /// kept for showing the cells, start over on the generation reached, and so does the
+    /// search for cycles. Unless the rules take turns or there is noise or Immigration, which
+    /// go one generation at a time.
+    pub fn fast_forward(&mut self, generations: u64) {
+        if generations == 0 {
+            return;
+        }
+        if self.rules.turns() > 1 || self.noise.is_some() || self.immigration.is_some() {
+            for _ in 0..generations {
+                self.step();
+            }
+            return;
+        }
+        if let Some(history) = &mut self.history {
+            history.truncate();
+        }
+        if let Some(edits) = &mut self.edits {
+            edits.barrier();
+        }
+        self.poked = mem::take(&mut self.poking);
+        self.engine.advance(self.rules.first(), generations);
+        let changes = self.engine.changes();
+        if self.generation >= self.tallied {
+            self.births += changes.births();
+            self.deaths += changes.deaths();
+        }
+        self.generation += generations;
+        self.tallied = self.tallied.max(self.generation);
+        self.recount();
+        if let Some(history) = &mut self.history {
+            history.push(&*self.engine);
+        }
+        if let Some(ages) = &mut self.ages {
+            ages.reset(&*self.engine);
+        }
+        if let Some(trails) = &mut self.trails {
+            trails.reset(&*self.engine);
+        }
+        if let Some(heat) = &mut self.heat {
+            *heat = Heat::new(&*self.engine);
+        }
+    }
+
+    /// The generation the simulation is at and each one after it, computed as they are
+    /// taken, such as `life.generations().take(100).map(|g| g.population)` for the
+    /// population of the first hundred
+    pub fn generations(&mut self) -> Generations<'_> {
+        Generations::new(self)
+    }
+
+    /// Like `generations`, each with the part of the field that is shown
+    pub fn fields(&mut self) -> Fields<'_> {
+        Fields(Generations::new(self))
+    }
+
+    /// Goes `by` generations back or forth through the history, returning how many it
+    /// actually went
+    pub fn travel(&mut self, by: isize) -> isize {
+        let Some(history) = &mut self.history else {
+            return 0;
+        };
+        let moved = history.travel(by, &mut *self.engine);
+        self.generation = self.generation.saturating_add_signed(moved as i64);
+        if let (Some(edits), true) = (&mut self.edits, moved != 0) {
+            edits.barrier();
+        }
+        self.recount();
+        // The history has no ages, trails, heat or colors, which start over on whatever
+        // generation it went to
+        if let Some(ages) = &mut self.ages {
+            ages.reset(&*self.engine);
+        }
+        if let Some(trails) = &mut self.trails {
+            trails.reset(&*self.engine);
+        }
+        if let Some(heat) = &mut self.heat {
+            *heat = Heat::new(&*self.engine);
+        }
+        if let Some(immigration) = &mut self.immigration {
+            immigration.reset(&*self.engine);
+        }
+        moved
+    }
+
+    /// Changes the size of the part of the field that is shown, and of bounded fields
+    /// themselves, keeping the cells at `anchor` where they are and starting over their
+    /// history. Gives back how many live cells were cropped away.
+    pub fn resize(&mut self, width: usize, height: usize, anchor: Anchor) -> Result<u64, String> {
+        if width == 0 || height == 0 {
+            return Err(format!("a {width}x{height} field has no cells to keep"));
+        }
+        let mut cropped = 0;
+        if self.edges.is_some() {
+            let (dx, dy) = anchor.offset((self.width, self.height), (width, height));
+            let population = self.population;
+            self.engine.resize(width, height, anchor)?;
+            if let Some(ages) = &mut self.ages {
+                ages.shift(dx, dy);
+            }
+            if let Some(trails) = &mut self.trails {
+                trails.shift(dx, dy);
+            }
+            if let Some(heat) = &mut self.heat {
+                heat.shift(dx, dy);
+            }
+            if let Some(immigration) = &mut self.immigration {
+                immigration.shift(dx, dy);
+                immigration.edited(&*self.engine);
+            }
+            self.recount();
+            cropped = population.saturating_sub(self.population);
+        }
+        (self.width, self.height) = (width, height);
+        if let Some(history) = &mut self.history {
+            history.resize(width, height, &*self.engine);
+        }
+        // The cells of the edits moved or are gone
+        if let Some(edits) = &mut self.edits {
+            edits.barrier();
+        }
+        Ok(cropped)
+    }
+
+    /// Switches to other rules from the generation shown on, which need the same states and
+    /// neighborhood as the ones before for the cells to mean the same. A bounded field moves
+    /// to the engine that suits the new rules best.
+    pub fn set_rules(&mut self, rules: Rules) -> Result<(), String> {
+        if rules.states() != self.rules.states()
+            || rules.neighborhood() != self.rules.neighborhood()
+        {
+            return Err(format!(
+                "can't switch from {} to {rules}, as they differ in their states or neighborhood",
+                self.rules
+            ));
+        }
+        if rules.scroll() != self.rules.scroll() {
+            return Err(format!(
+                "can't switch from {} to {rules}, as only one of them scrolls",
+                self.rules
+            ));
+        }
+        if self.immigration.is_some()
+            && rules
+                .iter()
+                .any(|rule| !matches!(rule, Rule::OuterTotalistic(_)))
+        {
+            return Err(format!(
+                "Immigration needs rules counting the neighbors, not {rules}"
+            ));
+        }
+        match self.edges {
+            Some(_)
+                if rules.is_life_like() != self.rules.is_life_like()
+                    || rules.turmites() != self.rules.turmites() =>
+            {
+                self.engine = bounded_engine(self.snapshot(), &rules, self.threads);
+            }
+            Some(_) => {}
+            None if rules.births_from_nothing() => {
+                return Err(format!(
+                    "{rules} gives birth to cells without live neighbors, which can't be done on an unbounded plane"
+                ))
+            }
+            // HashLife runs neither
+            None if rules.turmites() != self.rules.turmites() => {
+                let mut engine = unbounded_engine(&Field::new(0, 0), &rules);
+                for ((x, y), state) in self.engine.cell_states() {
+                    engine.set(x, y, state);
+                }
+                self.engine = engine;
+            }
+            None if !rules.iter().all(|rule| self.engine.runs(rule)) => {
+                return Err(
+                    "HashLife only runs two-state rules in the Moore neighborhood".to_string(),
+                )
+            }
+            None => {}
+        }
+        self.rules = rules;
+        self.recount();
+        Ok(())
+    }
+
+    /// Counts the live cells again and starts looking for cycles over, after the cells
+    /// changed other than by computing a generation
+    fn recount(&mut self) {
+        self.population = self.engine.live_cells().len() as u64;
+        self.note_peak();
+        if let Some(cycles) = &mut self.cycles {
+            cycles.reset(self.generation, &*self.engine);
+        }
+    }
+
+    fn note_peak(&mut self) {
+        if self.population > self.peak.0 {
+            self.peak = (self.population, self.generation);
+        }
+    }
+
+    /// Keeps the population, the history, the heat and the colors in line with cells that
+    /// were edited by hand
+    fn edited(&mut self) {
+        self.recount();
+        if let Some(history) = &mut self.history {
+            history.replace_shown(&*self.engine);
+        }
+        if let Some(heat) = &mut self.heat {
+            heat.edited(&*self.engine);
+        }
+        if let Some(immigration) = &mut self.immigration {
+            immigration.edited(&*self.engine);
+        }
+    }
+}
+
+/// The engine of a bounded field under `rules`
+fn bounded_engine(field: Field, rules: &Rules, threads: usize) -> Box<dyn Engine> {
+    if let Rule::Ant(turmite) = rules.first() {
+        Box::new(Turmites::from_field(&field, turmite, true))
+    } else if rules.is_life_like() {
+        // Same results as the per-cell field, only many times faster
+        Box::new(Packed::from_field(&field, threads))
+    } else {
+        Box::new(Bounded::new(field, threads))
+    }
+}
+
+/// The engine of an unbounded plane under `rules`, with the cells of `field` at the origin
+fn unbounded_engine(field: &Field, rules: &Rules) -> Box<dyn Engine> {
+    match rules.first() {
+        Rule::Ant(turmite) => Box::new(Turmites::from_field(field, turmite, false)),
+        _ => Box::new(SparseField::from_field(field)),
+    }
+}
+
+impl Life {
+    /// Brings a dead cell to life, or kills it otherwise
+    pub fn toggle(&mut self, x: i64, y: i64) {
+        let Some((resolved_x, resolved_y)) = self.resolve(x, y) else {
+            return;
+        };
+        let state = match self.engine.state(resolved_x, resolved_y) {
+            DEAD => ALIVE,
+            _ => DEAD,
+        };
+        self.put([(x, y, state)].into_iter());
+    }
+
+    /// Puts the given cells in the given states one after another, as a single edit
+    pub fn put(&mut self, cells: impl Iterator<Item = (i64, i64, u8)>) {
+        let mut changes = Vec::new();
+        for (x, y, state) in cells {
+            let Some((x, y)) = self.resolve(x, y) else {
+                continue;
+            };
+            let old = self.engine.state(x, y);
+            if old != state {
+                self.engine.set(x, y, state);
+                changes.push((x, y, old, state));
+            }
+        }
+        if let Some(edits) = &mut self.edits {
+            edits.push(changes);
+        }
+        self.edited();
+    }
+
+    /// Undoes the last edit made to the generation shown, if any, giving back the cells as
+    /// it put them
+    pub fn undo(&mut self) -> Option<Vec<(i64, i64, u8)>> {
+        let cells = self.edits.as_mut()?.undo(&mut *self.engine)?;
+        self.edited();
+        Some(cells)
+    }
+
+    /// Makes the edit undone last again, if any, giving back the cells as it put them
+    pub fn redo(&mut self) -> Option<Vec<(i64, i64, u8)>> {
+        let cells = self.edits.as_mut()?.redo(&mut *self.engine)?;
+        self.edited();
+        Some(cells)
+    }
+
+    /// Where edits of the given position and the age shown for it end up: across the edge of a
+    /// wrapping field, nowhere past other edges, and at the position itself on an unbounded
+    /// plane
+    pub fn resolve(&self, x: i64, y: i64) -> Option<(i64, i64)> {
+        let (width, height) = (self.width as i64, self.height as i64);
+        match self.edges {
+            None => Some((x, y)),
+            Some((Boundary::Wrap, topology)) => {
+                let (x, y) = (x.rem_euclid(2 * width), y.rem_euclid(2 * height));
+                let (x, y) = Boundary::Wrap.resolve(topology, x, y, self.width, self.height)?;
+                Some((x as i64, y as i64))
+            }
+            Some(_) => ((0..width).contains(&x) && (0..height).contains(&y)).then_some((x, y)),
+        }
+    }
+
+    /// Kills the given cells, leaving the ones around them as they are
+    pub fn erase(&mut self, cells: impl Iterator<Item = (i64, i64)>) {
+        self.put(cells.map(|(x, y)| (x, y, DEAD)));
+    }
+
+    /// The live cells of a rectangle as a pattern of its size, where edits of them end up
+    pub fn copy(&self, rect: Rect) -> Pattern {
+        let cells = rect
+            .cells()
+            .filter(|&(x, y)| {
+                self.resolve(x, y)
+                    .is_some_and(|(x, y)| self.engine.state(x, y) == ALIVE)
+            })
+            .map(|(x, y)| ((x - rect.left) as usize, (y - rect.top) as usize))
+            .collect();
+        Pattern {
+            width: rect.width(),
+            height: rect.height(),
+            cells,
+            rule: Some(self.rules.first()),
+            comments: Vec::new(),
+        }
+    }
+
+    /// Brings the cells of a stamp to life, leaving the ones around them as they are
+    pub fn stamp(&mut self, cells: impl Iterator<Item = (i64, i64)>) {
+        self.put(cells.map(|(x, y)| (x, y, ALIVE)));
+    }
+
+    /// Brings the dead ones of the given cells to life, as sprinkled at random to stir the
+    /// field up, giving back how many it did. Unlike other edits, they are counted.
+    pub fn poke(&mut self, cells: impl Iterator<Item = (i64, i64)>) -> u64 {
+        let population = self.population;
+        self.stamp(cells);
+        let poked = self.population.saturating_sub(population);
+        self.poking += poked;
+        self.total_poked += poked;
+        poked
+    }
+
+    /// Kills the cells of a rectangle and brings those of a stamp to life, as a single edit
+    pub fn paste(&mut self, rect: Rect, cells: impl Iterator<Item = (i64, i64)>) {
+        let cleared = rect.cells().map(|(x, y)| (x, y, DEAD));
+        self.put(cleared.chain(cells.map(|(x, y)| (x, y, ALIVE))));
+    }
+
+    /// The cycle the generations entered, if they did and it was looked for
+    pub fn cycle(&self) -> Option<Cycle> {
+        self.cycles.as_ref()?.found()
+    }
+
+    /// The shown part of the field as text, a line per row
+    pub fn board(&self) -> String {
+        let mut board = String::new();
+        for y in 0..self.height as i64 {
+            let row: String = (0..self.width as i64).map(|x| self.glyph(x, y)).collect();
+            board.push_str(row.trim_end());
+            board.push('\n');
+        }
+        board
+    }
+
+    /// How the cell at the given position looks on screen
+    pub fn glyph(&self, x: i64, y: i64) -> char {
+        if self.rules.first() == Rule::Wireworld {
+            return match self.engine.state(x, y) {
+                wireworld::HEAD => '@',
+                wireworld::TAIL => '~',
+                wireworld::CONDUCTOR => '#',
+                _ => ' ',
+            };
+        }
+        if let Rule::Ant(turmite) = self.rules.first() {
+            // Colors past the first two get glyphs of their own, in the order of their turns
+            const COLORS: [char; ant::MAX_COLORS as usize - 1] = [
+                '*', '+', 'o', '=', ':', '%', 'x', '&', '$', '@', '-', '~', '"', ';', ',',
+            ];
+            let state = self.engine.state(x, y);
+            return match turmite.heading(state) {
+                Some(heading) => ['^', '>', 'v', '<'][heading as usize],
+                None => match turmite.color(state) {
+                    0 => ' ',
+                    color => COLORS[color as usize - 1],
+                },
+            };
+        }
+        match self.engine.state(x, y) {
+            DEAD => self
+                .trails
+                .as_ref()
+                .zip(self.resolve(x, y))
+                .and_then(|(trails, (x, y))| trails.glyph(x, y))
+                .unwrap_or(' '),
+            ALIVE => '*',
+            dying => {
+                // Dying cells fade out through dimmer glyphs as they approach death
+                const FADING: [char; 3] = ['+', ':', '.'];
+                let dying_states = (self.rules.states() - 2) as usize;
+                FADING[(dying - 2) as usize * FADING.len() / dying_states]
+            }
+        }
+    }
+}
@@ -0,0 +1,64 @@
+//! Classic patterns compiled into the binary
+
+use crate::{formats::rle, pattern::Pattern};
+
+const PATTERNS: &[(&str, &str)] = &[
+    ("glider", "x = 3, y = 3\nbo$2bo$3o!"),
+    ("blinker", "x = 3, y = 1\n3o!"),
+    (
+        "pulsar",
+        "x = 13, y = 13\n2b3o3b3o2$o4bobo4bo$o4bobo4bo$o4bobo4bo$2b3o3b3o2$2b3o3b3o$\
+         o4bobo4bo$o4bobo4bo$o4bobo4bo2$2b3o3b3o!",
+    ),
+    ("r-pentomino", "x = 3, y = 3\nb2o$2o$bo!"),
+    ("acorn", "x = 7, y = 3\nbo$3bo$2o2b3o!"),
+    ("lwss", "x = 5, y = 4\nbo2bo$o$o3bo$4o!"),
+    (
+        "glider-gun",
+        "x = 36, y = 9\n24bo$22bobo$12b2o6b2o12b2o$11bo3bo4b2o12b2o$2o8bo5bo3b2o$\
+         2o8bo3bob2o4bobo$10bo5bo7bo$11bo3bo$12b2o!",
+    ),
+    ("diehard", "x = 8, y = 3\n6bo$2o$bo3b3o!"),
+];
+
+pub fn names() -> impl Iterator<Item = &'static str> {
+    PATTERNS.iter().map(|&(name, _)| name)
+}
+
+pub fn get(name: &str) -> Option<Pattern> {
+    PATTERNS
+        .iter()
+        .find(|&&(n, _)| n == name)
+        .map(|&(_, data)| rle::parse(data).expect("built-in patterns are valid"))
+}
+
+/// Validates a `--pattern` argument, suggesting similarly spelled names for typos
+pub fn parse_name(name: &str) -> Result<&'static str, String> {
+    if let Some(known) = names().find(|&known| known == name) {
+        return Ok(known);
+    }
+
+    let close: Vec<_> = names()
+        .filter(|known| edit_distance(known, name) <= 2 || known.contains(name))
+        .collect();
+    Err(if close.is_empty() {
+        "unknown pattern, see --list-patterns for the available ones".to_string()
+    } else {
+        format!("unknown pattern, did you mean {}?", close.join(" or "))
+    })
+}
+
+pub(crate) fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
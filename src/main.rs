@@ -1,161 +1,892 @@
+mod bench;
+mod charset;
+mod checkpoint;
+mod color;
+mod compare;
+mod controls;
+mod convert;
+mod error;
+mod frames;
+mod graphics;
+mod headless;
+mod outputs;
+mod pace;
+mod plain;
+mod recording;
+mod render;
+mod replay;
+mod server;
+mod settings;
+mod setup;
+mod snapshot;
+mod soups;
+mod sparkline;
+mod stamp;
+mod stats;
+mod stop;
+mod summary;
+mod tui;
+
 use std::{
-    fmt,
-    io::{stdout, Write},
-    mem, thread,
-    time::Duration,
+    ffi::OsString,
+    io::{self, IsTerminal, Write},
+    net::SocketAddr,
+    num::NonZeroUsize,
+    path::PathBuf,
+    process,
+    time::{Duration, Instant},
 };
 
-use clap::Parser;
-use crossterm::{
-    cursor, style,
-    terminal::{self, ClearType},
+use clap::{Args, CommandFactory, Parser, Subcommand, ValueEnum};
+use game_of_life::{
+    engine::EngineKind,
+    formats::Format,
+    library, measure,
+    placement::Placement,
+    rule::{preset, table, Automaton, Neighborhood, RuleList, Turmite},
+    session::Session,
+    state,
+    symmetry::Symmetry,
+    variant::{Split, Variant},
+    Anchor, Boundary, Rule, Rules, Topology,
 };
-use rand::Rng;
 
-struct Field {
-    cells: Vec<Vec<bool>>,
-    width: u16,
-    height: u16,
+use bench::Bench;
+use charset::{Aspect, Charset, Glyph, Glyphs};
+use color::{Color, Theme};
+use compare::Comparison;
+use convert::Convert;
+use error::Error;
+use frames::FrameFormat;
+use outputs::Outputs;
+use render::{CellStyle, Renderer, Resize, View};
+use replay::Replay;
+use stats::StatsFormat;
+use stop::Stop;
+use summary::{CensusFormat, SummaryFormat};
+
+fn main() {
+    // Anything that leaves the terminal as it was drawn on is gone by now
+    let code = execute().unwrap_or_else(|err| {
+        eprintln!("error: {err}");
+        err.exit_code()
+    });
+    process::exit(code);
 }
 
-impl Field {
-    fn new(width: u16, height: u16) -> Self {
-        Self {
-            cells: vec![vec![false; width as usize]; height as usize],
-            width,
-            height,
+/// Does what the command line asks for, giving back the code to exit with
+fn execute() -> Result<i32, Error> {
+    let mut config = match parse_cli()? {
+        Cli {
+            command: Some(Action::Bench(bench)),
+            ..
+        } => {
+            println!("{}", bench.run().map_err(Error::Usage)?);
+            return Ok(0);
         }
-    }
+        Cli {
+            command: Some(Action::Convert(convert)),
+            ..
+        } => return convert.run().map(|()| 0),
+        Cli {
+            command: Some(Action::Replay(replay)),
+            ..
+        } => return replay.run().map(|()| 0),
+        Cli {
+            command: Some(Action::Run(config)),
+            ..
+        } => *config,
+        Cli { config, .. } => config,
+    };
 
-    fn set(&mut self, x: u16, y: u16, val: bool) -> Result<(), &'static str> {
-        if x >= self.width || y >= self.height {
-            return Err("coordinates are out of bounds");
+    if config.list_patterns {
+        for name in library::names() {
+            let pattern = library::get(name).unwrap();
+            println!("{name:<12} {}x{}", pattern.width, pattern.height);
         }
-
-        self.cells[y as usize][x as usize] = val;
-        Ok(())
+        return Ok(0);
     }
-
-    fn is_alive(&self, mut x: i32, mut y: i32) -> bool {
-        x = x.rem_euclid(self.width as i32);
-        y = y.rem_euclid(self.height as i32);
-        self.cells[y as u16 as usize][x as u16 as usize]
+    if config.list_presets {
+        for (name, rule, description) in preset::all() {
+            println!("{name:<16} {:<14} {description}", rule.to_string());
+        }
+        return Ok(0);
+    }
+    if let Some(name) = config.preset {
+        config.rule = Some(preset::get(name).unwrap().into());
     }
 
-    fn next(&self, x: i32, y: i32) -> bool {
-        const NEIGHBORS: [[i32; 2]; 8] = [
-            [-1, -1],
-            [0, -1],
-            [1, -1],
-            [-1, 0],
-            [1, 0],
-            [-1, 1],
-            [0, 1],
-            [1, 1],
-        ];
+    let display = Display::of(&config);
+    config.check(display)?;
 
-        let mut alive_neighbors = 0u8;
-        for [i, j] in NEIGHBORS {
-            if self.is_alive(x + i, y + j) {
-                alive_neighbors += 1;
-            }
-        }
+    let glyphs = Glyphs::new(
+        config.charset,
+        config.alive_char.clone(),
+        config.dead_char.clone(),
+        config.aspect,
+    )
+    .map_err(Error::Usage)?;
 
-        alive_neighbors == 3 || alive_neighbors == 2 && self.is_alive(x, y)
+    let terminal_size = setup::terminal_size(&config, &glyphs);
+
+    // Before anything that can name its rule, like a pattern or a state file
+    if let Some(path) = &config.rule_file {
+        config.rule = Some(Rule::Table(table::load(path)?).into());
+    }
+    let replay = config.replay.as_deref().map(Session::load).transpose()?;
+    let mut life = setup::load(&config, replay.as_ref(), &glyphs, terminal_size)?;
+    if config.measure {
+        let limit = config.generations.unwrap_or(MEASURE_LIMIT);
+        match measure::measure(life.snapshot(), life.rules.clone(), limit).map_err(Error::Usage)? {
+            Some(velocity) => println!(
+                "{velocity}, period {}, moving ({}, {}) every period",
+                velocity.period, velocity.dx, velocity.dy
+            ),
+            None => println!("not a spaceship"),
+        }
+        return Ok(0);
+    }
+    config.check_stdout()?;
+    if config.compare {
+        // Flips at random would make the simulations differ whatever the --perturb
+        if life.noise.is_some() {
+            return Err(Error::Usage(
+                "--compare needs both simulations to follow the rule alone, without --noise"
+                    .to_string(),
+            ));
+        }
+        return Comparison::new(life, config.perturb, &config)?.run(&config, &glyphs);
     }
+    let mut outputs = Outputs::new(&config, &life, display, replay)?;
+    let from = life.generation();
+    let started = Instant::now();
+    let stop = match display {
+        Display::Headless => headless::run(&mut life, &config, &mut outputs),
+        Display::Plain => plain::run(&mut life, &config, &mut outputs),
+        // Restores the terminal before giving back an error, for it to be printed on
+        Display::Screen => tui::run(&mut life, &config, &glyphs, &mut outputs),
+    };
+    if let Some(soups) = &mut outputs.soups {
+        soups.flush();
+    }
+    let stop = stop.map_err(Error::Io)?;
+    outputs.finish(&life, display)?;
+    if let Some(path) = &config.save_state {
+        state::save(&life, path).map_err(Error::Io)?;
+    }
+    // Keeps stdout to the stats or the frames alone, and clean of what the screen was for
+    let mut out: Box<dyn Write> =
+        if config.stats_on_stdout() || config.frames_on_stdout() || display == Display::Screen {
+            Box::new(io::stderr())
+        } else {
+            Box::new(io::stdout())
+        };
+    // Plain text already showed the last generation along with the others
+    if stop != Stop::Quit && !config.quiet && display != Display::Plain {
+        let _ = write!(out, "{}", life.board());
+    }
+    summary::print(&mut out, &life, stop, &config, from, started.elapsed());
+    Ok(stop.exit_code())
 }
 
-struct Life {
-    current: Field,
-    next: Field,
-    width: u16,
-    height: u16,
+/// Where the generations are shown
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Display {
+    /// Nowhere, computing them as fast as possible
+    Headless,
+    /// As text, a generation after another, for stdout that isn't a terminal
+    Plain,
+    /// On a screen of their own in the terminal
+    Screen,
 }
 
-impl Life {
-    fn new(width: u16, height: u16) -> Self {
-        let mut current = Field::new(width, height);
-        let mut rng = rand::thread_rng();
-        for _ in 0..width * height / 4 {
-            current
-                .set(rng.gen_range(0..width), rng.gen_range(0..height), true)
-                .unwrap();
+impl Display {
+    /// The one configured, or without --plain or --force-tui, plain text unless stdout is a
+    /// terminal
+    fn of(config: &Config) -> Self {
+        if config.headless {
+            Self::Headless
+        } else if config.plain {
+            Self::Plain
+        } else if config.force_tui || io::stdout().is_terminal() {
+            Self::Screen
+        } else {
+            Self::Plain
         }
+    }
+}
 
-        Self {
-            current,
-            next: Field::new(width, height),
-            width,
-            height,
+/// Parses the command line, with the settings of the config file for the options it doesn't
+/// give
+fn parse_cli() -> Result<Cli, Error> {
+    let args: Vec<_> = std::env::args_os().collect();
+    let cli = Cli::command();
+    // Only to tell which options were given, since the ones the file gives may be missing
+    let matches = cli.clone().ignore_errors(true).get_matches_from(&args);
+    // The settings are those of running the simulation, which is what happens without a
+    // subcommand
+    let (command, config) = match matches.subcommand() {
+        Some(("run", config)) => (cli.find_subcommand("run").unwrap(), config),
+        Some(_) => return parse_args(args),
+        None => (&cli, &matches),
+    };
+    let path = match config.try_get_one::<PathBuf>("config") {
+        _ if config.try_get_one::<bool>("no_config").ok().flatten() == Some(&true) => None,
+        Ok(Some(path)) => Some((path.clone(), true)),
+        _ => settings::default_path().map(|path| (path, false)),
+    };
+    let more = match path {
+        Some((path, required)) => {
+            settings::args(&path, required, command, config).map_err(Error::Usage)?
         }
-    }
+        None => Vec::new(),
+    };
+    // After the options given, which are those of the subcommand if there is one
+    parse_args(args.into_iter().chain(more))
+}
 
-    fn step(&mut self) {
-        for y in 0..self.height {
-            for x in 0..self.width {
-                self.next
-                    .set(x, y, self.current.next(x as i32, y as i32))
-                    .unwrap();
-            }
+/// Parses the arguments, printing the help or the version and exiting if they ask for it
+fn parse_args(args: impl IntoIterator<Item = OsString>) -> Result<Cli, Error> {
+    Cli::try_parse_from(args).map_err(|err| {
+        if !err.use_stderr() {
+            err.exit();
         }
+        // Only what is wrong on a line, without the usage after it
+        let message = err.render().to_string();
+        let lines: Vec<_> = message
+            .lines()
+            .take_while(|line| !line.is_empty())
+            .map(str::trim)
+            .collect();
+        Error::Usage(lines.join(" ").trim_start_matches("error: ").to_string())
+    })
+}
 
-        mem::swap(&mut self.current, &mut self.next);
-    }
+fn parse_viewport(s: &str) -> Result<(i64, i64), String> {
+    s.split_once(',')
+        .and_then(|(x, y)| Some((x.trim().parse().ok()?, y.trim().parse().ok()?)))
+        .ok_or_else(|| "expected a column and a row like `100,50`".to_string())
 }
 
-impl fmt::Display for Life {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        for y in 0..self.height {
-            for x in 0..self.width {
-                write!(
-                    f,
-                    "{}",
-                    if self.current.is_alive(x as i32, y as i32) {
-                        "*"
-                    } else {
-                        " "
-                    }
-                )?;
-            }
-            writeln!(f)?;
-        }
-        Ok(())
+fn parse_fps(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(fps) if fps.is_finite() && fps >= 0.0 => Ok(fps),
+        _ => Err(
+            "expected a number of frames per second like 10 or 0.5, or 0 to run as fast as \
+                  possible"
+                .to_string(),
+        ),
     }
 }
 
-fn main() {
-    let config = Config::parse();
-
-    let mut life = Life::new(config.width, config.height);
+fn parse_seconds(s: &str) -> Result<Duration, String> {
+    s.parse()
+        .ok()
+        .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+        .ok_or_else(|| "expected a number of seconds like 2 or 0.5".to_string())
+}
 
-    loop {
-        let mut stdout = stdout();
-        crossterm::queue!(
-            stdout,
-            terminal::Clear(ClearType::All),
-            terminal::Clear(ClearType::Purge),
-            cursor::MoveTo(0, 0),
-            style::Print(&life),
-        )
-        .unwrap();
-        stdout.flush().unwrap();
+/// Parses the width or height of a field, which has at least a cell across and down
+fn parse_size(s: &str) -> Result<usize, String> {
+    match s.parse() {
+        Ok(size) if size > 0 => Ok(size),
+        _ => Err("expected a number of cells of at least 1".to_string()),
+    }
+}
 
-        life.step();
-        thread::sleep(Duration::from_secs(1) / config.fps);
+fn parse_probability(s: &str) -> Result<f64, String> {
+    match s.parse() {
+        Ok(p) if (0.0..=1.0).contains(&p) => Ok(p),
+        _ => Err("expected a number from 0 to 1".to_string()),
     }
 }
 
+/// Generations --measure waits for the shape to come back without --generations
+const MEASURE_LIMIT: u64 = 1000;
+
 /// Conway's Game of Life
 #[derive(Parser)]
-#[command(about)]
+#[command(about, args_conflicts_with_subcommands = true)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Action>,
+    /// Running the simulation is what happens without a subcommand
+    #[command(flatten)]
+    config: Config,
+}
+
+#[derive(Subcommand)]
+enum Action {
+    /// Show the generations in the terminal, which is the default
+    Run(Box<Config>),
+    Bench(Bench),
+    Convert(Convert),
+    Replay(Replay),
+}
+
+#[derive(Args)]
+#[command(
+    after_help = "Keys: ? to show the keys there are at the moment, space to pause and resume, n \
+                  or . to step while paused, the left and right arrows to go back and forth \
+                  through the --history while paused, r to start over from a new random soup, q \
+                  or Esc to quit. Clicking a cell flips it. With \
+                  --edit, the arrows or hjkl move the cursor, space flips the cell under it, s \
+                  saves the field to --output and Enter starts the simulation. While editing \
+                  or paused, g or a digit picks up the glider or a built-in pattern, numbered \
+                  in the order of --list-patterns, to aim with the arrows or the mouse, turn \
+                  with [ and ], and place with Enter or a click. There, v also starts selecting \
+                  a rectangle at the cursor, whose other corner the arrows, hjkl or the mouse \
+                  move, for y to copy its live cells, to the --clipboard too, or d to clear it, \
+                  and p picks up the copied cells to paste them over those under them. u \
+                  undoes the last edit of the generation shown and U or Ctrl-R redoes it. x \
+                  brings cells around the cursor to life at random while editing, and all over \
+                  the field while running or paused. * and / make the generations go faster \
+                  and slower while running or paused, and the digits go at a speed of their \
+                  own while running, from 1 to 120 generations a second. \
+                  Otherwise [ and ] switch between the --rules. - and + zoom out and in, f \
+                  follows the cells that change and V, or v unless editing or paused, switches \
+                  between the cells and the --view heatmap. While running or paused, w saves \
+                  the state to --save-state, P, or p while running, a picture of the field to \
+                  snapshot-<generation>.png, s the live cells to \
+                  gol-<unixtime>-gen<generation>.rle in the --save-dir and c shows a --census. \
+                  S saves the field to --svg, and > and < grow and shrink it by a tenth around \
+                  the --resize-anchor.\n\nExit codes: 0 when quitting or after the --generations, \
+                  2 when the cells died out, 3 when they became stable, 4 when they entered a \
+                  cycle with --stop-on-cycle, 5 when they fell below \
+                  --stop-population-below, 6 when they rose above --stop-population-above, 7 \
+                  when they reached the edges with --stop-if-escapes, 64 for options that are \
+                  wrong, 65 for a pattern, circuit, image or state that can't be read as one \
+                  and 74 when reading or writing something failed."
+)]
 struct Config {
-    /// Width of the field
-    #[arg(long, default_value_t = 40)]
-    width: u16,
-    /// Height of the field
-    #[arg(long, default_value_t = 15)]
-    height: u16,
-    /// Approximate steps per second
-    #[arg(long, default_value_t = 10)]
-    fps: u32,
+    /// Width of the field, which is shown in part if it doesn't fit in the terminal [default:
+    /// 40, or the width of the --stdin board or the terminal]
+    #[arg(long, value_parser = parse_size)]
+    width: Option<usize>,
+    /// Height of the field [default: 15, or the height of the --stdin board or the terminal]
+    #[arg(long, value_parser = parse_size)]
+    height: Option<usize>,
+    /// Size the field to fill the terminal, except for the status lines below it, wherever
+    /// --width or --height don't say otherwise
+    #[arg(long)]
+    fit: bool,
+    /// Column and row of the field shown at the top left corner of the terminal, when the
+    /// field is larger than the terminal or unbounded
+    #[arg(long, value_parser = parse_viewport, default_value = "0,0")]
+    viewport: (i64, i64),
+    /// Show each cell of the terminal for a square block of this many cells on a side, shaded
+    /// by how many of them are alive
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..=render::MAX_ZOOM as i64))]
+    zoom: u32,
+    /// What the cells on screen show
+    #[arg(long, value_enum, default_value_t = View::Cells)]
+    view: View,
+    /// How to draw the cells
+    #[arg(long, value_enum, default_value_t = Renderer::Glyph)]
+    renderer: Renderer,
+    /// Characters to draw live and dead cells with
+    #[arg(long, value_enum, default_value_t = Charset::Ascii)]
+    charset: Charset,
+    /// Character to draw live cells with instead of the one of the --charset, which can be
+    /// an emoji or another character two columns wide as long as the one of dead cells is too
+    #[arg(long)]
+    alive_char: Option<Glyph>,
+    /// Character to draw dead cells with instead of the one of the --charset
+    #[arg(long)]
+    dead_char: Option<Glyph>,
+    /// How many columns of the terminal each cell of the field is drawn over, two making up a
+    /// square about as tall as it is wide
+    #[arg(long, value_enum, default_value_t = Aspect::Narrow)]
+    aspect: Aspect,
+    /// What the characters of the cells are made of, solid ones being blanks in the colors of
+    /// the cells as their background, in the white theme unless --color says otherwise
+    #[arg(long, value_enum, default_value_t = CellStyle::Glyph)]
+    style: CellStyle,
+    /// Colors to draw the cells with, downsampled to as many colors as the terminal can show
+    /// [default: never, or white with --alive-color or --dead-color]
+    #[arg(long, value_enum)]
+    color: Option<Theme>,
+    /// Color of live cells instead of the one of the --color theme, by name or as `#rrggbb`
+    #[arg(long)]
+    alive_color: Option<Color>,
+    /// Color of dead cells instead of the one of the --color theme
+    #[arg(long)]
+    dead_color: Option<Color>,
+    /// Draw the cells that died in the last this many generations as a fading trail, when
+    /// drawing a character per cell that isn't zoomed out. The trails are only drawn, and
+    /// don't count as neighbors.
+    #[arg(long, default_value_t = 0)]
+    trail: u8,
+    /// Keep the cells that change centered, moving the viewport after them
+    #[arg(long)]
+    follow: bool,
+    /// Compute the --generations, or those of the session to --replay, as fast as possible
+    /// without showing them, then print the last one along with a summary, unless the
+    /// simulation stops before. HashLife jumps straight to the --generations when nothing
+    /// needs the ones in between, counting the births, deaths and the peak over the jump.
+    #[arg(long, conflicts_with = "edit")]
+    headless: bool,
+    /// Write out numbers about each generation: its number, the live cells, how many were
+    /// born and died, and their bounding box
+    #[arg(long, value_enum)]
+    stats: Option<StatsFormat>,
+    /// File to write the --stats to instead of stdout, as is needed unless --headless
+    #[arg(long, requires = "stats")]
+    stats_file: Option<PathBuf>,
+    /// Only print the summary of how the simulation ended, without the last generation
+    #[arg(long)]
+    quiet: bool,
+    /// How to print the summary of the run at the end, which goes to stderr after a screen
+    /// and to stdout otherwise
+    #[arg(long, value_enum, default_value_t = SummaryFormat::Text)]
+    summary: SummaryFormat,
+    /// After the summary, print how many blocks, beehives, loaves, boats, tubs, ponds,
+    /// blinkers, toads, beacons and gliders the last generation holds, and how many groups of
+    /// cells are none of them. c shows the same on the screen.
+    #[arg(long, value_enum, conflicts_with = "looping")]
+    census: Option<CensusFormat>,
+    /// Print each generation shown as plain text followed by a line with its number, rather
+    /// than drawing on a screen, which is what happens when stdout isn't a terminal
+    #[arg(long, conflicts_with_all = ["headless", "edit", "force_tui"])]
+    plain: bool,
+    /// Draw on a screen even when stdout doesn't look like a terminal
+    #[arg(long, conflicts_with = "headless")]
+    force_tui: bool,
+    /// Print the last generation and exit after computing this many, including those stepped
+    /// through by hand, unless the simulation stops before
+    #[arg(long)]
+    generations: Option<u64>,
+    /// Go on after all the cells died or a generation is the same as the one before, instead
+    /// of printing the last generation and exiting with the code 2 or 3
+    #[arg(long)]
+    keep_running: bool,
+    /// Longest period of the cycles looked for, which are shown in the status line once the
+    /// generations enter one. Takes a few dozen bytes per generation; 0 stops looking.
+    #[arg(long, default_value_t = 1024)]
+    cycle_window: u64,
+    /// Print the last generation and exit with the code 4 once the generations enter a cycle
+    #[arg(long)]
+    stop_on_cycle: bool,
+    /// Print the last generation and exit with the code 5 once there are fewer live cells
+    #[arg(long, value_name = "N")]
+    stop_population_below: Option<u64>,
+    /// Print the last generation and exit with the code 6 once there are more live cells
+    #[arg(long, value_name = "N")]
+    stop_population_above: Option<u64>,
+    /// Print the last generation and exit with the code 7 once a live cell is K cells or
+    /// fewer from the edges, such as a glider leaving the ash of a soup with --boundary dead
+    #[arg(
+        long,
+        value_name = "K",
+        num_args = 0..=1,
+        default_missing_value = "0",
+        conflicts_with = "unbounded"
+    )]
+    stop_if_escapes: Option<u64>,
+    /// Start over from a new random soup whenever the cells die out, become stable, enter a
+    /// cycle or reach the --generations, forever, with a line of its seed, what became of it
+    /// and its population printed for each. Each seed is derived from the one before, so that
+    /// the same --seed goes through the same soups.
+    #[arg(
+        long = "loop",
+        conflicts_with_all = [
+            "pattern_file", "pattern", "place", "stdin", "image", "automaton", "edit",
+            "load_state", "keep_running",
+        ]
+    )]
+    looping: bool,
+    /// Seconds to show a soup that stopped for, fading out, before the next one of --loop
+    #[arg(long, default_value = "2", value_parser = parse_seconds, requires = "looping")]
+    loop_pause: Duration,
+    /// Leave out the status line below the field, with the generation, the number of live
+    /// cells, the measured and target frame rates and the size of the field, and give its row
+    /// to the field as well
+    #[arg(long)]
+    no_status: bool,
+    /// Show the population of the last generations as a line of bars below the field, as
+    /// many as fit in the terminal
+    #[arg(long)]
+    sparkline: bool,
+    /// What happens to the field when the terminal changes size
+    #[arg(long, value_enum, default_value_t = Resize::Clamp)]
+    resize: Resize,
+    /// What stays in place when the field grows or shrinks, with --resize grow or the > and <
+    /// keys
+    #[arg(long, value_enum, default_value_t = Anchor::Center)]
+    resize_anchor: Anchor,
+    /// Approximate steps per second, which can be a fraction such as 0.5 for one every two
+    /// seconds, or 0 to run as fast as possible
+    #[arg(long, default_value_t = 10.0, value_parser = parse_fps)]
+    fps: f64,
+    /// Generations to compute for each frame that is drawn while running, of which --fps is
+    /// the rate. Stepping while paused still goes a generation at a time.
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u64).range(1..))]
+    render_every: u64,
+    /// Compute and draw the generations as fast as possible, the same as --fps 0
+    #[arg(long)]
+    max_speed: bool,
+    /// Keep up the --fps when drawing the frames takes longer than there is time for, by
+    /// computing the generations of the frames that fall behind without drawing them
+    #[arg(long, conflicts_with = "max_speed")]
+    drop_frames: bool,
+    /// Pattern file with the initial state, placed in the center of the field
+    #[arg(long)]
+    pattern_file: Option<PathBuf>,
+    /// Format of the pattern file, detected from its extension by default
+    #[arg(long, value_enum, requires = "pattern_file")]
+    format: Option<Format>,
+    /// Built-in pattern to start with, placed in the center of the field
+    #[arg(long, value_parser = library::parse_name, conflicts_with = "pattern_file")]
+    pattern: Option<&'static str>,
+    /// Place a built-in or file pattern at an offset, optionally transformed, e.g.
+    /// `glider@10,5:rot90:flipx`; repeatable, later placements overwrite earlier ones
+    #[arg(long, value_name = "SPEC")]
+    place: Vec<Placement>,
+    /// Cut off placements that extend past the field instead of rejecting them
+    #[arg(long)]
+    clip: bool,
+    /// Read the initial board from stdin as ASCII art, `*`, `#` or `O` being alive
+    #[arg(long, conflicts_with_all = ["pattern_file", "pattern"])]
+    stdin: bool,
+    /// PNG or BMP picture whose dark pixels become the initial live cells, scaled down to
+    /// fit the field if needed
+    #[arg(long, conflicts_with_all = ["pattern_file", "pattern", "stdin"])]
+    image: Option<PathBuf>,
+    /// Pixels with a brightness below this value are alive
+    #[arg(long, default_value_t = 128, requires = "image")]
+    threshold: u8,
+    /// Rule in B/S notation, e.g. B36/S23 for HighLife, B2/S/3 for the Generations rule
+    /// Brian's Brain, or R5,B34-45,S34-58 for Bugs, a Larger than Life rule of radius 5 whose
+    /// cells count themselves as neighbors unless given M0. Rules
+    /// separated by `|`, such as B3/S23|B36/S125, take turns a generation each.
+    /// [default: the rule of the pattern file, or B3/S23]
+    #[arg(long)]
+    rule: Option<Rules>,
+    /// Rules to switch between with ] and [ while the simulation runs, separated by commas,
+    /// such as B3/S23,B36/S23,B2/S, starting with the first of them. They need the same states
+    /// and neighborhood.
+    #[arg(long, conflicts_with = "rule")]
+    rules: Option<RuleList>,
+    /// Rule file of Golly with a @TABLE of up to 4 states in the Moore neighborhood with
+    /// permute symmetries, whose rule the cells run under. Patterns and state files can name
+    /// it too.
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["rule", "rules"])]
+    rule_file: Option<PathBuf>,
+    /// Rule known by a name, such as highlife for B36/S23. See --list-presets for all of them.
+    #[arg(long, value_parser = preset::parse_name, conflicts_with_all = ["rule", "rules", "rule_file"])]
+    preset: Option<&'static str>,
+    /// Cells whose live states are counted as neighbors [default: the one of the rule]
+    #[arg(long, value_enum)]
+    neighborhood: Option<Neighborhood>,
+    /// Kind of cellular automaton to run
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = Automaton::Life,
+        conflicts_with_all = ["rule", "rules", "rule_file", "preset", "neighborhood", "variant", "pattern", "image", "format", "place"]
+    )]
+    automaton: Automaton,
+    /// Turns of the ants of --automaton ant on the cells of each color, which there are as
+    /// many of: `R` to turn right and `L` to turn left, such as `RLR` or `LLRR`
+    #[arg(long, default_value_t = Turmite::LANGTON, requires = "automaton")]
+    turns: Turmite,
+    /// Ants of --automaton ant, spread over the middle row
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u64).range(1..), requires = "automaton")]
+    ants: u64,
+    /// Wolfram rule of --automaton elementary, from 0 to 255: bit n is the next state of a cell
+    /// whose left neighbor, own state and right neighbor read n in binary
+    #[arg(long, default_value_t = 30, requires = "automaton")]
+    wolfram: u8,
+    /// Start --automaton elementary from a random row, with live cells by the --density,
+    /// rather than from a single live cell in the middle
+    #[arg(long, requires = "automaton")]
+    random_row: bool,
+    /// Variant of the rule that keeps more about the live cells, drawn in colors
+    #[arg(long, value_enum)]
+    variant: Option<Variant>,
+    /// How the live cells of Immigration are colored to start with
+    #[arg(long, value_enum, default_value_t = Split::Halves, requires = "variant")]
+    split: Split,
+    /// What neighbors the cells at the edges of the field have
+    #[arg(long, value_enum, default_value_t = Boundary::Wrap)]
+    boundary: Boundary,
+    /// How the edges are joined when they wrap around
+    #[arg(long, value_enum, default_value_t = Topology::Torus)]
+    topology: Topology,
+    /// Simulate an infinite plane instead, of which the field is the part that is shown
+    #[arg(long, conflicts_with_all = ["boundary", "topology"])]
+    unbounded: bool,
+    /// How the generations are computed
+    #[arg(long, value_enum, default_value_t = EngineKind::Naive)]
+    engine: EngineKind,
+    /// Number of threads computing the generations of a bounded field
+    #[arg(long, default_value_t = NonZeroUsize::MIN)]
+    threads: NonZeroUsize,
+    /// Seed of the random soup, so that a run can be replayed [default: a random one,
+    /// printed to stderr]
+    #[arg(long)]
+    seed: Option<u64>,
+    /// Fraction of live cells in the random soup
+    #[arg(long, default_value_t = 0.25, value_parser = parse_probability)]
+    density: f64,
+    /// Probability of each cell of the field flipping after every generation, at random but
+    /// the same for the same --seed. Under noise, the simulation doesn't stop when all the
+    /// cells died or stopped changing, nor looks for cycles.
+    #[arg(long, default_value_t = 0.0, value_parser = parse_probability, conflicts_with = "stop_on_cycle")]
+    noise: f64,
+    /// Fraction of the dead cells that x brings to life, to stir up a field that settled
+    #[arg(long, default_value_t = 0.05, value_parser = parse_probability)]
+    poke_density: f64,
+    /// How far from the cursor x brings cells to life while editing, rather than all over the
+    /// field
+    #[arg(long, value_name = "CELLS", default_value_t = 5)]
+    poke_radius: u32,
+    /// Symmetry of the random soup
+    #[arg(long, value_enum, default_value_t = Symmetry::None)]
+    symmetry: Symmetry,
+    /// Draw every frame from scratch instead of only the cells that changed, for terminals
+    /// that get out of sync
+    #[arg(long)]
+    full_redraw: bool,
+    /// Show the first generation paused, to look at it before anything moves
+    #[arg(long, conflicts_with_all = ["edit", "headless"])]
+    paused: bool,
+    /// Draw on the field before starting the simulation, which is empty unless a pattern is
+    /// given
+    #[arg(long)]
+    edit: bool,
+    /// Generations to keep for stepping back while paused, on bounded fields. Takes about
+    /// width * height / 8 bytes per generation for two-state rules, and a few times that for
+    /// rules with more states.
+    #[arg(long, default_value_t = 256)]
+    history: usize,
+    /// Print the built-in patterns with their sizes and exit
+    #[arg(long)]
+    list_patterns: bool,
+    /// Print the names of the rules --preset knows with the rules and what they do, and exit
+    #[arg(long)]
+    list_presets: bool,
+    /// Run the spaceship on the field on an unbounded plane, without showing it, until its
+    /// shape comes back or the --generations are up, 1000 by default, and print its period
+    /// and speed, such as c/4 diagonal
+    #[arg(long, conflicts_with_all = ["edit", "looping"])]
+    measure: bool,
+    /// Run a second simulation of the same field in lockstep with the first, beside it or
+    /// below it, showing the cells they differ in in a color of their own
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "edit", "looping", "measure", "census", "output", "save_state", "checkpoint_every",
+            "gif", "record", "snapshot", "svg", "frames", "listen",
+        ]
+    )]
+    compare: bool,
+    /// Cell to flip in the second simulation of --compare before it starts, as a column and a
+    /// row like `10,5`
+    #[arg(long, value_name = "X,Y", value_parser = parse_viewport, requires = "compare")]
+    perturb: Option<(i64, i64)>,
+    /// File the last generation is saved to when the simulation stops, and while editing
+    /// whenever s is pressed, as RLE unless its extension says otherwise
+    #[arg(long)]
+    output: Option<PathBuf>,
+    /// File to save the whole state of the simulation to when it stops or w is pressed, for
+    /// --load-state to go on from exactly where it was left off
+    #[arg(long)]
+    save_state: Option<PathBuf>,
+    /// Edits by hand that u can undo at a time, the oldest ones forgotten past them, or 0 to
+    /// keep none. Going to another generation forgets them all.
+    #[arg(long, value_name = "EDITS", default_value_t = 100)]
+    undo_depth: usize,
+    /// Directory that s saves the live cells to while running or paused, in files named after
+    /// the time and the generation [default: the working directory]
+    #[arg(long, value_name = "DIR")]
+    save_dir: Option<PathBuf>,
+    /// File that the cells copied with y are saved to as well, as RLE unless its extension
+    /// says otherwise
+    #[arg(long, value_name = "PATH")]
+    clipboard: Option<PathBuf>,
+    /// Go on from a state saved with --save-state, with its field, rules, generation, seeds
+    /// and noise, as well as the ages, trails, heat and colors it kept
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "pattern_file", "pattern", "place", "stdin", "image", "rule", "neighborhood",
+            "automaton", "variant", "split", "boundary", "topology", "unbounded", "width",
+            "height", "fit", "seed", "density", "symmetry", "noise",
+        ]
+    )]
+    load_state: Option<PathBuf>,
+    /// File to write the run to as it goes, with the cells toggled, the stamps placed, the
+    /// rules switched to and the soups reseeded to along the way, for --replay to go through
+    /// it again
+    #[arg(long, value_name = "PATH")]
+    record_session: Option<PathBuf>,
+    /// Go through a run written with --record-session again, from the same start and with the
+    /// same edits at the same generations, until the generation it stopped at
+    #[arg(
+        long,
+        value_name = "PATH",
+        conflicts_with_all = [
+            "pattern_file", "pattern", "place", "stdin", "image", "rule", "neighborhood",
+            "automaton", "variant", "split", "boundary", "topology", "unbounded", "width",
+            "height", "fit", "seed", "density", "symmetry", "noise", "load_state",
+            "record_session", "edit", "looping", "compare",
+        ]
+    )]
+    replay: Option<PathBuf>,
+    /// Save the state to the --checkpoint-dir every this many generations, to go on from
+    /// with --load-state if the run is cut short
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+    checkpoint_every: Option<u64>,
+    /// Directory of the checkpoints, named after their generations
+    #[arg(long, default_value = "checkpoints")]
+    checkpoint_dir: PathBuf,
+    /// Checkpoints to keep, deleting the oldest ones
+    #[arg(long, default_value_t = 5, value_parser = clap::value_parser!(u64).range(1..))]
+    checkpoint_keep: u64,
+    /// Stop with an error when a checkpoint can't be written, instead of warning about it
+    /// the first time and going on
+    #[arg(long, requires = "checkpoint_every")]
+    strict: bool,
+    /// Record the generations shown, or all of them with --headless, into an animated GIF
+    /// that loops, showing each for as long as --fps says
+    #[arg(long)]
+    gif: Option<PathBuf>,
+    /// Record every generation into a tape, to play back with `game-of-life replay`
+    #[arg(long, value_name = "PATH")]
+    record: Option<PathBuf>,
+    /// Pixels on a side of each cell of the --gif
+    #[arg(long, default_value_t = 4, value_parser = clap::value_parser!(u16).range(1..), requires = "gif")]
+    gif_scale: u16,
+    /// Write a PNG picture of the field at the --snapshot-at generations, or at the last one
+    /// without any. A `{gen}` in the path stands for the generation.
+    #[arg(long)]
+    snapshot: Option<PathBuf>,
+    /// Generation to take a --snapshot of, which can be given more than once
+    #[arg(long, requires = "snapshot")]
+    snapshot_at: Vec<u64>,
+    /// Pixels on a side of each cell of the snapshots
+    #[arg(long, default_value_t = 4, value_parser = clap::value_parser!(u16).range(1..))]
+    snapshot_scale: u16,
+    /// Write the last generation as an SVG picture, with a rectangle for each run of cells
+    /// in a row
+    #[arg(long)]
+    svg: Option<PathBuf>,
+    /// Length of a side of each cell of the --svg
+    #[arg(long, default_value_t = 10, value_parser = clap::value_parser!(u16).range(1..), requires = "svg")]
+    svg_cell_size: u16,
+    /// Draw lines between the cells of the --svg, in the given color or a dark gray
+    #[arg(long, num_args = 0..=1, default_missing_value = "#303030", requires = "svg")]
+    svg_grid: Option<Color>,
+    /// Write each generation shown, or all of them with --headless, as a picture to stdout,
+    /// such as for `ffmpeg -f image2pipe -i - out.mp4`, or into the --frames-dir
+    #[arg(long, value_enum)]
+    frames: Option<FrameFormat>,
+    /// Show the generations as text to whoever connects to this address and port over TCP,
+    /// such as with `nc host 4000`, at most as often as --fps says
+    #[arg(long, value_name = "ADDRESS")]
+    listen: Option<SocketAddr>,
+    /// Directory to write the --frames into instead, as frame-000000.pbm and so on
+    #[arg(long, requires = "frames")]
+    frames_dir: Option<PathBuf>,
+    /// Pixels on a side of each cell of the --frames
+    #[arg(long, default_value_t = 4, value_parser = clap::value_parser!(u16).range(1..), requires = "frames")]
+    frames_scale: u16,
+    /// TOML file of settings for the options not given, such as `charset = "block"` or
+    /// `fps = 30` [default: $XDG_CONFIG_HOME/game-of-life/config.toml, if there is one]
+    #[arg(long, conflicts_with = "no_config")]
+    config: Option<PathBuf>,
+    /// Leave out the settings of the config file
+    #[arg(long)]
+    no_config: bool,
+}
+
+impl Config {
+    /// Rejects options that don't go together, and falls back from those the terminal on
+    /// `display` doesn't support, with a warning
+    fn check(&mut self, display: Display) -> Result<(), Error> {
+        if self.headless && self.generations.is_none() && self.replay.is_none() {
+            return Err(Error::Usage(
+                "--headless needs the --generations to compute, or a session to --replay"
+                    .to_string(),
+            ));
+        }
+        if display == Display::Plain && self.edit {
+            return Err(Error::Usage(
+                "--edit needs a terminal to draw on, which stdout isn't without --force-tui"
+                    .to_string(),
+            ));
+        }
+        if self.aspect == Aspect::Square && self.renderer != Renderer::Glyph {
+            let name = self.renderer.to_possible_value().unwrap();
+            return Err(Error::Usage(format!(
+                "--aspect square widens the characters of --renderer glyph, not the cells of \
+                 --renderer {}",
+                name.get_name()
+            )));
+        }
+        if self.style == CellStyle::Solid && self.renderer != Renderer::Glyph {
+            let name = self.renderer.to_possible_value().unwrap();
+            return Err(Error::Usage(format!(
+                "--style solid fills the characters of --renderer glyph, not the cells of \
+                 --renderer {}",
+                name.get_name()
+            )));
+        }
+        if self.style == CellStyle::Solid && self.color == Some(Theme::Never) {
+            return Err(Error::Usage(
+                "--style solid draws the cells in colors, which --color never has none of"
+                    .to_string(),
+            ));
+        }
+        if display == Display::Screen && self.style == CellStyle::Solid && !color::supported() {
+            eprintln!(
+                "warning: the terminal doesn't seem to show colors, drawing the glyphs of the cells \
+                 instead of solid ones"
+            );
+            self.style = CellStyle::Glyph;
+        }
+        if display == Display::Screen && !graphics::supported(self.renderer) {
+            let name = self.renderer.to_possible_value().unwrap();
+            eprintln!(
+                "warning: the terminal doesn't say it shows the bitmaps of --renderer {}, drawing characters instead",
+                name.get_name()
+            );
+            self.renderer = Renderer::Glyph;
+        }
+        Ok(())
+    }
+
+    /// Stdout is taken by the screen, so that these only go there when --headless
+    fn check_stdout(&self) -> Result<(), Error> {
+        if self.stats_on_stdout() && !self.headless {
+            return Err(Error::Usage(
+                "--stats go to a --stats-file unless --headless".to_string(),
+            ));
+        }
+        if self.frames_on_stdout() && !self.headless {
+            return Err(Error::Usage(
+                "--frames go to a --frames-dir unless --headless".to_string(),
+            ));
+        }
+        if self.stats_on_stdout() && self.frames_on_stdout() {
+            return Err(Error::Usage(
+                "--stats and --frames can't both go to stdout".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn stats_on_stdout(&self) -> bool {
+        self.stats.is_some() && self.stats_file.is_none()
+    }
+
+    fn frames_on_stdout(&self) -> bool {
+        self.frames.is_some() && self.frames_dir.is_none()
+    }
+
+    /// Rows below the field taken up by the status line and the sparkline
+    fn status_lines(&self) -> u16 {
+        u16::from(!self.no_status) + u16::from(self.sparkline)
+    }
 }
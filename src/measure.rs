@@ -0,0 +1,93 @@
+//! Telling how fast and which way a spaceship flies, from when its shape comes back
+
+use std::{collections::HashMap, fmt};
+
+use crate::{engine::EngineKind, Field, Life, Rules};
+
+/// How a spaceship moves: its shape comes back after a period, moved by a displacement
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Velocity {
+    pub period: u64,
+    /// Cells moved to the right and down every period, which are negative for left and up
+    pub dx: i64,
+    pub dy: i64,
+}
+
+impl fmt::Display for Velocity {
+    /// As a fraction of the speed of light in lowest terms and the direction, like `c/4
+    /// diagonal`, `2c/5 orthogonal` or `(2,1)c/6 oblique`
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (dx, dy) = (self.dx.unsigned_abs(), self.dy.unsigned_abs());
+        let (along, across) = (dx.max(dy), dx.min(dy));
+        let direction = match across {
+            0 => "orthogonal",
+            _ if across == along => "diagonal",
+            _ => "oblique",
+        };
+        let divisor = gcd(gcd(along, across), self.period);
+        let (along, across, period) = (along / divisor, across / divisor, self.period / divisor);
+        match (direction, along) {
+            ("oblique", _) => write!(f, "({along},{across})c")?,
+            (_, 1) => write!(f, "c")?,
+            _ => write!(f, "{along}c")?,
+        }
+        if period != 1 {
+            write!(f, "/{period}")?;
+        }
+        write!(f, " {direction}")
+    }
+}
+
+/// Runs `field` under `rules` on an unbounded plane until the shape of its cells comes back,
+/// for `limit` generations at most. Gives back how it moved in between, or none if it never
+/// came back, came back where it was like an oscillator does, or died out.
+pub fn measure(field: Field, rules: Rules, limit: u64) -> Result<Option<Velocity>, String> {
+    let turns = rules.turns() as u64;
+    let mut life = Life::new(field, rules, true, EngineKind::Naive, 1)?;
+    // The generation and the corner of the bounding box each shape was seen at, under the
+    // turn of the rules then, which has to come back too
+    let mut seen = HashMap::new();
+    loop {
+        let Some((shape, corner)) = shape(&life) else {
+            return Ok(None);
+        };
+        let generation = life.generation();
+        if let Some((before, start)) =
+            seen.insert((shape, generation % turns), (generation, corner))
+        {
+            let velocity = Velocity {
+                period: generation - before,
+                dx: corner.0 - start.0,
+                dy: corner.1 - start.1,
+            };
+            return Ok(((velocity.dx, velocity.dy) != (0, 0)).then_some(velocity));
+        }
+        if generation >= limit {
+            return Ok(None);
+        }
+        life.step();
+    }
+}
+
+/// Cells that are not dead with their states, sorted
+type Shape = Vec<((i64, i64), u8)>;
+
+/// The cells of `life` relative to the top left corner of their bounding box, which is given
+/// along with them, or none if all of them are dead
+fn shape(life: &Life) -> Option<(Shape, (i64, i64))> {
+    let mut cells = life.engine.cell_states();
+    let left = cells.iter().map(|&((x, _), _)| x).min()?;
+    let top = cells.iter().map(|&((_, y), _)| y).min()?;
+    for ((x, y), _) in &mut cells {
+        (*x, *y) = (*x - left, *y - top);
+    }
+    cells.sort_unstable();
+    Some((cells, (left, top)))
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    match b {
+        0 => a,
+        _ => gcd(b, a % b),
+    }
+}
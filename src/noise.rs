@@ -0,0 +1,86 @@
+//! Cells flipping at random after each generation, to see how well patterns hold up
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{
+    engine::Engine,
+    rule::{ALIVE, DEAD},
+};
+
+/// Flips each cell of the field independently with the same probability after each
+/// generation, the same for the same seed and generation
+pub struct Noise {
+    probability: f64,
+    seed: u64,
+    /// Drawn from anew every generation, so that all it takes to go on is the seed
+    rng: StdRng,
+    /// Cells flipped after the last generation
+    flips: u64,
+}
+
+impl Noise {
+    pub fn new(probability: f64, seed: u64) -> Self {
+        Self {
+            probability,
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+            flips: 0,
+        }
+    }
+
+    pub fn probability(&self) -> f64 {
+        self.probability
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn flips(&self) -> u64 {
+        self.flips
+    }
+
+    /// Flips the cells of the `width` by `height` field at the origin after `generation`,
+    /// bringing dead and dying ones to life and killing live ones. Returns how many more live
+    /// cells there are after.
+    pub fn apply(
+        &mut self,
+        engine: &mut dyn Engine,
+        width: usize,
+        height: usize,
+        generation: u64,
+    ) -> i64 {
+        // Not the same draws a random soup of the same seed is made of
+        let mix = generation
+            .wrapping_add(1)
+            .wrapping_mul(0x9e37_79b9_7f4a_7c15);
+        self.rng = StdRng::seed_from_u64(self.seed ^ mix);
+        self.flips = 0;
+        let cells = width as u64 * height as u64;
+        let mut growth = 0;
+        let mut i = self.skip();
+        while i < cells {
+            let (x, y) = ((i % width as u64) as i64, (i / width as u64) as i64);
+            if engine.state(x, y) == ALIVE {
+                engine.set(x, y, DEAD);
+                growth -= 1;
+            } else {
+                engine.set(x, y, ALIVE);
+                growth += 1;
+            }
+            self.flips += 1;
+            i = i.saturating_add(1 + self.skip());
+        }
+        growth
+    }
+
+    /// Cells to pass over until the next one that flips, drawn from the geometric
+    /// distribution so that it takes a draw per flip instead of one per cell
+    fn skip(&mut self) -> u64 {
+        if self.probability >= 1.0 {
+            return 0;
+        }
+        let draw: f64 = self.rng.gen();
+        ((1.0 - draw).ln() / (-self.probability).ln_1p()) as u64
+    }
+}
@@ -0,0 +1,221 @@
+//! Everything the generations are written to while they are computed, besides the screen
+
+use std::path::Path;
+
+use game_of_life::{
+    formats::Format,
+    image::SvgOptions,
+    session::{Event, Session, SessionRecorder},
+    tape::Recorder,
+    Life, Rule,
+};
+
+use crate::{
+    checkpoint::Checkpoints,
+    color,
+    error::Error,
+    frames::Frames,
+    pace::{frame_time, Pace},
+    recording::Recording,
+    server::Server,
+    setup::track,
+    snapshot::{Snapshots, Svg},
+    soups::Soups,
+    stats::Stats,
+    Config, Display,
+};
+
+/// Where the generations go besides the screen, each only if asked for
+pub struct Outputs {
+    pub stats: Option<Stats>,
+    pub checkpoints: Option<Checkpoints>,
+    pub recording: Option<Recording>,
+    /// With --record
+    pub tape: Option<Recorder>,
+    pub snapshots: Snapshots,
+    pub frames: Option<Frames>,
+    pub svg: Option<Svg>,
+    pub server: Option<Server>,
+    /// With --loop
+    pub soups: Option<Soups>,
+    /// With --record-session
+    pub session: Option<SessionRecorder>,
+    /// The session gone through again with --replay, whose edits are made as the run gets to
+    /// them
+    pub replay: Option<Session>,
+}
+
+impl Outputs {
+    /// Opens the outputs configured for the run of `life`, shown on `display`, with the
+    /// session to `replay` if there is one
+    pub fn new(
+        config: &Config,
+        life: &Life,
+        display: Display,
+        replay: Option<Session>,
+    ) -> Result<Self, Error> {
+        let stats = config
+            .stats
+            .map(|format| Stats::new(format, config.stats_file.as_deref()))
+            .transpose()
+            .map_err(Error::Io)?;
+        let checkpoints = config
+            .checkpoint_every
+            .map(|every| {
+                Checkpoints::new(
+                    every,
+                    &config.checkpoint_dir,
+                    config.checkpoint_keep as usize,
+                    config.strict,
+                )
+            })
+            .transpose()
+            .map_err(Error::Io)?;
+        let recording = config
+            .gif
+            .as_deref()
+            .map(|path| Recording::new(path, life, config.gif_scale, frame_time(config)))
+            .transpose()
+            .map_err(Error::Io)?;
+        let tape = config
+            .record
+            .as_deref()
+            .map(|path| Recorder::create(path, &life.rules))
+            .transpose()
+            .map_err(Error::Io)?;
+        let colors = color::states(
+            config.color.unwrap_or_default(),
+            config.alive_color,
+            config.dead_color,
+            life.rules.first() == Rule::Wireworld,
+        );
+        let snapshots = Snapshots::new(
+            config.snapshot.as_deref(),
+            &config.snapshot_at,
+            config.snapshot_scale,
+            colors.clone(),
+        )
+        .map_err(Error::Usage)?;
+        let frames = config
+            .frames
+            .map(|format| {
+                Frames::new(
+                    format,
+                    config.frames_dir.as_deref(),
+                    config.frames_scale,
+                    colors.clone(),
+                )
+            })
+            .transpose()
+            .map_err(Error::Io)?;
+        let svg = config.svg.clone().map(|path| Svg {
+            path,
+            options: SvgOptions {
+                cell_size: config.svg_cell_size,
+                palette: colors.clone(),
+                grid: config.svg_grid.map(|color| {
+                    let (r, g, b) = color.rgb();
+                    [r, g, b]
+                }),
+            },
+        });
+        let session = config
+            .record_session
+            .as_deref()
+            .map(|path| SessionRecorder::create(path, life))
+            .transpose()
+            .map_err(Error::Io)?;
+        let server = config
+            .listen
+            .map(|address| Server::new(address, frame_time(config)))
+            .transpose()
+            .map_err(Error::Io)?;
+        Ok(Self {
+            stats,
+            checkpoints,
+            recording,
+            tape,
+            snapshots,
+            frames,
+            svg,
+            server,
+            soups: config
+                .looping
+                .then(|| Soups::new(display == Display::Screen)),
+            session,
+            replay,
+        })
+    }
+
+    /// Writes an edit made at `generation` to the --record-session, if there is one
+    pub fn edit(&mut self, generation: u64, event: impl FnOnce() -> Event) -> Result<(), String> {
+        match &mut self.session {
+            Some(session) => session.record(generation, &event()),
+            None => Ok(()),
+        }
+    }
+
+    /// Finishes writing the outputs once the simulation stopped at `life`
+    pub fn finish(self, life: &Life, display: Display) -> Result<(), Error> {
+        self.snapshots.finish(life).map_err(Error::Io)?;
+        if let Some(svg) = &self.svg {
+            svg.write(life).map_err(Error::Io)?;
+        }
+        if let Some(recording) = self.recording {
+            recording.finish().map_err(Error::Io)?;
+        }
+        if let Some(tape) = self.tape {
+            tape.finish().map_err(Error::Io)?;
+        }
+        if let Some(session) = self.session {
+            session.finish(life.generation()).map_err(Error::Io)?;
+        }
+        // Only shown for a moment on the screen, which is gone by now
+        if let (Some(err), Display::Screen) = (
+            self.checkpoints.as_ref().and_then(Checkpoints::error),
+            display,
+        ) {
+            eprintln!("warning: {err}");
+        }
+        Ok(())
+    }
+}
+
+/// The file the last generation is saved to, if any, and its format
+pub fn output(config: &Config) -> Option<(&Path, Format)> {
+    config
+        .output
+        .as_deref()
+        .map(|path| (path, Format::from_extension(path).unwrap_or(Format::Rle)))
+}
+
+/// Makes the edits of the session replayed that are due at the generation `life` is at, and
+/// with a `pace` the changes of speed, giving back whether the session is over there
+pub fn replay(
+    life: &mut Life,
+    config: &Config,
+    outputs: &mut Outputs,
+    mut pace: Option<&mut Pace>,
+) -> Result<bool, String> {
+    let Some(session) = &mut outputs.replay else {
+        return Ok(false);
+    };
+    while let Some(event) = session.next(life.generation())? {
+        event.apply(life, config.engine, config.threads.get())?;
+        if let (Event::Speed(fps), Some(pace)) = (&event, &mut pace) {
+            pace.set(*fps);
+        }
+        // As a soup reseeded to is when the run starts over
+        if let Event::Reseed(_) = event {
+            track(life, config)?;
+            if let Some(stats) = &mut outputs.stats {
+                stats.record(life)?;
+            }
+            if let Some(tape) = &mut outputs.tape {
+                tape.record(life)?;
+            }
+            outputs.snapshots.record(life)?;
+        }
+    }
+    Ok(session.ended(life.generation()))
+}
@@ -0,0 +1,71 @@
+//! How fast the generations go by
+
+use std::time::{Duration, Instant};
+
+use crate::Config;
+
+/// The time between frames, which is none when running as fast as possible
+pub fn frame_time(config: &Config) -> Duration {
+    Pace::new(config).frame_time()
+}
+
+/// How fast the generations are shown, which keys and the sessions replayed change as they go
+pub struct Pace {
+    /// Frames a second, or 0 for as fast as possible
+    pub fps: f64,
+    /// When the next frame is due
+    pub next_frame: Instant,
+}
+
+impl Pace {
+    pub fn new(config: &Config) -> Self {
+        let fps = if config.max_speed { 0.0 } else { config.fps };
+        let mut pace = Self {
+            fps,
+            next_frame: Instant::now(),
+        };
+        pace.next_frame += pace.frame_time();
+        pace
+    }
+
+    pub fn frame_time(&self) -> Duration {
+        match self.fps {
+            0.0 => Duration::ZERO,
+            fps => Duration::from_secs_f64(1.0 / fps),
+        }
+    }
+
+    /// The speed a step faster or slower, within the speeds keys step through, where going
+    /// slower than as fast as possible starts from the fastest of those
+    pub fn stepped(&self, faster: bool) -> f64 {
+        match (self.fps, faster) {
+            (0.0, true) => 0.0,
+            (0.0, false) => MAX_FPS,
+            (fps, true) => (fps * SPEED_STEP).clamp(MIN_FPS, MAX_FPS),
+            (fps, false) => (fps / SPEED_STEP).clamp(MIN_FPS, MAX_FPS),
+        }
+    }
+
+    /// Goes at `fps` from the frame being waited for on, which comes as long after the last
+    /// one as the new speed has it, giving back whether the speed changed
+    pub fn set(&mut self, fps: f64) -> bool {
+        if fps == self.fps {
+            return false;
+        }
+        let last = self.next_frame.checked_sub(self.frame_time());
+        self.fps = fps;
+        let frame_time = self.frame_time();
+        self.next_frame = last.map_or(self.next_frame, |last| last + frame_time);
+        true
+    }
+}
+
+/// How many times faster or slower `*` and `/` make the generations go
+const SPEED_STEP: f64 = 1.5;
+
+/// The slowest and fastest speeds `*` and `/` go to, in frames a second
+const MIN_FPS: f64 = 0.5;
+const MAX_FPS: f64 = 500.0;
+
+/// The speeds the digits go at while running, in frames a second
+pub const SPEEDS: [f64; 9] = [1.0, 2.0, 5.0, 10.0, 15.0, 20.0, 30.0, 60.0, 120.0];
@@ -0,0 +1,178 @@
+use std::{fmt, fs, path::Path};
+
+use crate::{formats::Format, rule::Rule, Field, LoadError};
+
+/// A finite arrangement of live cells, independent of any field
+#[derive(Clone)]
+pub struct Pattern {
+    pub width: usize,
+    pub height: usize,
+    /// Coordinates of the live cells, relative to the top-left corner
+    pub cells: Vec<(usize, usize)>,
+    /// Rule the pattern was made for, if the format records one
+    pub rule: Option<Rule>,
+    /// Notes written along with the pattern, in the order they came in
+    pub comments: Vec<Comment>,
+}
+
+/// A line of notes about a pattern, of the kinds RLE tells apart
+#[derive(Clone, PartialEq, Eq)]
+pub enum Comment {
+    /// `#N` in RLE
+    Name(String),
+    /// `#O` in RLE, who made the pattern and when
+    Author(String),
+    /// `#C` in RLE
+    Note(String),
+}
+
+impl Comment {
+    pub fn text(&self) -> &str {
+        match self {
+            Self::Name(text) | Self::Author(text) | Self::Note(text) => text,
+        }
+    }
+}
+
+/// As the formats without kinds of comments write them, labeling the name and author the way
+/// the LifeWiki does
+impl fmt::Display for Comment {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Name(name) => write!(f, "Name: {name}"),
+            Self::Author(author) => write!(f, "Author: {author}"),
+            Self::Note(note) => write!(f, "{note}"),
+        }
+    }
+}
+
+impl Pattern {
+    /// Reads a pattern file, detecting its format from the extension unless `format` is given
+    pub fn load(path: &Path, format: Option<Format>) -> Result<Self, LoadError> {
+        let format = Format::detect(path, format).map_err(LoadError::Parse)?;
+        let text = fs::read_to_string(path)
+            .map_err(|err| LoadError::Read(format!("failed to read {}: {err}", path.display())))?;
+        format
+            .parse(&text)
+            .map_err(|err| LoadError::Parse(format!("{}: {err}", path.display())))
+    }
+
+    pub fn save(&self, path: &Path, format: Format) -> Result<(), String> {
+        fs::write(path, format.write(self))
+            .map_err(|err| format!("failed to write {}: {err}", path.display()))
+    }
+
+    /// Collects the live cells of the whole field, keeping its dimensions
+    pub fn from_field(field: &Field, rule: Rule) -> Self {
+        let mut cells = Vec::new();
+        for y in 0..field.height {
+            for x in 0..field.width {
+                if field.is_alive(x as i64, y as i64) {
+                    cells.push((x, y));
+                }
+            }
+        }
+
+        Self {
+            width: field.width,
+            height: field.height,
+            cells,
+            rule: Some(rule),
+            comments: Vec::new(),
+        }
+    }
+
+    pub fn place_centered(&self, field: &mut Field) -> Result<(), String> {
+        if self.width > field.width || self.height > field.height {
+            return Err(format!(
+                "pattern is {}x{}, which does not fit in the {}x{} field (see --width and --height)",
+                self.width, self.height, field.width, field.height
+            ));
+        }
+
+        let dx = (field.width - self.width) / 2;
+        let dy = (field.height - self.height) / 2;
+        for &(x, y) in &self.cells {
+            field
+                .set(x + dx, y + dy, true)
+                .map_err(|err| err.to_string())?;
+        }
+        Ok(())
+    }
+
+    /// Copies the whole bounding box of the pattern, dead cells included, with its top-left
+    /// corner at (`x`, `y`). Cells falling outside the field are dropped if `clip` is set,
+    /// otherwise they make the placement fail without touching the field.
+    pub fn place_at(&self, field: &mut Field, x: i64, y: i64, clip: bool) -> Result<(), String> {
+        let fits = x >= 0
+            && y >= 0
+            && x + self.width as i64 <= field.width as i64
+            && y + self.height as i64 <= field.height as i64;
+        if !fits && !clip {
+            return Err(format!(
+                "{}x{} pattern at {x},{y} does not fit in the {}x{} field (see --clip)",
+                self.width, self.height, field.width, field.height
+            ));
+        }
+
+        let mut grid = vec![false; self.width * self.height];
+        for &(cx, cy) in &self.cells {
+            grid[cy * self.width + cx] = true;
+        }
+        for py in 0..self.height {
+            for px in 0..self.width {
+                let (fx, fy) = (x + px as i64, y + py as i64);
+                if let (Ok(fx), Ok(fy)) = (usize::try_from(fx), usize::try_from(fy)) {
+                    let alive = grid[py * self.width + px];
+                    // Out of bounds cells are only possible when clipping
+                    let _ = field.set(fx, fy, alive);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Crops the pattern to the bounding box of its live cells
+    pub fn trimmed(mut self) -> Self {
+        let left = self.cells.iter().map(|&(x, _)| x).min().unwrap_or(0);
+        let top = self.cells.iter().map(|&(_, y)| y).min().unwrap_or(0);
+        for (x, y) in &mut self.cells {
+            (*x, *y) = (*x - left, *y - top);
+        }
+        self.width = self.cells.iter().map(|&(x, _)| x + 1).max().unwrap_or(0);
+        self.height = self.cells.iter().map(|&(_, y)| y + 1).max().unwrap_or(0);
+        self
+    }
+
+    /// Rotates the pattern clockwise by 90 degrees
+    pub fn rotated(self) -> Self {
+        let height = self.height;
+        Self {
+            width: self.height,
+            height: self.width,
+            cells: self
+                .cells
+                .into_iter()
+                .map(|(x, y)| (height - 1 - y, x))
+                .collect(),
+            rule: self.rule,
+            comments: self.comments,
+        }
+    }
+
+    /// Mirrors the pattern left to right
+    pub fn flipped_x(mut self) -> Self {
+        for (x, _) in &mut self.cells {
+            *x = self.width - 1 - *x;
+        }
+        self
+    }
+
+    /// Mirrors the pattern top to bottom
+    pub fn flipped_y(mut self) -> Self {
+        for (_, y) in &mut self.cells {
+            *y = self.height - 1 - *y;
+        }
+        self
+    }
+}
@@ -0,0 +1,85 @@
+//! `--place` specifications like `glider@10,5:rot90:flipx`
+
+use std::{path::PathBuf, str::FromStr};
+
+use crate::{library, pattern::Pattern, Field};
+
+#[derive(Clone)]
+pub struct Placement {
+    source: Source,
+    x: i64,
+    y: i64,
+    transforms: Vec<Transform>,
+}
+
+#[derive(Clone)]
+enum Source {
+    Library(&'static str),
+    File(PathBuf),
+}
+
+#[derive(Clone, Copy)]
+enum Transform {
+    /// Clockwise rotation by this many quarter turns
+    Rotate(u8),
+    FlipX,
+    FlipY,
+}
+
+impl Placement {
+    pub fn apply(&self, field: &mut Field, clip: bool) -> Result<(), String> {
+        let mut pattern = match &self.source {
+            Source::Library(name) => library::get(name).unwrap(),
+            Source::File(path) => Pattern::load(path, None)?,
+        };
+        for &transform in &self.transforms {
+            pattern = match transform {
+                Transform::Rotate(turns) => (0..turns).fold(pattern, |p, _| p.rotated()),
+                Transform::FlipX => pattern.flipped_x(),
+                Transform::FlipY => pattern.flipped_y(),
+            };
+        }
+        pattern.place_at(field, self.x, self.y, clip)
+    }
+}
+
+impl FromStr for Placement {
+    type Err = String;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let (source, rest) = spec
+            .rsplit_once('@')
+            .ok_or("expected `<pattern>@<x>,<y>[:<transform>...]`")?;
+        let source = match library::parse_name(source) {
+            Ok(name) => Source::Library(name),
+            Err(_) => Source::File(PathBuf::from(source)),
+        };
+
+        let mut parts = rest.split(':');
+        let offset = parts.next().unwrap();
+        let (x, y) = offset
+            .split_once(',')
+            .and_then(|(x, y)| Some((x.trim().parse().ok()?, y.trim().parse().ok()?)))
+            .ok_or(format!("invalid offset `{offset}`, expected `<x>,<y>`"))?;
+
+        let transforms = parts
+            .map(|transform| match transform {
+                "rot90" => Ok(Transform::Rotate(1)),
+                "rot180" => Ok(Transform::Rotate(2)),
+                "rot270" => Ok(Transform::Rotate(3)),
+                "flipx" => Ok(Transform::FlipX),
+                "flipy" => Ok(Transform::FlipY),
+                _ => Err(format!(
+                    "unknown transform `{transform}`, expected one of rot90, rot180, rot270, flipx, flipy"
+                )),
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            source,
+            x,
+            y,
+            transforms,
+        })
+    }
+}
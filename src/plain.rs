@@ -0,0 +1,106 @@
+//! Printing the generations as plain text one after another
+
+use std::{
+    io::{stdout, BufWriter, Write},
+    thread,
+    time::Instant,
+};
+
+use game_of_life::{session::Event, state, Life};
+
+use crate::{
+    outputs::{output, replay, Outputs},
+    pace::Pace,
+    setup::reseed,
+    soups,
+    stop::{self, Stop},
+    Config,
+};
+
+/// Prints the generations shown one after another as plain text, each followed by a line that
+/// tells it apart from the next, at the pace of the frames until the simulation stops. With
+/// --quiet nothing is printed but the summary at the end.
+pub fn run(life: &mut Life, config: &Config, outputs: &mut Outputs) -> Result<Stop, String> {
+    let output = output(config);
+    let write_error = |err| format!("failed to write to stdout: {err}");
+    let mut stdout = BufWriter::new(stdout().lock());
+    let mut pace = Pace::new(config);
+    if let Some(stats) = &mut outputs.stats {
+        stats.record(life)?;
+    }
+    if let Some(tape) = &mut outputs.tape {
+        tape.record(life)?;
+    }
+    outputs.snapshots.record(life)?;
+    loop {
+        if !config.quiet {
+            writeln!(
+                stdout,
+                "{}-- gen {} pop {}",
+                life.board(),
+                life.generation(),
+                life.population()
+            )
+            .and_then(|()| stdout.flush())
+            .map_err(write_error)?;
+        }
+        if let Some(recording) = &mut outputs.recording {
+            recording.record(life)?;
+        }
+        if let Some(frames) = &mut outputs.frames {
+            frames.record(life)?;
+        }
+        if let Some(server) = &mut outputs.server {
+            server.record(life);
+        }
+        replay(life, config, outputs, Some(&mut pace))?;
+        if let Some(stop) = stop::check(life, config, outputs.replay.as_ref(), true) {
+            let Some(soups) = &mut outputs.soups else {
+                if let Some((path, format)) = output {
+                    life.save(path, format)?;
+                }
+                return Ok(stop);
+            };
+            soups.finish(life, stop);
+            thread::sleep(config.loop_pause);
+            let generation = life.generation();
+            reseed(life, config, soups::next_seed(life))?;
+            outputs.edit(generation, || Event::Reseed(state::write(life)))?;
+            if let Some(stats) = &mut outputs.stats {
+                stats.record(life)?;
+            }
+            if let Some(tape) = &mut outputs.tape {
+                tape.record(life)?;
+            }
+            outputs.snapshots.record(life)?;
+            pace.next_frame = Instant::now() + pace.frame_time();
+            continue;
+        }
+
+        thread::sleep(pace.next_frame.saturating_duration_since(Instant::now()));
+        pace.next_frame += pace.frame_time();
+        // Not going past the limit, where the simulation stops
+        let mut steps = config.render_every;
+        if let Some(limit) = config.generations {
+            steps = steps.min(limit.saturating_sub(life.generation()));
+        }
+        for _ in 0..steps {
+            if replay(life, config, outputs, Some(&mut pace))? {
+                break;
+            }
+            life.step();
+            outputs.snapshots.record(life)?;
+            if let Some(stats) = &mut outputs.stats {
+                stats.record(life)?;
+            }
+            if let Some(tape) = &mut outputs.tape {
+                tape.record(life)?;
+            }
+            if let Some(checkpoints) = &mut outputs.checkpoints {
+                if let Some(err) = checkpoints.record(life)? {
+                    eprintln!("warning: {err}");
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,147 @@
+//! Recording the generations into an animated GIF, to share a run
+
+use std::{
+    fs::File,
+    io::BufWriter,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use game_of_life::{
+    gif::Encoder,
+    rule::{wireworld, ALIVE, DEAD},
+    variant::Side,
+    Life, Rule,
+};
+
+/// Dead cells on black, live ones in white and dying ones in gray, with the colors of
+/// Immigration and Wireworld the same as on screen
+const PALETTE: [[u8; 3]; 6] = [
+    [0x00, 0x00, 0x00],
+    [0xff, 0xff, 0xff],
+    [0x5f, 0x5f, 0x5f],
+    [0xff, 0x40, 0x40],
+    [0x40, 0x90, 0xff],
+    [0xff, 0xb0, 0x00],
+];
+const DEAD_COLOR: u8 = 0;
+const ALIVE_COLOR: u8 = 1;
+const DYING_COLOR: u8 = 2;
+const RED: u8 = 3;
+const BLUE: u8 = 4;
+const AMBER: u8 = 5;
+
+/// Frames of the field as it was when the recording started, each cell a square block of
+/// pixels, written out as they come
+pub struct Recording {
+    encoder: Encoder<BufWriter<File>>,
+    path: PathBuf,
+    width: usize,
+    height: usize,
+    scale: u16,
+    /// Hundredths of a second each frame is shown for
+    delay: u16,
+    /// The pixels of the frame being written, kept to reuse the allocation
+    pixels: Vec<u8>,
+    /// The generation of the last frame, which isn't recorded again while paused
+    last: Option<u64>,
+}
+
+impl Recording {
+    /// Starts recording the field of `life` into the file at `path`, showing each frame for
+    /// `frame_time`, or as briefly as players do for none
+    pub fn new(path: &Path, life: &Life, scale: u16, frame_time: Duration) -> Result<Self, String> {
+        let size = |cells: usize| {
+            u16::try_from(cells)
+                .ok()
+                .and_then(|cells| cells.checked_mul(scale))
+                .ok_or(format!("a {scale} times larger field doesn't fit in a GIF"))
+        };
+        let (width, height) = (size(life.width)?, size(life.height)?);
+        let file = File::create(path)
+            .map_err(|err| format!("failed to create {}: {err}", path.display()))?;
+        let encoder = Encoder::new(BufWriter::new(file), width, height, &PALETTE)
+            .map_err(|err| format!("failed to write {}: {err}", path.display()))?;
+        // Players show frames of less than 2 hundredths of a second for longer
+        let delay = (frame_time.as_secs_f64() * 100.0)
+            .round()
+            .clamp(2.0, u16::MAX as f64) as u16;
+        Ok(Self {
+            encoder,
+            path: path.to_path_buf(),
+            width: life.width,
+            height: life.height,
+            scale,
+            delay,
+            pixels: Vec::with_capacity(width as usize * height as usize),
+            last: None,
+        })
+    }
+
+    /// Adds the generation `life` is at, unless it is the one of the last frame
+    pub fn record(&mut self, life: &Life) -> Result<(), String> {
+        if self.last == Some(life.generation()) {
+            return Ok(());
+        }
+        self.last = Some(life.generation());
+        let scale = self.scale as usize;
+        self.pixels.clear();
+        for y in 0..self.height as i64 {
+            let row = self.pixels.len();
+            for x in 0..self.width as i64 {
+                let color = color(life, x, y);
+                self.pixels.extend(std::iter::repeat_n(color, scale));
+            }
+            for _ in 1..scale {
+                self.pixels
+                    .extend_from_within(row..row + self.width * scale);
+            }
+        }
+        self.encoder
+            .frame(&self.pixels, self.delay)
+            .map_err(|err| format!("failed to write {}: {err}", self.path.display()))
+    }
+
+    /// Ends the animation, which is only a valid GIF after that
+    pub fn finish(self) -> Result<(), String> {
+        self.encoder
+            .finish()
+            .map(drop)
+            .map_err(|err| format!("failed to write {}: {err}", self.path.display()))
+    }
+}
+
+fn color(life: &Life, x: i64, y: i64) -> u8 {
+    let state = life.engine.state(x, y);
+    if life.rules.first() == Rule::Wireworld {
+        return match state {
+            DEAD => DEAD_COLOR,
+            wireworld::HEAD => BLUE,
+            wireworld::TAIL => RED,
+            _ => AMBER,
+        };
+    }
+    if let Rule::Ant(turmite) = life.rules.first() {
+        return match (turmite.heading(state), turmite.color(state)) {
+            (Some(_), _) => RED,
+            (None, 0) => DEAD_COLOR,
+            (None, 1) => ALIVE_COLOR,
+            _ => DYING_COLOR,
+        };
+    }
+    match state {
+        DEAD => DEAD_COLOR,
+        ALIVE => {
+            let side = life
+                .immigration
+                .as_ref()
+                .and_then(|immigration| immigration.get(x, y));
+            match side {
+                Some(Side::First) => RED,
+                Some(Side::Second) => BLUE,
+                None => ALIVE_COLOR,
+            }
+        }
+        _ => DYING_COLOR,
+    }
+}
@@ -0,0 +1,1031 @@
+//! Drawing the generations in the terminal
+
+use std::{io, iter, mem, panic, thread};
+
+use clap::ValueEnum;
+use crossterm::{
+    cursor, event,
+    style::{self, Attribute},
+    terminal::{self, ClearType},
+};
+
+use game_of_life::{
+    rule::{wireworld, Neighborhood, ALIVE, DEAD},
+    trail,
+    variant::Side,
+    Boundary, Field, Life, Rect, Rule, Topology,
+};
+
+use crate::{
+    charset::Glyphs,
+    color::{self, Palette},
+    graphics::{self, Graphics},
+};
+
+/// Frames after which the whole screen is drawn again, even if only some cells changed, in
+/// case the terminal got out of sync
+const REPAINT_INTERVAL: u32 = 100;
+
+/// Shown for the cells of a stamp that has yet to be placed
+const PREVIEW: char = 'o';
+
+/// The braille pattern without any dots, which the others add to
+const BLANK_BRAILLE: char = '\u{2800}';
+
+/// Blocks of cells in a zoomed out view, from empty to full
+const DENSITY: [char; color::SHADES] = [' ', '░', '▒', '▓', '█'];
+
+/// Fraction of the way to the cells that changed that the viewport goes each frame when it
+/// follows them, so that it doesn't jitter along with every cell
+const FOLLOW_DAMPING: f64 = 0.2;
+
+/// Zoom factors go up to blocks of this many cells on a side
+pub const MAX_ZOOM: u32 = 1 << 12;
+
+/// Switches the terminal to a screen of its own in raw mode with the cursor hidden for as
+/// long as it is alive, and back when dropped, also when returning early with an error. A
+/// panic restores the terminal before its message is printed, so that it stays readable.
+pub struct TerminalGuard;
+
+impl TerminalGuard {
+    pub fn enter() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        let guard = Self;
+        crossterm::execute!(
+            io::stdout(),
+            terminal::EnterAlternateScreen,
+            cursor::Hide,
+            event::EnableMouseCapture
+        )?;
+
+        let default_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            restore_terminal();
+            default_hook(info);
+        }));
+        Ok(guard)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        restore_terminal();
+        // Taking the hook while panicking is not allowed, and the process is going down anyway
+        if !thread::panicking() {
+            let _ = panic::take_hook();
+        }
+    }
+}
+
+fn restore_terminal() {
+    // There is nothing left to do about a failure at this point
+    let _ = crossterm::execute!(
+        io::stdout(),
+        event::DisableMouseCapture,
+        cursor::Show,
+        terminal::LeaveAlternateScreen
+    );
+    let _ = terminal::disable_raw_mode();
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum Resize {
+    /// Show as much of the field as fits in the terminal
+    #[default]
+    Clamp,
+    /// Grow or crop the field to fill the terminal, the new cells being dead
+    Grow,
+}
+
+/// What the cells on screen show
+#[derive(Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum View {
+    /// Whether they are alive
+    #[default]
+    Cells,
+    /// How often they were born or died lately, shaded from cold to hot, which leaves still
+    /// lifes blank and shows where oscillators and spaceships are or have been
+    Heatmap,
+}
+
+/// How the cells are drawn
+#[derive(Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum Renderer {
+    /// A character per cell, showing the state of dying cells
+    #[default]
+    Glyph,
+    /// Two cells above each other per character, drawn as half blocks, which makes the cells
+    /// about as tall as they are wide
+    Halfblock,
+    /// Blocks of 2x4 cells per character, drawn as the dots of braille patterns
+    Braille,
+    /// A bitmap of 4x8 cells per character, shown as a sixel image by the terminals that can,
+    /// such as xterm, foot and WezTerm
+    Sixel,
+    /// A bitmap of 4x8 cells per character, shown through the graphics protocol of Kitty,
+    /// which Ghostty and WezTerm speak too
+    Kitty,
+}
+
+/// What the cells drawn a character each are made of
+#[derive(Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum CellStyle {
+    /// Their glyphs, in the colors if any
+    #[default]
+    Glyph,
+    /// Blanks in the colors as their background, with no glyphs at all
+    Solid,
+}
+
+impl Renderer {
+    /// Columns and rows of the field in each character
+    fn cells(self) -> (u16, u16) {
+        match self {
+            Self::Glyph => (1, 1),
+            Self::Halfblock => (1, 2),
+            Self::Braille => (2, 4),
+            Self::Sixel | Self::Kitty => graphics::CELLS,
+        }
+    }
+
+    /// Whether cells of hexagonal fields are spaced out, to shift odd rows by half a cell.
+    /// Characters of several cells have no way of shifting some of their rows.
+    fn spaced(self, neighborhood: Neighborhood) -> bool {
+        self == Self::Glyph && neighborhood == Neighborhood::Hexagonal
+    }
+
+    /// Columns of the terminal each character takes up. Only the glyphs of a character per
+    /// cell can be wider than one column.
+    fn width(self, glyphs: &Glyphs) -> u16 {
+        match self {
+            Self::Glyph => glyphs.width,
+            _ => 1,
+        }
+    }
+
+    /// Columns of the terminal from one character to the next, which spaced out ones are
+    /// twice as far apart
+    fn stride(self, neighborhood: Neighborhood, glyphs: &Glyphs) -> u16 {
+        self.width(glyphs) * if self.spaced(neighborhood) { 2 } else { 1 }
+    }
+}
+
+/// The largest field that fits in a terminal of the given columns and rows, leaving the last
+/// rows for as many status lines
+pub fn fit(
+    terminal: (u16, u16),
+    neighborhood: Neighborhood,
+    renderer: Renderer,
+    glyphs: &Glyphs,
+    status_lines: u16,
+) -> (usize, usize) {
+    let stride = renderer.stride(neighborhood, glyphs);
+    let (columns, lines) = fit_lines(terminal, stride, status_lines);
+    let (width, height) = renderer.cells();
+    (
+        usize::from(columns) * usize::from(width),
+        usize::from(lines) * usize::from(height),
+    )
+}
+
+/// The characters and lines of the terminal that the field can take up, with each character
+/// `stride` columns apart
+fn fit_lines((columns, rows): (u16, u16), stride: u16, status_lines: u16) -> (u16, u16) {
+    (
+        (columns / stride).max(1),
+        rows.saturating_sub(status_lines).max(1),
+    )
+}
+
+/// A character split into an upper and a lower half, each of which is either filled or not
+fn half_block(upper: bool, lower: bool) -> char {
+    match (upper, lower) {
+        (false, false) => ' ',
+        (true, false) => '▀',
+        (false, true) => '▄',
+        (true, true) => '█',
+    }
+}
+
+pub struct Screen {
+    /// Glyphs of the cells on screen, their colors if any and whether they are selected, row
+    /// by row, empty before the first frame
+    shown: Vec<(char, Option<style::Color>, bool)>,
+    /// Glyphs of the frame being drawn, kept to reuse the allocation
+    glyphs: Vec<(char, Option<style::Color>, bool)>,
+    /// Terminal output of the frame being drawn, so that it can be written all at once
+    frame: Vec<u8>,
+    /// Draw every frame in full instead of only the cells that changed
+    full_redraw: bool,
+    frames_since_repaint: u32,
+    /// Columns and rows of the terminal
+    terminal: (u16, u16),
+    /// Size of the part of the field on screen, in characters
+    visible: (u16, u16),
+    /// Cell of the field at the top left corner of the screen
+    origin: (i64, i64),
+    /// Each cell on screen stands for a square block of this many cells on a side
+    zoom: u32,
+    /// Whether the viewport keeps the cells that changed in the last generation centered
+    follow: bool,
+    /// The cell at the center of the viewport while following, kept to a fraction of a cell
+    camera: Option<(f64, f64)>,
+    renderer: Renderer,
+    view: View,
+    /// Glyphs of live and dead cells
+    charset: Glyphs,
+    /// Colors of the cells, if any
+    palette: Option<Palette>,
+    /// Rows at the bottom of the terminal that are kept for status lines
+    status_lines: u16,
+    style: CellStyle,
+    /// The foreground color the terminal was last set to in the frame being drawn, or the
+    /// background one when drawing solid cells
+    color: Option<style::Color>,
+    /// Whether the terminal was last set to reverse video in the frame being drawn
+    reversed: bool,
+    /// Cells of the field drawn in reverse video, as a rectangle being selected is
+    selection: Option<Rect>,
+    /// How bitmaps are shown, when drawing them instead of characters
+    graphics: Option<Box<dyn Graphics>>,
+    /// Color indices of the pixels of the bitmap on screen and of the one being drawn, row by
+    /// row, the first one empty before the first frame
+    picture: (Vec<u8>, Vec<u8>),
+}
+
+impl Screen {
+    pub fn new(
+        full_redraw: bool,
+        origin: (i64, i64),
+        zoom: u32,
+        follow: bool,
+        renderer: Renderer,
+        charset: Glyphs,
+        palette: Option<Palette>,
+    ) -> Self {
+        Self {
+            shown: Vec::new(),
+            glyphs: Vec::new(),
+            frame: Vec::new(),
+            full_redraw,
+            frames_since_repaint: 0,
+            terminal: terminal::size().unwrap_or((u16::MAX, u16::MAX)),
+            visible: (0, 0),
+            origin,
+            zoom,
+            follow,
+            camera: None,
+            renderer,
+            view: View::default(),
+            charset,
+            palette,
+            status_lines: 1,
+            style: CellStyle::Glyph,
+            color: None,
+            reversed: false,
+            selection: None,
+            graphics: None,
+            picture: (Vec::new(), Vec::new()),
+        }
+    }
+
+    /// Draws the cells in solid colors rather than their glyphs, which only applies to a
+    /// character per cell in colors
+    pub fn set_style(&mut self, style: CellStyle) {
+        self.style = style;
+        self.invalidate();
+    }
+
+    /// Draws the cells of a rectangle of the field in reverse video, which bitmaps don't show
+    pub fn set_selection(&mut self, selection: Option<Rect>) {
+        self.selection = selection;
+    }
+
+    /// Draws the field as bitmaps shown this way, instead of characters
+    pub fn set_graphics(&mut self, graphics: Box<dyn Graphics>) {
+        self.graphics = Some(graphics);
+        self.invalidate();
+    }
+
+    /// Works out how much of the field fits on screen, and keeps the viewport within the
+    /// edges of a field that doesn't wrap around. On a wrapping field it is moved back
+    /// over the field if it went past an edge, and on an unbounded plane it is free to go
+    /// anywhere.
+    pub fn fit_viewport(&mut self, life: &Life) {
+        let stride = self
+            .renderer
+            .stride(life.rules.neighborhood(), &self.charset);
+        let (fit_width, fit_height) = fit_lines(self.terminal, stride, self.status_lines);
+        let (scale_x, scale_y) = self.scale();
+        // Within the terminal, however large the field is
+        let blocks = |cells: usize, scale: i64, fit: u16| {
+            (cells as u64).div_ceil(scale as u64).min(u64::from(fit)) as u16
+        };
+        let (width, height) = (
+            blocks(life.width, scale_x, fit_width),
+            blocks(life.height, scale_y, fit_height),
+        );
+        if (width, height) != self.visible {
+            self.visible = (width, height);
+            self.invalidate();
+        }
+
+        let (x, y) = self.origin;
+        let (field_width, field_height) = (life.width as i64, life.height as i64);
+        self.origin = match life.edges {
+            None => (x, y),
+            // Twisted seams only repeat after being crossed twice
+            Some((Boundary::Wrap, Topology::Torus)) => {
+                (x.rem_euclid(field_width), y.rem_euclid(field_height))
+            }
+            Some((Boundary::Wrap, _)) => (
+                x.rem_euclid(2 * field_width),
+                y.rem_euclid(2 * field_height),
+            ),
+            Some(_) => (
+                x.clamp(0, (field_width - width as i64 * scale_x).max(0)),
+                y.clamp(0, (field_height - height as i64 * scale_y).max(0)),
+            ),
+        };
+    }
+
+    /// Columns and rows of the field that each character on screen stands for
+    fn scale(&self) -> (i64, i64) {
+        let zoom = self.zoom as i64;
+        let (width, height) = self.renderer.cells();
+        (zoom * width as i64, zoom * height as i64)
+    }
+
+    /// The cell of the field at the center of the screen, as far as it is on the field
+    fn center(&self) -> (f64, f64) {
+        let (scale_x, scale_y) = self.scale();
+        let half = |cells: u16, scale: i64| cells as f64 * scale as f64 / 2.0;
+        (
+            self.origin.0 as f64 + half(self.visible.0, scale_x),
+            self.origin.1 as f64 + half(self.visible.1, scale_y),
+        )
+    }
+
+    pub fn following(&self) -> bool {
+        self.follow
+    }
+
+    pub fn set_follow(&mut self, follow: bool) {
+        self.follow = follow;
+        self.camera = None;
+    }
+
+    /// Moves the viewport part of the way towards the centroid of the cells that changed in
+    /// the last generation, if following them and any did
+    pub fn follow(&mut self, life: &Life) {
+        if !self.follow {
+            return;
+        }
+        let Some((x, y)) = life.engine.changes().centroid() else {
+            return;
+        };
+        let (center_x, center_y) = self.center();
+        let (width, height) = (
+            center_x - self.origin.0 as f64,
+            center_y - self.origin.1 as f64,
+        );
+        let (camera_x, camera_y) = self.camera.unwrap_or((center_x, center_y));
+        let (mut dx, mut dy) = (x as f64 - camera_x, y as f64 - camera_y);
+        // Around a torus the way to the cells is the shorter one, across the edge if need be
+        if life.edges == Some((Boundary::Wrap, Topology::Torus)) {
+            let shortest = |d: f64, size: usize| {
+                let size = size as f64;
+                (d + size / 2.0).rem_euclid(size) - size / 2.0
+            };
+            (dx, dy) = (shortest(dx, life.width), shortest(dy, life.height));
+        }
+        let camera @ (x, y) = (
+            camera_x + dx * FOLLOW_DAMPING,
+            camera_y + dy * FOLLOW_DAMPING,
+        );
+        self.camera = Some(camera);
+        let top = self.origin.1;
+        self.origin = ((x - width).round() as i64, (y - height).round() as i64);
+        self.fit_viewport(life);
+        self.reshift(life, top);
+    }
+
+    /// Keeps this many rows below the field for status lines, giving any others to the field
+    pub fn set_status_lines(&mut self, lines: u16) {
+        self.status_lines = lines;
+        self.invalidate();
+    }
+
+    pub fn view(&self) -> View {
+        self.view
+    }
+
+    pub fn set_view(&mut self, view: View) {
+        self.view = view;
+        self.invalidate();
+    }
+
+    pub fn zoom(&self) -> u32 {
+        self.zoom
+    }
+
+    /// Changes the zoom factor, keeping the part of the field at the center of the screen
+    /// there
+    pub fn set_zoom(&mut self, life: &Life, zoom: u32) {
+        let (x, y) = self.center();
+        self.zoom = zoom.clamp(1, MAX_ZOOM);
+        self.fit_viewport(life);
+        let (new_x, new_y) = self.center();
+        let (dx, dy) = ((x - new_x).round() as i64, (y - new_y).round() as i64);
+        self.pan_cells(life, dx, dy);
+        self.invalidate();
+    }
+
+    pub fn origin(&self) -> (i64, i64) {
+        self.origin
+    }
+
+    /// Columns and rows of the field on screen, in cells
+    pub fn visible(&self) -> (u16, u16) {
+        self.visible
+    }
+
+    /// Moves the viewport by the given number of characters on screen
+    pub fn pan(&mut self, life: &Life, dx: i64, dy: i64) {
+        let (scale_x, scale_y) = self.scale();
+        self.pan_cells(life, dx.saturating_mul(scale_x), dy.saturating_mul(scale_y));
+    }
+
+    /// Moves the viewport by the given number of cells of the field
+    fn pan_cells(&mut self, life: &Life, dx: i64, dy: i64) {
+        let top = self.origin.1;
+        self.origin = (
+            self.origin.0.saturating_add(dx),
+            self.origin.1.saturating_add(dy),
+        );
+        self.fit_viewport(life);
+        self.reshift(life, top);
+    }
+
+    /// Draws everything from scratch if the viewport moved by an odd number of rows from `top`
+    /// on a hexagonal field, since every row on screen then changes whether it is shifted,
+    /// with the cells that stayed the same as well
+    fn reshift(&mut self, life: &Life, top: i64) {
+        if self.renderer.spaced(life.rules.neighborhood()) && (self.origin.1 - top) % 2 != 0 {
+            self.invalidate();
+        }
+    }
+
+    /// The cell of the field at a position on screen, the top left one of the cells the
+    /// character there stands for
+    pub fn on_field(&self, (x, y): (u16, u16)) -> (i64, i64) {
+        let (scale_x, scale_y) = self.scale();
+        (
+            self.origin.0 + x as i64 * scale_x,
+            self.origin.1 + y as i64 * scale_y,
+        )
+    }
+
+    /// The position on screen of a cell of the field, if it is in view
+    pub fn on_screen(&self, (x, y): (i64, i64)) -> Option<(u16, u16)> {
+        let (scale_x, scale_y) = self.scale();
+        let x = u16::try_from((x - self.origin.0).div_euclid(scale_x)).ok()?;
+        let y = u16::try_from((y - self.origin.1).div_euclid(scale_y)).ok()?;
+        (x < self.visible.0 && y < self.visible.1).then_some((x, y))
+    }
+
+    /// Counts the cells that are not dead in the block of cells at (x, y) when zoomed out,
+    /// together with the number of cells in it. Blocks cut off by the edges of a field that
+    /// doesn't wrap around only count the part of them within it.
+    fn block(&self, life: &Life, x: i64, y: i64) -> (u64, u64) {
+        let zoom = self.zoom as i64;
+        let (mut left, mut top, mut right, mut bottom) = (x, y, x + zoom, y + zoom);
+        if matches!(life.edges, Some((boundary, _)) if boundary != Boundary::Wrap) {
+            (left, top) = (left.max(0), top.max(0));
+            right = right.min(life.width as i64);
+            bottom = bottom.min(life.height as i64);
+        }
+        let area = ((right - left) * (bottom - top)).max(1) as u64;
+        let count = life
+            .engine
+            .population(left, top, (right - left) as u64, (bottom - top) as u64);
+        (count, area)
+    }
+
+    /// How the cells a character on screen stands for look, from the top left one at (x, y)
+    fn glyph(&self, life: &Life, x: i64, y: i64) -> char {
+        if let (View::Heatmap, Some(heat)) = (self.view, &life.heat) {
+            // Shaded by the hottest of the cells, so that a single active one shows
+            let heat = heat.hottest((x, y), self.scale(), |x, y| life.resolve(x, y));
+            return DENSITY[(heat as usize * 4).div_ceil(u8::MAX as usize)];
+        }
+        match (self.renderer, self.zoom) {
+            (Renderer::Glyph, 1) => life.glyph(x, y),
+            // Shaded by how many of the cells are not dead
+            (Renderer::Glyph, _) => {
+                let (count, area) = self.block(life, x, y);
+                DENSITY[((count * 4).div_ceil(area) as usize).min(4)]
+            }
+            (Renderer::Halfblock, _) => {
+                half_block(self.filled(life, x, y, 0, 0), self.filled(life, x, y, 0, 1))
+            }
+            (Renderer::Braille | Renderer::Sixel | Renderer::Kitty, _) => {
+                // Dots are numbered down the left column and then the right one, except for
+                // the bottom row that came with the extension to eight dots
+                const DOTS: [[u32; 2]; 4] =
+                    [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+                let mut dots = 0;
+                for (j, row) in (0..).zip(DOTS) {
+                    for (i, dot) in (0..).zip(row) {
+                        if self.filled(life, x, y, i, j) {
+                            dots |= dot;
+                        }
+                    }
+                }
+                char::from_u32(BLANK_BRAILLE as u32 + dots).unwrap()
+            }
+        }
+    }
+
+    /// Whether a part of a character that stands for several cells is filled: the `i`th cell,
+    /// or block of cells when zoomed out, across and the `j`th down from those at (x, y). It
+    /// is if any of its cells are not dead. The parts of the last characters past the edges of
+    /// a field are left empty, where the cells from across would show again if it wraps around.
+    fn filled(&self, life: &Life, x: i64, y: i64, i: i64, j: i64) -> bool {
+        let zoom = self.zoom as i64;
+        let (x, y) = (x + i * zoom, y + j * zoom);
+        let on_field =
+            x - self.origin.0 < life.width as i64 && y - self.origin.1 < life.height as i64;
+        if life.edges.is_some() && !on_field {
+            return false;
+        }
+        match zoom {
+            1 => life.engine.state(x, y) != DEAD,
+            _ => self.block(life, x, y).0 > 0,
+        }
+    }
+
+    /// Builds the terminal output bringing the screen up to date with the current generation,
+    /// with the lines of `status` on the rows kept below the field, the cursor shown on a cell
+    /// if given, and the `preview` cells of a stamp drawn over the ones of the field. Only as
+    /// much of the field is drawn as fits in the terminal.
+    pub fn draw(
+        &mut self,
+        life: &Life,
+        status: &str,
+        cell_cursor: Option<(u16, u16)>,
+        preview: &[(u16, u16)],
+    ) -> io::Result<&[u8]> {
+        self.fit_viewport(life);
+        let (width, height) = self.visible;
+        self.frame.clear();
+        if self.graphics.is_some() {
+            self.paint_picture(life, preview)?;
+        } else {
+            self.paint_glyphs(life, preview)?;
+        }
+
+        let columns = self.terminal.0 as usize;
+        for (row, line) in (height..height + self.status_lines).zip(status.split('\n')) {
+            // A longer line would wrap, and on the last row of the terminal scroll everything up
+            let line = match line.char_indices().nth(columns.saturating_sub(1)) {
+                Some((end, _)) => &line[..end],
+                None => line,
+            };
+            crossterm::queue!(
+                self.frame,
+                cursor::MoveTo(0, row),
+                terminal::Clear(ClearType::CurrentLine),
+                style::Print(line)
+            )?;
+        }
+        match cell_cursor.filter(|&(x, y)| x < width && y < height) {
+            Some((x, y)) => {
+                let column = self.column(life, x, y);
+                crossterm::queue!(self.frame, cursor::MoveTo(column, y), cursor::Show)?
+            }
+            // Leaves the cursor below the field, or on its last row when it takes up the whole
+            // terminal
+            None => {
+                let row = height.min(self.terminal.1.saturating_sub(1));
+                crossterm::queue!(self.frame, cursor::MoveTo(0, row), cursor::Hide)?
+            }
+        }
+        Ok(&self.frame)
+    }
+
+    /// Draws the glyphs of the cells on screen, only those that changed unless repainting
+    fn paint_glyphs(&mut self, life: &Life, preview: &[(u16, u16)]) -> io::Result<()> {
+        let (width, height) = self.visible;
+        let mut glyphs = mem::take(&mut self.glyphs);
+        glyphs.clear();
+        for y in 0..height {
+            for x in 0..width {
+                let (x, y) = self.on_field((x, y));
+                let glyph = self.glyph(life, x, y);
+                let selected = self
+                    .selection
+                    .is_some_and(|selection| selection.overlaps((x, y), self.zoom as i64));
+                glyphs.push((glyph, self.color(life, x, y, glyph), selected));
+            }
+        }
+        self.glyphs = glyphs;
+        for &(x, y) in preview {
+            if x < width && y < height {
+                let color = self.palette.map(|palette| palette.alive);
+                self.glyphs[y as usize * width as usize + x as usize] = (PREVIEW, color, false);
+            }
+        }
+
+        self.color = None;
+        self.reversed = false;
+        if let Some(palette) = self.palette {
+            crossterm::queue!(self.frame, style::SetBackgroundColor(palette.background))?;
+        }
+        let repaint = self.full_redraw
+            || self.shown.len() != self.glyphs.len()
+            || self.frames_since_repaint >= REPAINT_INTERVAL;
+        if repaint {
+            self.paint(life)?;
+            self.frames_since_repaint = 0;
+        } else {
+            self.paint_changes(life)?;
+            self.frames_since_repaint += 1;
+        }
+
+        if self.palette.is_some() {
+            crossterm::queue!(self.frame, style::ResetColor)?;
+        }
+        if self.reversed {
+            crossterm::queue!(self.frame, style::SetAttribute(Attribute::NoReverse))?;
+        }
+        mem::swap(&mut self.shown, &mut self.glyphs);
+        Ok(())
+    }
+
+    /// Draws the field as a bitmap of a color index per pixel, each cell of it a pixel, or a
+    /// block of cells when zoomed out. Frames are only sent when they look different, since
+    /// each one is the whole picture.
+    fn paint_picture(&mut self, life: &Life, preview: &[(u16, u16)]) -> io::Result<()> {
+        let (columns, rows) = self.visible;
+        let (across, down) = graphics::CELLS;
+        let (width, height) = (columns.saturating_mul(across), rows.saturating_mul(down));
+        let zoom = self.zoom as i64;
+        let mut pixels = mem::take(&mut self.picture.1);
+        pixels.clear();
+        for y in 0..height {
+            for x in 0..width {
+                let (left, top) = self.on_field((x / across, y / down));
+                let (i, j) = ((x % across) as i64, (y % down) as i64);
+                pixels.push(self.pixel(life, left + i * zoom, top + j * zoom));
+            }
+        }
+        // The cells of a stamp only have the characters they are at
+        for &(x, y) in preview.iter().filter(|&&(x, y)| x < columns && y < rows) {
+            for j in y * down..(y + 1) * down {
+                let row = j as usize * width as usize;
+                pixels[row + (x * across) as usize..row + ((x + 1) * across) as usize]
+                    .fill(graphics::ALIVE);
+            }
+        }
+
+        let repaint = self.full_redraw
+            || self.picture.0.len() != pixels.len()
+            || self.frames_since_repaint >= REPAINT_INTERVAL;
+        if repaint {
+            crossterm::queue!(self.frame, terminal::Clear(ClearType::All))?;
+            self.frames_since_repaint = 0;
+        } else {
+            self.frames_since_repaint += 1;
+        }
+        if repaint || pixels != self.picture.0 {
+            let (width, height) = (usize::from(width), usize::from(height));
+            let mut picture = Field::new(width, height);
+            for (i, &pixel) in pixels.iter().enumerate() {
+                picture.set_state(i % width, i / width, pixel).unwrap();
+            }
+            if let Some(graphics) = &mut self.graphics {
+                graphics.draw_frame(&picture, columns, rows, &mut self.frame)?;
+            }
+        }
+        self.picture = (pixels, mem::take(&mut self.picture.0));
+        Ok(())
+    }
+
+    /// The color index of the pixel of the cell at (x, y), or of the block of cells from it on
+    /// when zoomed out, the way the characters of the other renderers show it
+    fn pixel(&self, life: &Life, x: i64, y: i64) -> u8 {
+        let on_field =
+            x - self.origin.0 < life.width as i64 && y - self.origin.1 < life.height as i64;
+        if life.edges.is_some() && !on_field {
+            return graphics::BACKGROUND;
+        }
+        let zoom = self.zoom as i64;
+        if let (View::Heatmap, Some(heat)) = (self.view, &life.heat) {
+            let heat = heat.hottest((x, y), (zoom, zoom), |x, y| life.resolve(x, y));
+            let shade = (heat as u32 * graphics::HEAT_SHADES as u32).div_ceil(u8::MAX as u32);
+            return match shade {
+                0 => graphics::BACKGROUND,
+                _ => graphics::HEAT + shade as u8 - 1,
+            };
+        }
+        if zoom > 1 {
+            return match self.block(life, x, y).0 {
+                0 => graphics::BACKGROUND,
+                _ => graphics::ALIVE,
+            };
+        }
+        let state = life.engine.state(x, y);
+        if let Rule::Ant(turmite) = life.rules.first() {
+            return match (turmite.heading(state), turmite.color(state)) {
+                (Some(_), _) => graphics::SIDES,
+                (None, 0) => graphics::BACKGROUND,
+                (None, 1) => graphics::ALIVE,
+                _ => graphics::DYING,
+            };
+        }
+        match state {
+            DEAD => graphics::BACKGROUND,
+            _ if life.rules.first() == Rule::Wireworld => {
+                graphics::WIREWORLD + state - wireworld::HEAD
+            }
+            ALIVE => {
+                let (x, y) = life.resolve(x, y).unwrap_or((x, y));
+                let side = life
+                    .immigration
+                    .as_ref()
+                    .and_then(|immigration| immigration.get(x, y));
+                match side {
+                    Some(Side::First) => graphics::SIDES,
+                    Some(Side::Second) => graphics::SIDES + 1,
+                    None => graphics::ALIVE,
+                }
+            }
+            _ => graphics::DYING,
+        }
+    }
+
+    /// Builds the terminal output of a box in the middle of the screen, over the frame on it,
+    /// with `title` at the top and a line for each key and what it does. What doesn't fit in
+    /// the terminal is cut off, and the cells under the box are drawn again in the next frame.
+    pub fn overlay(&mut self, title: &str, keys: &[(String, &str)]) -> io::Result<&[u8]> {
+        self.invalidate();
+        self.frame.clear();
+        let (columns, rows) = (self.terminal.0 as usize, self.terminal.1 as usize);
+        let widest = keys.iter().map(|(key, _)| key.chars().count()).max();
+        let mut lines: Vec<String> = keys
+            .iter()
+            .map(|(key, help)| format!("{key:<widest$}  {help}", widest = widest.unwrap_or(0)))
+            .collect();
+        let inner = lines
+            .iter()
+            .map(|line| line.chars().count())
+            .chain([title.chars().count() + 2])
+            .max()
+            .unwrap_or(0)
+            .min(columns.saturating_sub(4));
+        let body = lines.len().min(rows.saturating_sub(2));
+        if inner == 0 || body == 0 {
+            return Ok(&self.frame);
+        }
+        if body < lines.len() {
+            let more = lines.len() - body + 1;
+            lines.truncate(body - 1);
+            lines.push(format!("and {more} more"));
+        }
+
+        let (left, top) = ((columns - inner - 4) / 2, (rows - body - 2) / 2);
+        let border: String = format!("─ {title} ")
+            .chars()
+            .chain(iter::repeat('─'))
+            .take(inner + 2)
+            .collect();
+        crossterm::queue!(
+            self.frame,
+            style::ResetColor,
+            cursor::Hide,
+            cursor::MoveTo(left as u16, top as u16),
+            style::Print(format!("┌{border}┐"))
+        )?;
+        for (row, line) in (top + 1..).zip(&lines) {
+            let line: String = line.chars().take(inner).collect();
+            crossterm::queue!(
+                self.frame,
+                cursor::MoveTo(left as u16, row as u16),
+                style::Print(format!("│ {line:<inner$} │"))
+            )?;
+        }
+        crossterm::queue!(
+            self.frame,
+            cursor::MoveTo(left as u16, (top + body + 1) as u16),
+            style::Print(format!("└{}┘", "─".repeat(inner + 2)))
+        )?;
+        Ok(&self.frame)
+    }
+
+    /// Draws everything from scratch in the next frame, such as when the terminal may have lost
+    /// what was on it
+    pub fn invalidate(&mut self) {
+        self.shown.clear();
+        self.picture.0.clear();
+    }
+
+    /// Fits the next frames into a terminal of a new size, which they are drawn from scratch
+    /// on
+    pub fn resize(&mut self, columns: u16, rows: u16) {
+        self.terminal = (columns, rows);
+        self.invalidate();
+    }
+
+    /// Whether a row on screen is shifted by half a cell, as the odd rows of a hexagonal field
+    /// are
+    fn shifted(&self, life: &Life, row: u16) -> bool {
+        // Blocks of cells have no such thing as odd rows
+        self.renderer.spaced(life.rules.neighborhood())
+            && self.zoom == 1
+            && (self.origin.1 + row as i64).rem_euclid(2) == 1
+    }
+
+    /// The terminal column where a cell on screen is shown
+    fn column(&self, life: &Life, x: u16, y: u16) -> u16 {
+        let stride = self
+            .renderer
+            .stride(life.rules.neighborhood(), &self.charset);
+        let shift = self.renderer.width(&self.charset) * u16::from(self.shifted(life, y));
+        x * stride + shift
+    }
+
+    /// The cell on screen at a position of the terminal in the last frame, if any
+    pub fn cell_at(&self, life: &Life, column: u16, row: u16) -> Option<(u16, u16)> {
+        let stride = self
+            .renderer
+            .stride(life.rules.neighborhood(), &self.charset);
+        let shift = self.renderer.width(&self.charset) * u16::from(self.shifted(life, row));
+        // The space after each cell counts as part of it
+        let x = column.checked_sub(shift)? / stride;
+        let (width, height) = self.visible;
+        (x < width && row < height).then_some((x, row))
+    }
+
+    fn paint(&mut self, life: &Life) -> io::Result<()> {
+        crossterm::queue!(
+            self.frame,
+            terminal::Clear(ClearType::All),
+            cursor::MoveTo(0, 0)
+        )?;
+
+        let spaced = self.renderer.spaced(life.rules.neighborhood());
+        let width = self.renderer.width(&self.charset) as usize;
+        let glyphs = mem::take(&mut self.glyphs);
+        for (y, row) in glyphs.chunks(self.visible.0.max(1) as usize).enumerate() {
+            // Going on past the last row would scroll the terminal when the field fills it
+            if y > 0 {
+                self.unfill()?;
+                self.frame.extend_from_slice(b"\r\n");
+            }
+            // Spacing out the cells lets odd rows be shifted by half a cell
+            if self.shifted(life, y as u16) {
+                self.frame.extend(iter::repeat_n(b' ', width));
+            }
+            for (x, &(glyph, color, selected)) in row.iter().enumerate() {
+                if spaced && x > 0 {
+                    self.unfill()?;
+                    self.frame.extend(iter::repeat_n(b' ', width));
+                }
+                self.put(glyph, color, selected)?;
+            }
+        }
+        self.glyphs = glyphs;
+        Ok(())
+    }
+
+    /// The color of a glyph of the cells from the one at (x, y) on: that of dead cells for
+    /// blank glyphs, a fading one for trails and that of live cells for all the others, or by
+    /// its age for a live cell drawn on its own when coloring them by age
+    fn color(&self, life: &Life, x: i64, y: i64, glyph: char) -> Option<style::Color> {
+        let palette = self.palette?;
+        if matches!(glyph, ' ' | BLANK_BRAILLE) {
+            return Some(palette.dead);
+        }
+        // Solid cells can only tell the shades apart by their colors
+        if let Some(i) = DENSITY.iter().position(|&shade| shade == glyph) {
+            if self.solid() && (self.zoom > 1 || self.view == View::Heatmap) {
+                return Some(palette.shades[i]);
+            }
+        }
+        if let Some(i) = trail::GLYPHS.iter().position(|&trail| trail == glyph) {
+            return Some(palette.trails[i]);
+        }
+        let on_its_own =
+            self.view == View::Cells && self.renderer == Renderer::Glyph && self.zoom == 1;
+        if on_its_own && life.rules.first() == Rule::Wireworld {
+            let state = life.engine.state(x, y);
+            return Some(palette.wireworld[(state - wireworld::HEAD) as usize]);
+        }
+        // The ants stand out from the cells they walk over
+        if let Rule::Ant(turmite) = life.rules.first() {
+            if on_its_own && turmite.heading(life.engine.state(x, y)).is_some() {
+                return Some(palette.side(Side::First));
+            }
+        }
+        if !on_its_own || life.engine.state(x, y) != ALIVE {
+            return Some(palette.alive);
+        }
+        let (x, y) = life.resolve(x, y).unwrap_or((x, y));
+        let side = life
+            .immigration
+            .as_ref()
+            .and_then(|immigration| immigration.get(x, y));
+        if let Some(side) = side {
+            return Some(palette.side(side));
+        }
+        let age = life
+            .ages
+            .as_ref()
+            .and_then(|ages| palette.age(ages.get(x, y)));
+        Some(age.unwrap_or(palette.alive))
+    }
+
+    /// Writes out a glyph of the screen, or the one configured for live or dead cells when
+    /// drawing a character per cell. Other glyphs are a column wide, and padded to the width
+    /// of the configured ones. Solid cells are blanks as wide, in the color as the
+    /// background. The color is only set when it differs from that of the glyph before, so
+    /// that runs of cells of the same color take a single one, and so is reverse video for
+    /// the cells `selected`.
+    fn put(&mut self, glyph: char, color: Option<style::Color>, selected: bool) -> io::Result<()> {
+        if selected != self.reversed {
+            let attribute = if selected {
+                Attribute::Reverse
+            } else {
+                Attribute::NoReverse
+            };
+            crossterm::queue!(self.frame, style::SetAttribute(attribute))?;
+            self.reversed = selected;
+        }
+        if let Some(changed) = color.filter(|&color| self.color != Some(color)) {
+            if self.solid() {
+                crossterm::queue!(self.frame, style::SetBackgroundColor(changed))?;
+            } else {
+                crossterm::queue!(self.frame, style::SetForegroundColor(changed))?;
+            }
+            self.color = color;
+        }
+        if self.solid() {
+            let width = self.renderer.width(&self.charset) as usize;
+            self.frame.extend(iter::repeat_n(b' ', width));
+            return Ok(());
+        }
+        let text = match (self.renderer, glyph) {
+            (Renderer::Glyph, '*') => &self.charset.alive,
+            (Renderer::Glyph, ' ') => &self.charset.dead,
+            _ => {
+                let mut utf8 = [0; 4];
+                let width = self.renderer.width(&self.charset) as usize;
+                self.frame
+                    .extend_from_slice(glyph.encode_utf8(&mut utf8).as_bytes());
+                self.frame.extend(iter::repeat_n(b' ', width - 1));
+                return Ok(());
+            }
+        };
+        self.frame.extend_from_slice(text.as_bytes());
+        Ok(())
+    }
+
+    fn solid(&self) -> bool {
+        self.style == CellStyle::Solid && self.renderer == Renderer::Glyph && self.palette.is_some()
+    }
+
+    /// Sets the background back to that of the screen after solid cells, for what is drawn
+    /// between them and past the end of the row not to be tinted
+    fn unfill(&mut self) -> io::Result<()> {
+        if let (true, Some(palette)) = (self.solid(), self.palette) {
+            if self.color != Some(palette.background) {
+                crossterm::queue!(self.frame, style::SetBackgroundColor(palette.background))?;
+                self.color = Some(palette.background);
+            }
+        }
+        Ok(())
+    }
+
+    /// Only draws the cells that changed, so the cost follows the activity of the pattern
+    /// rather than the size of the field
+    fn paint_changes(&mut self, life: &Life) -> io::Result<()> {
+        let (width, glyph_width) = (self.visible.0 as usize, self.renderer.width(&self.charset));
+        // Where printing a glyph leaves the cursor, to skip moving it for runs of changes
+        let mut cursor = None;
+        for i in 0..self.glyphs.len() {
+            let (glyph, color, selected) = self.glyphs[i];
+            if (glyph, color, selected) == self.shown[i] {
+                continue;
+            }
+            let (x, y) = ((i % width) as u16, (i / width) as u16);
+            let column = self.column(life, x, y);
+            if cursor != Some((column, y)) {
+                crossterm::queue!(self.frame, cursor::MoveTo(column, y))?;
+            }
+            self.put(glyph, color, selected)?;
+            cursor = Some((column + glyph_width, y));
+        }
+        Ok(())
+    }
+}
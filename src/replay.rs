@@ -0,0 +1,218 @@
+//! Playing back a run recorded with --record, at a pace of its own
+
+use std::{
+    fmt::Write as _,
+    io::{self, BufWriter, IsTerminal, Write},
+    path::PathBuf,
+    thread,
+    time::{Duration, Instant},
+};
+
+use clap::Args;
+
+use game_of_life::{
+    engine::EngineKind,
+    rule::table,
+    tape::{Frame, Player},
+    Life, Rule, Rules,
+};
+
+use crate::{
+    charset::{Aspect, Charset, Glyphs},
+    color::{Palette, Theme},
+    controls::{self, Command, Mode},
+    error::Error,
+    render::{Renderer, Screen, TerminalGuard},
+};
+
+/// Play back a tape recorded with --record, from its first generation or a chosen one on
+#[derive(Args)]
+#[command(
+    after_help = "Keys: space to pause and resume, n or . to step while paused, the left and right \
+                  arrows to go a generation back and forth while paused, hjkl to pan, - and + to \
+                  zoom out and in, q or Esc to quit."
+)]
+pub struct Replay {
+    /// Tape to play back
+    input: PathBuf,
+    /// Generations to show every second, or 0 to go as fast as possible
+    #[arg(long, default_value_t = 10.0, value_parser = crate::parse_fps)]
+    fps: f64,
+    /// Generation to start at, or the first one recorded after it
+    #[arg(long, value_name = "GEN")]
+    seek: Option<u64>,
+    /// Print the generations as plain text, the way a run without a terminal does, even to a
+    /// terminal
+    #[arg(long)]
+    plain: bool,
+    /// Characters to draw live and dead cells with
+    #[arg(long, value_enum, default_value_t = Charset::Ascii)]
+    charset: Charset,
+    /// How many columns of the terminal each cell is drawn over
+    #[arg(long, value_enum, default_value_t = Aspect::Narrow)]
+    aspect: Aspect,
+    /// Rule file the run was recorded under, if it was given one with --rule-file
+    #[arg(long, value_name = "PATH")]
+    rule_file: Option<PathBuf>,
+}
+
+impl Replay {
+    pub fn run(&self) -> Result<(), Error> {
+        if let Some(path) = &self.rule_file {
+            table::load(path)?;
+        }
+        let mut player = Player::open(&self.input)?;
+        if let Some(generation) = self.seek {
+            player.seek(generation)?;
+        }
+        let Some(first) = player.next_frame()? else {
+            return Err(Error::Pattern(match self.seek {
+                Some(generation) => format!(
+                    "{}: no generation recorded from {generation} on",
+                    self.input.display()
+                ),
+                None => format!("{}: no generations recorded", self.input.display()),
+            }));
+        };
+        let frame_time = if self.fps == 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(1.0 / self.fps)
+        };
+        if self.plain || !io::stdout().is_terminal() {
+            play_plain(&mut player, first, frame_time)
+        } else {
+            self.play(&mut player, first, frame_time)
+        }
+    }
+
+    /// Shows the generations on the screen until quitting, staying at the last one
+    fn play(&self, player: &mut Player, first: Frame, frame_time: Duration) -> Result<(), Error> {
+        let terminal_error = |err| Error::Io(format!("failed to draw on the terminal: {err}"));
+        let glyphs = Glyphs::new(self.charset, None, None, self.aspect).map_err(Error::Usage)?;
+        let theme = if player.rules.first() == Rule::Wireworld {
+            Theme::White
+        } else {
+            Theme::Never
+        };
+
+        let _guard = TerminalGuard::enter().map_err(terminal_error)?;
+        let mut screen = Screen::new(
+            false,
+            (0, 0),
+            1,
+            false,
+            Renderer::Glyph,
+            glyphs,
+            Palette::new(theme, None, None),
+        );
+        let mut stdout = BufWriter::new(io::stdout().lock());
+        let mut generation = first.generation;
+        let mut life = life(first, &player.rules)?;
+        let mut mode = Mode::Running;
+        let mut ended = false;
+        let mut next_frame = Instant::now() + frame_time;
+        let mut status = String::new();
+        loop {
+            status.clear();
+            let _ = write!(
+                status,
+                "gen {generation} pop {} {}x{}",
+                life.population(),
+                life.width,
+                life.height
+            );
+            match (ended, mode) {
+                (true, _) => status.push_str(" end of the tape: left to go back, q to quit"),
+                (false, Mode::Paused) => status.push_str(
+                    " paused: space to resume, n to step, left and right to go back and forth, \
+                     q to quit",
+                ),
+                _ => {}
+            }
+            let frame = screen
+                .draw(&life, &status, None, &[])
+                .map_err(terminal_error)?;
+            stdout
+                .write_all(frame)
+                .and_then(|()| stdout.flush())
+                .map_err(terminal_error)?;
+
+            // Waits for the next frame, or unless running, for a key that changes what is shown
+            let mut advance = false;
+            loop {
+                let deadline = (mode == Mode::Running).then_some(next_frame);
+                match controls::next_command(mode, deadline).map_err(terminal_error)? {
+                    None => {
+                        next_frame = (next_frame + frame_time).max(Instant::now());
+                        advance = true;
+                    }
+                    Some(Command::TogglePause) => {
+                        mode = match mode {
+                            Mode::Running => Mode::Paused,
+                            _ => Mode::Running,
+                        };
+                        next_frame = Instant::now() + frame_time;
+                    }
+                    Some(Command::Step) => advance = true,
+                    Some(Command::Travel(by)) => {
+                        player.seek(generation.saturating_add_signed(by as i64))?;
+                        advance = true;
+                    }
+                    Some(Command::Pan { dx, dy }) => screen.pan(&life, dx as i64, dy as i64),
+                    Some(Command::Zoom { out }) => {
+                        let zoom = screen.zoom();
+                        screen.set_zoom(&life, if out { zoom * 2 } else { zoom / 2 });
+                    }
+                    Some(Command::Resize { columns, rows }) => screen.resize(columns, rows),
+                    Some(Command::Quit) => return Ok(()),
+                    Some(_) => continue,
+                }
+                break;
+            }
+            if !advance {
+                continue;
+            }
+            match player.next_frame()? {
+                Some(frame) => {
+                    ended = false;
+                    generation = frame.generation;
+                    life = self::life(frame, &player.rules)?;
+                }
+                None => {
+                    ended = true;
+                    mode = Mode::Paused;
+                }
+            }
+        }
+    }
+}
+
+/// Prints the generations one after another as plain text, as a run does without a terminal
+fn play_plain(player: &mut Player, first: Frame, frame_time: Duration) -> Result<(), Error> {
+    let write_error = |err| Error::Io(format!("failed to write to stdout: {err}"));
+    let mut stdout = BufWriter::new(io::stdout().lock());
+    let mut next_frame = Instant::now();
+    let mut frame = Some(first);
+    while let Some(shown) = frame {
+        thread::sleep(next_frame.saturating_duration_since(Instant::now()));
+        next_frame += frame_time;
+        let generation = shown.generation;
+        let life = life(shown, &player.rules)?;
+        writeln!(
+            stdout,
+            "{}-- gen {generation} pop {}",
+            life.board(),
+            life.population()
+        )
+        .and_then(|()| stdout.flush())
+        .map_err(write_error)?;
+        frame = player.next_frame()?;
+    }
+    Ok(())
+}
+
+/// The field of a frame, to be drawn the way a run is
+fn life(frame: Frame, rules: &Rules) -> Result<Life, Error> {
+    Life::new(frame.field, rules.clone(), false, EngineKind::Naive, 1).map_err(Error::Pattern)
+}
@@ -0,0 +1,407 @@
+pub mod ant;
+pub mod elementary;
+mod ltl;
+pub mod preset;
+pub mod table;
+mod totalistic;
+pub mod wireworld;
+
+use std::{fmt, str::FromStr};
+
+use clap::ValueEnum;
+
+pub use ant::Turmite;
+pub use ltl::LargerThanLife;
+pub use table::RuleTable;
+pub use totalistic::OuterTotalistic;
+
+pub const DEAD: u8 = 0;
+pub const ALIVE: u8 = 1;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Rule {
+    OuterTotalistic(OuterTotalistic),
+    LargerThanLife(LargerThanLife),
+    Wireworld,
+    /// Loaded from a rule file
+    Table(&'static RuleTable),
+    /// Ants walking over the cells
+    Ant(Turmite),
+    /// A row of cells under a Wolfram rule, scrolling up a bounded field
+    Elementary(u8),
+}
+
+/// Kinds of cellular automata, apart from the rules they take
+#[derive(Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum Automaton {
+    /// Cells that are alive, dead or dying by the rule
+    #[default]
+    Life,
+    /// Electrons running along wires, from a circuit where `#` is a conductor, `@` an
+    /// electron head and `~` its tail
+    Wireworld,
+    /// Langton's Ant, or the turmite of the --turns, walking over a blank field
+    Ant,
+    /// The row of cells of the --wolfram rule at the bottom, with the rows it was before
+    /// scrolling up above it
+    Elementary,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Neighborhood {
+    /// The 8 surrounding cells
+    Moore,
+    /// The 4 orthogonally adjacent cells
+    #[value(name = "vonneumann")]
+    VonNeumann,
+    /// 6 cells of a hexagonal grid, emulated by shifting every other row half a cell
+    #[value(name = "hex")]
+    Hexagonal,
+}
+
+impl Neighborhood {
+    pub fn size(self) -> u8 {
+        match self {
+            Self::Moore => 8,
+            Self::VonNeumann => 4,
+            Self::Hexagonal => 6,
+        }
+    }
+
+    /// Positions of the neighbors relative to a cell on an even or an odd row
+    pub fn offsets(self, odd_row: bool) -> &'static [[i64; 2]] {
+        const MOORE: [[i64; 2]; 8] = [
+            [-1, -1],
+            [0, -1],
+            [1, -1],
+            [-1, 0],
+            [1, 0],
+            [-1, 1],
+            [0, 1],
+            [1, 1],
+        ];
+        const VON_NEUMANN: [[i64; 2]; 4] = [[0, -1], [-1, 0], [1, 0], [0, 1]];
+        // Odd rows are drawn half a cell to the right, so the diagonal neighbors of a cell
+        // lie to the left on even rows and to the right on odd ones
+        const HEX_EVEN_ROW: [[i64; 2]; 6] = [[-1, -1], [0, -1], [-1, 0], [1, 0], [-1, 1], [0, 1]];
+        const HEX_ODD_ROW: [[i64; 2]; 6] = [[0, -1], [1, -1], [-1, 0], [1, 0], [0, 1], [1, 1]];
+
+        match self {
+            Self::Moore => &MOORE,
+            Self::VonNeumann => &VON_NEUMANN,
+            Self::Hexagonal if odd_row => &HEX_ODD_ROW,
+            Self::Hexagonal => &HEX_EVEN_ROW,
+        }
+    }
+}
+
+impl fmt::Display for Neighborhood {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Moore => "Moore",
+            Self::VonNeumann => "von Neumann",
+            Self::Hexagonal => "hexagonal",
+        })
+    }
+}
+
+impl Rule {
+    pub fn states(&self) -> u8 {
+        match self {
+            Self::OuterTotalistic(rule) => rule.states(),
+            Self::LargerThanLife(rule) => rule.states(),
+            Self::Wireworld => 4,
+            Self::Table(table) => table.states(),
+            Self::Ant(turmite) => turmite.states(),
+            Self::Elementary(_) => 2,
+        }
+    }
+
+    pub fn neighborhood(&self) -> Neighborhood {
+        match self {
+            Self::OuterTotalistic(rule) => rule.neighborhood(),
+            Self::LargerThanLife(_) | Self::Wireworld | Self::Table(_) | Self::Elementary(_) => {
+                Neighborhood::Moore
+            }
+            Self::Ant(_) => Neighborhood::VonNeumann,
+        }
+    }
+
+    /// Whether this is a two-state outer totalistic rule in the Moore neighborhood, like
+    /// Conway's Life
+    pub fn is_life_like(&self) -> bool {
+        match self {
+            Self::OuterTotalistic(rule) => {
+                rule.states() == 2 && rule.neighborhood() == Neighborhood::Moore
+            }
+            Self::LargerThanLife(_)
+            | Self::Wireworld
+            | Self::Table(_)
+            | Self::Ant(_)
+            | Self::Elementary(_) => false,
+        }
+    }
+
+    /// Whether dead cells come alive without any live cells around, which would fill an
+    /// unbounded plane in a single generation
+    pub fn births_from_nothing(&self) -> bool {
+        match self {
+            Self::OuterTotalistic(rule) => rule.next(DEAD, 0) != DEAD,
+            Self::LargerThanLife(rule) => rule.next(DEAD, 0) != DEAD,
+            Self::Wireworld | Self::Ant(_) => false,
+            Self::Elementary(rule) => rule & 1 == 1,
+            Self::Table(table) => table.next(DEAD, [8, 0, 0, 0]) != DEAD,
+        }
+    }
+
+    pub fn with_neighborhood(self, neighborhood: Neighborhood) -> Result<Self, String> {
+        match self {
+            Self::OuterTotalistic(rule) => rule
+                .with_neighborhood(neighborhood)
+                .map(Self::OuterTotalistic),
+            Self::LargerThanLife(_) | Self::Wireworld | Self::Table(_) | Self::Elementary(_)
+                if neighborhood == Neighborhood::Moore =>
+            {
+                Ok(self)
+            }
+            Self::Ant(_) if neighborhood == Neighborhood::VonNeumann => Ok(self),
+            Self::LargerThanLife(_) => {
+                Err("Larger than Life rules only support the Moore neighborhood".to_string())
+            }
+            Self::Wireworld => Err("Wireworld only supports the Moore neighborhood".to_string()),
+            Self::Table(table) => Err(format!(
+                "{} only supports the Moore neighborhood",
+                table.name()
+            )),
+            Self::Ant(_) => Err("ants only step to the von Neumann neighborhood".to_string()),
+            Self::Elementary(_) => {
+                Err("elementary automata only read the cells beside and below".to_string())
+            }
+        }
+    }
+}
+
+/// Rules taking turns, one per generation, starting over after the last one
+#[derive(Clone, PartialEq, Eq)]
+pub struct Rules(Vec<Rule>);
+
+impl Rules {
+    /// The rule computing the generation after `generation`
+    pub fn get(&self, generation: u64) -> Rule {
+        self.0[(generation % self.0.len() as u64) as usize]
+    }
+
+    /// The first of the rules, which has the same states and neighborhood as the others
+    pub fn first(&self) -> Rule {
+        self.0[0]
+    }
+
+    /// How many rules take turns
+    pub fn turns(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Rule> + '_ {
+        self.0.iter().copied()
+    }
+
+    pub fn states(&self) -> u8 {
+        self.first().states()
+    }
+
+    pub fn neighborhood(&self) -> Neighborhood {
+        self.first().neighborhood()
+    }
+
+    pub fn is_life_like(&self) -> bool {
+        self.0.iter().all(Rule::is_life_like)
+    }
+
+    pub fn births_from_nothing(&self) -> bool {
+        self.0.iter().any(Rule::births_from_nothing)
+    }
+
+    /// Whether these are turmites, whose ants walk over the cells rather than the cells
+    /// evolving
+    pub fn turmites(&self) -> bool {
+        self.0.iter().any(|rule| matches!(rule, Rule::Ant(_)))
+    }
+
+    /// Whether these are elementary automata, whose rows scroll up rather than evolve
+    pub fn scroll(&self) -> bool {
+        self.0
+            .iter()
+            .any(|rule| matches!(rule, Rule::Elementary(_)))
+    }
+
+    pub fn with_neighborhood(self, neighborhood: Neighborhood) -> Result<Self, String> {
+        self.0
+            .into_iter()
+            .map(|rule| rule.with_neighborhood(neighborhood))
+            .collect::<Result<_, _>>()
+            .map(Self)
+    }
+}
+
+/// Rules to switch between while the simulation runs, separated by commas, such as
+/// `B3/S23,B36/S23,B2/S`
+#[derive(Clone)]
+pub struct RuleList(Vec<Rules>);
+
+impl RuleList {
+    /// The rules at `index`, starting over after the last ones both ways
+    pub fn get(&self, index: isize) -> &Rules {
+        &self.0[index.rem_euclid(self.0.len() as isize) as usize]
+    }
+}
+
+impl FromStr for RuleList {
+    type Err = String;
+
+    /// Larger than Life rules have commas of their own, so that the parts after one belong to
+    /// it until it is whole and for as long as they still fit into it
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // The last of the rules taking turns, which is the one a part could belong to
+        fn last(rules: &str) -> &str {
+            rules.rsplit('|').next().unwrap_or_default().trim_start()
+        }
+        let mut list: Vec<String> = Vec::new();
+        for part in s.split(',') {
+            match list.last_mut() {
+                Some(rules)
+                    if last(rules).starts_with(['R', 'r'])
+                        && (last(rules).parse::<LargerThanLife>().is_err()
+                            || format!("{},{part}", last(rules))
+                                .parse::<LargerThanLife>()
+                                .is_ok()) =>
+                {
+                    rules.push(',');
+                    rules.push_str(part);
+                }
+                _ => list.push(part.to_string()),
+            }
+        }
+        let list = list
+            .iter()
+            .map(|rules| rules.parse())
+            .collect::<Result<Vec<Rules>, _>>()?;
+        let first = &list[0];
+        if let Some(other) = list.iter().find(|rules| {
+            rules.states() != first.states() || rules.neighborhood() != first.neighborhood()
+        }) {
+            return Err(format!(
+                "{first} and {other} can't be switched between, as they differ in their states \
+                 or neighborhood"
+            ));
+        }
+        Ok(Self(list))
+    }
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Rule::default().into()
+    }
+}
+
+impl From<Rule> for Rules {
+    fn from(rule: Rule) -> Self {
+        Self(vec![rule])
+    }
+}
+
+impl FromStr for Rules {
+    type Err = String;
+
+    /// Rules separated by `|`, which have to agree on the states and the neighborhood for the
+    /// cells to mean the same under all of them
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rules = s
+            .split('|')
+            .map(str::parse)
+            .collect::<Result<Vec<Rule>, _>>()?;
+        let first = rules[0];
+        if let Some(other) = rules.iter().find(|rule| {
+            rule.states() != first.states() || rule.neighborhood() != first.neighborhood()
+        }) {
+            return Err(format!(
+                "{first} and {other} can't take turns, as they differ in their states or \
+                 neighborhood"
+            ));
+        }
+        let ant = |rule: &Rule| matches!(rule, Rule::Ant(_));
+        if let Some(other) = rules.iter().find(|rule| ant(rule) != ant(&first)) {
+            return Err(format!(
+                "{first} and {other} can't take turns, as only one of them is a turmite"
+            ));
+        }
+        Ok(Self(rules))
+    }
+}
+
+impl fmt::Display for Rules {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, rule) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str("|")?;
+            }
+            rule.fmt(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl Default for Rule {
+    fn default() -> Self {
+        Self::OuterTotalistic(OuterTotalistic::CONWAY)
+    }
+}
+
+impl FromStr for Rule {
+    type Err = String;
+
+    /// The name of a rule table that was loaded stands for it, turns like `RL` for turmites,
+    /// and `W` and a number for an elementary automaton, as Golly writes them
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let wolfram = s.trim().strip_prefix(['W', 'w']);
+        if let Some(table) = table::find(s) {
+            Ok(Self::Table(table))
+        } else if let Some(number) = wolfram.filter(|number| !number.is_empty()) {
+            number
+                .parse()
+                .map(Self::Elementary)
+                .map_err(|_| format!("expected a Wolfram rule from W0 to W255, not {s}"))
+        } else if s.trim().len() > 1 && s.trim().chars().all(|c| "LRlr".contains(c)) {
+            s.parse().map(Self::Ant)
+        } else if s.trim().eq_ignore_ascii_case("wireworld") {
+            Ok(Self::Wireworld)
+        } else if s.trim_start().starts_with(['R', 'r']) {
+            s.parse().map(Self::LargerThanLife)
+        } else {
+            s.parse().map(Self::OuterTotalistic)
+        }
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::OuterTotalistic(rule) => rule.fmt(f),
+            Self::LargerThanLife(rule) => rule.fmt(f),
+            Self::Wireworld => f.write_str("Wireworld"),
+            Self::Table(table) => f.write_str(table.name()),
+            Self::Ant(turmite) => turmite.fmt(f),
+            Self::Elementary(rule) => write!(f, "W{rule}"),
+        }
+    }
+}
+
+/// The transition of a cell that was not born and did not survive: live cells start dying,
+/// dying ones get closer to death, and dead ones stay dead
+fn decay(state: u8, states: u8) -> u8 {
+    match state {
+        DEAD => DEAD,
+        state if state + 1 < states => state + 1,
+        _ => DEAD,
+    }
+}
@@ -0,0 +1,119 @@
+//! Langton's Ant and the other turmites, ants walking over cells of a few colors: an ant turns
+//! by the color of its cell, paints the cell the next color and steps forward
+//!
+//! The state of a cell holds its color and, if an ant is on it, the way the ant faces. The
+//! ants themselves walk apart from the cells, so that any number of them can be on the same
+//! one.
+
+use std::{fmt, str::FromStr};
+
+use crate::Field;
+
+/// The most colors the cells can take, as many as the turns of a turmite
+pub const MAX_COLORS: u8 = 16;
+
+/// How a cell is moved to by an ant facing up, right, down and left
+pub const STEPS: [[i64; 2]; 4] = [[0, -1], [1, 0], [0, 1], [-1, 0]];
+
+/// The turns of the ants on the cells of each color, such as `RL` for Langton's Ant
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Turmite {
+    /// Bit n is set if the ants turn right on the cells of color n, and left otherwise
+    right: u16,
+    colors: u8,
+}
+
+impl Turmite {
+    /// Turning right on the dead cells and left on the live ones
+    pub const LANGTON: Self = Self {
+        right: 0b01,
+        colors: 2,
+    };
+
+    pub fn colors(self) -> u8 {
+        self.colors
+    }
+
+    /// The colors of the cells, each without an ant and with one facing any of 4 ways
+    pub fn states(self) -> u8 {
+        self.colors * 5
+    }
+
+    pub fn color(self, state: u8) -> u8 {
+        state % self.colors
+    }
+
+    /// Which way the ant on a cell faces, from up clockwise, if there is one
+    pub fn heading(self, state: u8) -> Option<u8> {
+        (state >= self.colors).then(|| state / self.colors - 1)
+    }
+
+    /// The state of a cell of `color` with an ant facing `heading`
+    pub fn with_ant(self, color: u8, heading: u8) -> u8 {
+        color + self.colors * (heading + 1)
+    }
+
+    /// Which way an ant facing `heading` faces once it turned on a cell of `color`
+    pub fn turn(self, color: u8, heading: u8) -> u8 {
+        let right = self.right >> color & 1 == 1;
+        (heading + if right { 1 } else { 3 }) % 4
+    }
+}
+
+impl fmt::Display for Turmite {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for color in 0..self.colors {
+            f.write_str(if self.right >> color & 1 == 1 {
+                "R"
+            } else {
+                "L"
+            })?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Turmite {
+    type Err = String;
+
+    /// A turn for each color, `L` or `R`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if !(2..=MAX_COLORS as usize).contains(&s.len()) {
+            return Err(format!(
+                "expected 2 to {MAX_COLORS} turns like `RL`, one for each color, not {} of them",
+                s.len()
+            ));
+        }
+        let mut right = 0;
+        for (color, turn) in s.chars().enumerate() {
+            match turn {
+                'R' | 'r' => right |= 1 << color,
+                'L' | 'l' => {}
+                turn => return Err(format!("expected the turns L or R, not `{turn}`")),
+            }
+        }
+        Ok(Self {
+            right,
+            colors: s.len() as u8,
+        })
+    }
+}
+
+/// Puts `count` ants facing up on the cells of the first color, spread evenly over the middle
+/// row
+pub fn place_ants(field: &mut Field, turmite: Turmite, count: usize) -> Result<(), String> {
+    if count > field.width {
+        return Err(format!(
+            "{count} ants don't fit on a row of the {}x{} field",
+            field.width, field.height
+        ));
+    }
+    for i in 0..count {
+        let x = field.width * (2 * i + 1) / (2 * count);
+        field
+            .set_state(x, field.height / 2, turmite.with_ant(0, 0))
+            .map_err(|err| err.to_string())?;
+    }
+    Ok(())
+}
@@ -0,0 +1,19 @@
+//! The elementary cellular automata of a single row of cells, shown as a field where the
+//! generations scroll up: the bottom row is the current one, and each row above it the one
+//! the row below was a generation before
+
+use super::ALIVE;
+
+/// The cell at (x, y) of a field `height` rows high, which in the bottom row becomes the
+/// state the Wolfram `rule` gives the cell and the two beside it, and above it takes over the
+/// state of the cell below
+pub fn next(rule: u8, state: impl Fn(i64, i64) -> u8, x: i64, y: i64, height: usize) -> u8 {
+    let bottom = height as i64 - 1;
+    if y < bottom {
+        return state(x, y + 1);
+    }
+    // Bit n of the rule is the next state of the cells that read n in binary, left to right
+    let neighbors =
+        (x - 1..=x + 1).fold(0, |bits, x| bits << 1 | u8::from(state(x, bottom) == ALIVE));
+    rule >> neighbors & 1
+}
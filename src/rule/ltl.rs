@@ -0,0 +1,139 @@
+//! Larger than Life rules over a square neighborhood of arbitrary radius, written like
+//! `R5,B34-45,S34-58` or in Golly's `R5,C0,M1,S34..58,B34..45,NM` notation. Without an `M`,
+//! the middle cell counts as a neighbor in the first notation, as it does in Bugs, and not in
+//! Golly's.
+
+use std::{fmt, str::FromStr};
+
+use super::{ALIVE, DEAD};
+
+const MAX_RADIUS: u8 = 50;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct LargerThanLife {
+    radius: u8,
+    /// Inclusive range of live neighbor counts giving birth to a dead cell
+    birth: (u16, u16),
+    /// Inclusive range of live neighbor counts keeping a live cell alive
+    survival: (u16, u16),
+    /// Whether a cell counts as its own neighbor
+    middle: bool,
+    /// As in Generations rules, the number of states including the dying ones
+    states: u8,
+}
+
+impl LargerThanLife {
+    pub fn radius(&self) -> u8 {
+        self.radius
+    }
+
+    pub fn states(&self) -> u8 {
+        self.states
+    }
+
+    /// Computes the next state of a cell from the number of live cells in the square of
+    /// the rule's radius around it, the cell itself included
+    pub fn next(&self, state: u8, live_in_square: u32) -> u8 {
+        let neighbors = live_in_square - u32::from(state == ALIVE && !self.middle);
+        let within =
+            |(min, max): (u16, u16)| (u32::from(min)..=u32::from(max)).contains(&neighbors);
+        match state {
+            DEAD if within(self.birth) => ALIVE,
+            ALIVE if within(self.survival) => ALIVE,
+            state => super::decay(state, self.states),
+        }
+    }
+
+    fn max_neighbors(&self) -> u16 {
+        let side = 2 * u16::from(self.radius) + 1;
+        side * side - u16::from(!self.middle)
+    }
+}
+
+impl FromStr for LargerThanLife {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (mut radius, mut birth, mut survival, mut middle) = (None, None, None, None);
+        let mut rule = Self {
+            radius: 1,
+            birth: (0, 0),
+            survival: (0, 0),
+            middle: false,
+            states: 2,
+        };
+        for part in s.trim().split(',').map(str::trim) {
+            let (key, value) = part.split_at(part.chars().next().map_or(0, char::len_utf8));
+            match key.to_ascii_uppercase().as_str() {
+                "R" => radius = Some(value),
+                "B" => birth = Some(parse_range(value)?),
+                "S" => survival = Some(parse_range(value)?),
+                "M" => {
+                    middle = match value {
+                        "0" => Some(false),
+                        "1" => Some(true),
+                        _ => {
+                            return Err(format!(
+                                "invalid middle cell setting `{part}`, expected M0 or M1"
+                            ))
+                        }
+                    }
+                }
+                // Golly writes C0 for the plain two states
+                "C" if value == "0" => rule.states = 2,
+                "C" => rule.states = super::totalistic::parse_states(value)?,
+                "N" if value.eq_ignore_ascii_case("M") => {}
+                "N" => return Err("only the Moore neighborhood (NM) is supported".to_string()),
+                _ => return Err(format!("unexpected `{part}` in Larger than Life rule")),
+            }
+        }
+
+        rule.middle = middle.unwrap_or(!s.contains(".."));
+        rule.radius = match radius.map(str::parse) {
+            Some(Ok(radius @ 1..=MAX_RADIUS)) => radius,
+            Some(_) => return Err(format!("the radius must be from 1 to {MAX_RADIUS}")),
+            None => return Err("the rule needs a radius like R5".to_string()),
+        };
+        let (Some(birth), Some(survival)) = (birth, survival) else {
+            return Err("the rule needs both a B and an S range".to_string());
+        };
+        let max = rule.max_neighbors();
+        if birth.1 > max || survival.1 > max {
+            return Err(format!(
+                "neighbor counts can't exceed {max} for radius {}",
+                rule.radius
+            ));
+        }
+        rule.birth = birth;
+        rule.survival = survival;
+        Ok(rule)
+    }
+}
+
+impl fmt::Display for LargerThanLife {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "R{},C{},M{},S{}..{},B{}..{},NM",
+            self.radius,
+            if self.states > 2 { self.states } else { 0 },
+            u8::from(self.middle),
+            self.survival.0,
+            self.survival.1,
+            self.birth.0,
+            self.birth.1
+        )
+    }
+}
+
+/// Parses `min-max`, `min..max` or a single count
+fn parse_range(range: &str) -> Result<(u16, u16), String> {
+    let (min, max) = range
+        .split_once("..")
+        .or_else(|| range.split_once('-'))
+        .unwrap_or((range, range));
+    match (min.parse(), max.parse()) {
+        (Ok(min), Ok(max)) if min <= max => Ok((min, max)),
+        _ => Err(format!("invalid neighbor count range `{range}`")),
+    }
+}
@@ -0,0 +1,102 @@
+//! Rules known by a name, so that they don't have to be written out
+
+use super::Rule;
+use crate::library::edit_distance;
+
+/// Names, rules and what becomes of the cells under them
+const PRESETS: &[(&str, &str, &str)] = &[
+    ("life", "B3/S23", "Conway's Game of Life"),
+    ("highlife", "B36/S23", "Life with a small replicator"),
+    (
+        "daynight",
+        "B3678/S34678",
+        "live and dead cells behave the same, as islands in a sea",
+    ),
+    (
+        "seeds",
+        "B2/S",
+        "every cell dies at once, and the rest explodes",
+    ),
+    (
+        "lifewithoutdeath",
+        "B3/S012345678",
+        "cells never die, growing ladders",
+    ),
+    (
+        "anneal",
+        "B4678/S35678",
+        "each cell goes the way of most of its neighbors, melting into blobs",
+    ),
+    ("maze", "B3/S12345", "grows into the corridors of a maze"),
+    (
+        "mazectric",
+        "B3/S1234",
+        "a maze of longer, straighter corridors",
+    ),
+    (
+        "diamoeba",
+        "B35678/S5678",
+        "diamonds that grow and shrink like amoebas",
+    ),
+    (
+        "2x2",
+        "B36/S125",
+        "blocks of 2x2 cells behaving like larger cells",
+    ),
+    ("34life", "B34/S34", "many small oscillators and spaceships"),
+    ("coral", "B3/S45678", "slowly growing coral"),
+    (
+        "move",
+        "B368/S245",
+        "chaos with common spaceships, also called Morley",
+    ),
+    (
+        "replicator",
+        "B1357/S1357",
+        "every pattern makes copies of itself",
+    ),
+    (
+        "briansbrain",
+        "B2/S/3",
+        "cells always die, after a state of dying",
+    ),
+];
+
+pub fn names() -> impl Iterator<Item = &'static str> {
+    PRESETS.iter().map(|&(name, ..)| name)
+}
+
+/// The presets with their rules and what they do
+pub fn all() -> impl Iterator<Item = (&'static str, Rule, &'static str)> {
+    PRESETS
+        .iter()
+        .map(|&(name, rule, description)| (name, parse(rule), description))
+}
+
+pub fn get(name: &str) -> Option<Rule> {
+    PRESETS
+        .iter()
+        .find(|&&(n, ..)| n == name)
+        .map(|&(_, rule, _)| parse(rule))
+}
+
+/// Validates a `--preset` argument, suggesting similarly spelled names for typos
+pub fn parse_name(name: &str) -> Result<&'static str, String> {
+    let name = name.to_ascii_lowercase();
+    if let Some(known) = names().find(|&known| known == name) {
+        return Ok(known);
+    }
+
+    let close: Vec<_> = names()
+        .filter(|known| edit_distance(known, &name) <= 2 || known.contains(name.as_str()))
+        .collect();
+    Err(if close.is_empty() {
+        "unknown preset, see --list-presets for the available ones".to_string()
+    } else {
+        format!("unknown preset, did you mean {}?", close.join(" or "))
+    })
+}
+
+fn parse(rule: &str) -> Rule {
+    rule.parse().expect("presets are valid")
+}
@@ -0,0 +1,287 @@
+//! Rules given as a table of transitions, the way the `.rule` files of Golly give them
+//!
+//! Only the `@TABLE` of a file is read, and of those only tables of up to four states in the
+//! Moore neighborhood whose transitions are permuted: the next state of a cell depends on its
+//! own and on how many of its neighbors are in each state, wherever they are. The other parts
+//! of a file, like `@TREE`, `@COLORS` and `@ICONS`, are skipped.
+
+use std::{collections::HashMap, fs, path::Path, sync::Mutex};
+
+use super::Neighborhood;
+use crate::LoadError;
+
+/// The most states a table can have
+pub const MAX_STATES: u8 = 4;
+
+/// The counts of the neighbors in each state but the dead one, as the digits of a number of
+/// base 9, since there are up to 8 of them
+const COUNTS: usize = 9 * 9 * 9;
+
+/// Where the table has no transition, so that the cell stays as it is
+const UNCHANGED: u8 = u8::MAX;
+
+/// The tables loaded, which live as long as the program does, so that the rules made of them
+/// can be copied around like any other and be found again by their name
+static TABLES: Mutex<Vec<&'static RuleTable>> = Mutex::new(Vec::new());
+
+#[derive(PartialEq, Eq)]
+pub struct RuleTable {
+    name: String,
+    states: u8,
+    /// The next state of a cell by its state and the counts of its neighbors
+    next: Vec<u8>,
+}
+
+impl RuleTable {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn states(&self) -> u8 {
+        self.states
+    }
+
+    /// The next state of a cell in `state`, given how many of its neighbors are in each state
+    pub fn next(&self, state: u8, counts: [u8; MAX_STATES as usize]) -> u8 {
+        match self.next[index(state, counts)] {
+            UNCHANGED => state,
+            next => next,
+        }
+    }
+}
+
+/// Reads the table of a `.rule` file, which the name of the rule stands for from then on
+pub fn load(path: &Path) -> Result<&'static RuleTable, LoadError> {
+    let text = fs::read_to_string(path)
+        .map_err(|err| LoadError::Read(format!("failed to read {}: {err}", path.display())))?;
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let table = parse(&text, &stem)
+        .map_err(|err| LoadError::Parse(format!("{}: {err}", path.display())))?;
+    let table: &'static RuleTable = Box::leak(Box::new(table));
+    TABLES.lock().unwrap().push(table);
+    Ok(table)
+}
+
+/// The table of the name loaded last, if any
+pub fn find(name: &str) -> Option<&'static RuleTable> {
+    let tables = TABLES.lock().unwrap();
+    let mut tables = tables.iter().rev();
+    tables
+        .find(|table| table.name.eq_ignore_ascii_case(name.trim()))
+        .copied()
+}
+
+/// A state of the cells at some point of a transition
+#[derive(Clone, Copy)]
+enum Slot {
+    State(u8),
+    /// One of the variables of the transition by its position, which has the same value
+    /// wherever it appears
+    Variable(usize),
+}
+
+/// Reads the text of a `.rule` file, naming the rule after its `@RULE` line or else `name`
+pub fn parse(text: &str, name: &str) -> Result<RuleTable, String> {
+    let mut name = name.to_string();
+    let mut section = "";
+    let mut table = false;
+    let mut states = None;
+    let (mut neighborhood, mut symmetries) = (false, false);
+    let mut variables: HashMap<&str, Vec<u8>> = HashMap::new();
+    let mut next = Vec::new();
+    for (number, line) in (1..).zip(text.lines()) {
+        let error = |message: String| format!("line {number}: {message}");
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(header) = line.strip_prefix('@') {
+            let (title, rest) = header
+                .split_once(char::is_whitespace)
+                .unwrap_or((header, ""));
+            section = title;
+            match title {
+                "RULE" if !rest.trim().is_empty() => name = rest.trim().to_string(),
+                "TABLE" => table = true,
+                _ => {}
+            }
+            continue;
+        }
+        if section != "TABLE" {
+            continue;
+        }
+
+        if let Some((key, value)) = line.split_once(':') {
+            let value = value.trim();
+            match key.trim() {
+                "n_states" => {
+                    let count = value
+                        .parse()
+                        .ok()
+                        .filter(|count| (2..=MAX_STATES).contains(count))
+                        .ok_or_else(|| {
+                            error(format!(
+                                "only tables of 2 to {MAX_STATES} states are supported, not \
+                                 {value}"
+                            ))
+                        })?;
+                    states = Some(count);
+                    next = vec![UNCHANGED; count as usize * COUNTS];
+                }
+                "neighborhood" if value.eq_ignore_ascii_case("Moore") => neighborhood = true,
+                "neighborhood" => {
+                    return Err(error(format!(
+                        "only the Moore neighborhood is supported, not {value}"
+                    )))
+                }
+                "symmetries" if value == "permute" => symmetries = true,
+                "symmetries" => {
+                    return Err(error(format!(
+                        "only permute symmetries are supported, not {value}"
+                    )))
+                }
+                key => return Err(error(format!("unknown setting `{key}`"))),
+            }
+            continue;
+        }
+        let (Some(states), true, true) = (states, neighborhood, symmetries) else {
+            return Err(error(
+                "n_states, neighborhood and symmetries have to come before the variables and \
+                 transitions"
+                    .to_string(),
+            ));
+        };
+
+        if let Some(variable) = line.strip_prefix("var ") {
+            let (variable, values) = variable
+                .split_once('=')
+                .and_then(|(variable, values)| {
+                    let values = values.trim().strip_prefix('{')?.strip_suffix('}')?;
+                    Some((variable.trim(), values))
+                })
+                .ok_or_else(|| error("expected a variable like `var a={0,1,2}`".to_string()))?;
+            let mut expanded = Vec::new();
+            for value in values.split(',').map(str::trim) {
+                match variables.get(value) {
+                    Some(values) => expanded.extend_from_slice(values),
+                    None => expanded.push(state(value, states).map_err(error)?),
+                }
+            }
+            variables.insert(variable, expanded);
+            continue;
+        }
+
+        // With fewer than 11 states, a transition can be written as a digit for each state
+        let parts: Vec<&str> = if line.contains(',') {
+            line.split(',').map(str::trim).collect()
+        } else {
+            line.char_indices()
+                .map(|(i, c)| &line[i..i + c.len_utf8()])
+                .collect()
+        };
+        let (output, inputs) = parts.split_last().unwrap_or((&"", &[]));
+        if inputs.len() != 9 {
+            return Err(error(format!(
+                "expected the states of the cell, its 8 neighbors and its next one, not {} \
+                 states",
+                parts.len()
+            )));
+        }
+        // The variables of the transition in the order they first appear
+        let mut bound: Vec<&str> = Vec::new();
+        let mut slots = Vec::with_capacity(inputs.len());
+        for &part in inputs {
+            slots.push(match variables.get(part) {
+                Some(_) => {
+                    Slot::Variable(bound.iter().position(|&name| name == part).unwrap_or_else(
+                        || {
+                            bound.push(part);
+                            bound.len() - 1
+                        },
+                    ))
+                }
+                None => Slot::State(state(part, states).map_err(error)?),
+            });
+        }
+        let output = match (
+            variables.get(output),
+            bound.iter().position(|name| name == output),
+        ) {
+            (None, _) => Slot::State(state(output, states).map_err(error)?),
+            (Some(_), Some(position)) => Slot::Variable(position),
+            (Some(_), None) => {
+                return Err(error(format!(
+                    "the next state is the variable `{output}`, which none of the others is"
+                )))
+            }
+        };
+        let values: Vec<&[u8]> = bound.iter().map(|name| &variables[name][..]).collect();
+        expand(&slots, output, &values, &mut next);
+    }
+
+    if !table {
+        return Err(
+            "there is no @TABLE, which is the only part of a rule file that is supported"
+                .to_string(),
+        );
+    }
+    let Some(states) = states else {
+        return Err("the @TABLE has no n_states".to_string());
+    };
+    Ok(RuleTable { name, states, next })
+}
+
+/// Adds a transition for each of the values its variables can take, unless an earlier one
+/// already covers the same cells
+fn expand(inputs: &[Slot], output: Slot, values: &[&[u8]], next: &mut [u8]) {
+    // Which value each variable takes, counting up through all of them
+    let mut choice = vec![0; values.len()];
+    loop {
+        let value = |slot| match slot {
+            Slot::State(state) => state,
+            Slot::Variable(i) => values[i][choice[i]],
+        };
+        let mut counts = [0; MAX_STATES as usize];
+        for &neighbor in &inputs[1..] {
+            counts[value(neighbor) as usize] += 1;
+        }
+        let entry = &mut next[index(value(inputs[0]), counts)];
+        if *entry == UNCHANGED {
+            *entry = value(output);
+        }
+
+        let mut i = 0;
+        loop {
+            let Some(digit) = choice.get_mut(i) else {
+                return;
+            };
+            *digit += 1;
+            if *digit < values[i].len() {
+                break;
+            }
+            *digit = 0;
+            i += 1;
+        }
+    }
+}
+
+/// How many of the Moore neighbors of a cell are in each state, given the states of the cells
+pub fn counts(state: impl Fn(i64, i64) -> u8, x: i64, y: i64) -> [u8; MAX_STATES as usize] {
+    let mut counts = [0; MAX_STATES as usize];
+    for &[i, j] in Neighborhood::Moore.offsets(false) {
+        counts[state(x + i, y + j) as usize] += 1;
+    }
+    counts
+}
+
+fn state(part: &str, states: u8) -> Result<u8, String> {
+    part.parse()
+        .ok()
+        .filter(|&state| state < states)
+        .ok_or_else(|| format!("`{part}` is neither a state below {states} nor a variable"))
+}
+
+fn index(state: u8, counts: [u8; MAX_STATES as usize]) -> usize {
+    let [_, first, second, third] = counts.map(usize::from);
+    state as usize * COUNTS + first + 9 * second + 81 * third
+}
@@ -0,0 +1,181 @@
+//! Outer totalistic rules in B/S notation, like `B3/S23` for Conway's Life, optionally with
+//! Generations-style dying states, like `B2/S/3` for Brian's Brain
+
+use std::{fmt, str::FromStr};
+
+use super::{Neighborhood, ALIVE, DEAD};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct OuterTotalistic {
+    /// Bit `n` is set if a dead cell with `n` live neighbors comes alive
+    birth: u16,
+    /// Bit `n` is set if a live cell with `n` live neighbors stays alive
+    survival: u16,
+    /// Number of cell states: dead, alive and `states - 2` dying ones that live cells not
+    /// surviving pass through before they are dead
+    states: u8,
+    neighborhood: Neighborhood,
+}
+
+impl OuterTotalistic {
+    pub const CONWAY: Self = Self {
+        birth: 1 << 3,
+        survival: 1 << 2 | 1 << 3,
+        states: 2,
+        neighborhood: Neighborhood::Moore,
+    };
+
+    pub fn states(&self) -> u8 {
+        self.states
+    }
+
+    pub fn neighborhood(&self) -> Neighborhood {
+        self.neighborhood
+    }
+
+    /// Switches to counting neighbors over `neighborhood`, as long as the rule's neighbor
+    /// counts are still possible with it
+    pub fn with_neighborhood(self, neighborhood: Neighborhood) -> Result<Self, String> {
+        let max = neighborhood.size();
+        if (self.birth | self.survival) >> (max + 1) != 0 {
+            return Err(format!(
+                "neighbor counts can't exceed {max} in the {neighborhood} neighborhood"
+            ));
+        }
+        Ok(Self {
+            neighborhood,
+            ..self
+        })
+    }
+
+    /// Only fully alive cells count as neighbors, dying ones don't
+    pub fn next(&self, state: u8, neighbors: u8) -> u8 {
+        match state {
+            DEAD if self.birth & 1 << neighbors != 0 => ALIVE,
+            ALIVE if self.survival & 1 << neighbors != 0 => ALIVE,
+            state => super::decay(state, self.states),
+        }
+    }
+}
+
+impl FromStr for OuterTotalistic {
+    type Err = String;
+
+    /// Accepts `B3/S23` (in any order, with or without slashes), the legacy survival-first
+    /// `23/3`, and for Generations rules a state count as `B2/S/C3`, `B2/S/3` or `/2/3`.
+    /// A trailing `V` or `H` selects the von Neumann or the hexagonal neighborhood.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().to_ascii_uppercase();
+        let (s, neighborhood) = match s.as_bytes().last() {
+            Some(b'V') => (&s[..s.len() - 1], Neighborhood::VonNeumann),
+            Some(b'H') => (&s[..s.len() - 1], Neighborhood::Hexagonal),
+            _ => (&s[..], Neighborhood::Moore),
+        };
+        Self::parse_moore(s)?.with_neighborhood(neighborhood)
+    }
+}
+
+impl OuterTotalistic {
+    fn parse_moore(s: &str) -> Result<Self, String> {
+        if !s.contains(['B', 'S']) {
+            let parts: Vec<&str> = s.split('/').collect();
+            let (survival, birth, states) = match parts[..] {
+                [survival, birth] => (survival, birth, None),
+                [survival, birth, states] => (survival, birth, Some(states)),
+                _ => return Err("expected a rule like B3/S23 or 23/3".to_string()),
+            };
+            return Ok(Self {
+                birth: parse_counts(birth)?,
+                survival: parse_counts(survival)?,
+                states: states.map_or(Ok(2), parse_states)?,
+                neighborhood: Neighborhood::Moore,
+            });
+        }
+
+        let (mut birth, mut survival, mut states) = (None, None, None::<String>);
+        let mut section = None;
+        for c in s.chars() {
+            match c {
+                'B' => {
+                    birth.get_or_insert(0);
+                    section = Some('B');
+                }
+                'S' => {
+                    survival.get_or_insert(0);
+                    section = Some('S');
+                }
+                'C' | 'G' => {
+                    states = Some(String::new());
+                    section = Some('C');
+                }
+                '/' => section = None,
+                '0'..='9' => match section {
+                    Some('B') => *birth.as_mut().unwrap() |= count_bit(c)?,
+                    Some('S') => *survival.as_mut().unwrap() |= count_bit(c)?,
+                    Some(_) => states.as_mut().unwrap().push(c),
+                    // A bare number after both counts is the Generations state count
+                    None if birth.is_some() && survival.is_some() && states.is_none() => {
+                        states = Some(c.to_string());
+                        section = Some('C');
+                    }
+                    None => return Err("neighbor counts must follow B or S".to_string()),
+                },
+                c => return Err(format!("unexpected character `{c}` in rule")),
+            }
+        }
+
+        match (birth, survival) {
+            (Some(birth), Some(survival)) => Ok(Self {
+                birth,
+                survival,
+                states: states.as_deref().map_or(Ok(2), parse_states)?,
+                neighborhood: Neighborhood::Moore,
+            }),
+            _ => Err("the rule needs both a B and an S part".to_string()),
+        }
+    }
+}
+
+impl fmt::Display for OuterTotalistic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let counts = |mask: u16| {
+            (0..=8)
+                .filter(|n| mask & 1 << n != 0)
+                .map(|n| char::from(b'0' + n))
+                .collect::<String>()
+        };
+        write!(f, "B{}/S{}", counts(self.birth), counts(self.survival))?;
+        if self.states > 2 {
+            write!(f, "/{}", self.states)?;
+        }
+        match self.neighborhood {
+            Neighborhood::Moore => Ok(()),
+            Neighborhood::VonNeumann => write!(f, "V"),
+            Neighborhood::Hexagonal => write!(f, "H"),
+        }
+    }
+}
+
+fn parse_counts(counts: &str) -> Result<u16, String> {
+    counts
+        .chars()
+        .map(count_bit)
+        .try_fold(0, |mask, bit| Ok(mask | bit?))
+}
+
+fn count_bit(c: char) -> Result<u16, String> {
+    match c.to_digit(10) {
+        Some(n @ 0..=8) => Ok(1 << n),
+        Some(n) => Err(format!("neighbor count {n} is out of the 0-8 range")),
+        None => Err(format!("unexpected character `{c}` in rule")),
+    }
+}
+
+pub(super) fn parse_states(states: &str) -> Result<u8, String> {
+    match states.parse() {
+        Ok(states @ 2..) => Ok(states),
+        _ => Err(format!(
+            "invalid state count `{states}`, expected a number from 2 to 255"
+        )),
+    }
+}
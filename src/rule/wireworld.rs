@@ -0,0 +1,97 @@
+//! Wireworld, where electrons run along wires of conductors
+
+use super::ALIVE;
+use crate::Field;
+
+/// Electron heads are the live cells, the only ones counted as neighbors
+pub const HEAD: u8 = ALIVE;
+pub const TAIL: u8 = 2;
+pub const CONDUCTOR: u8 = 3;
+
+/// Heads become tails, tails become conductors again, and conductors become heads next to
+/// one or two heads
+pub fn next(state: u8, heads: u8) -> u8 {
+    match state {
+        HEAD => TAIL,
+        TAIL => CONDUCTOR,
+        CONDUCTOR if matches!(heads, 1 | 2) => HEAD,
+        state => state,
+    }
+}
+
+/// A circuit drawn as text, independent of any field
+pub struct Board {
+    pub width: usize,
+    pub height: usize,
+    /// Positions and states of the cells that aren't empty, relative to the top-left corner
+    pub cells: Vec<(usize, usize, u8)>,
+}
+
+/// The cells of a `width` by `height` field as a circuit that `Board::parse` reads back, with
+/// a character for every one of them
+pub fn write(width: usize, height: usize, state: impl Fn(i64, i64) -> u8) -> String {
+    let mut circuit = String::with_capacity((width + 1) * height);
+    for y in 0..height as i64 {
+        circuit.extend((0..width as i64).map(|x| match state(x, y) {
+            HEAD => '@',
+            TAIL => '~',
+            CONDUCTOR => '#',
+            _ => '.',
+        }));
+        circuit.push('\n');
+    }
+    circuit
+}
+
+impl Board {
+    /// Every character is a cell: `#` is a conductor, `@` an electron head and `~` its tail,
+    /// while `.` and spaces are empty. Lines starting with `!` are comments.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let rows: Vec<(usize, &str)> = text
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line.trim_end_matches('\r')))
+            .filter(|(_, line)| !line.starts_with('!'))
+            .collect();
+        let mut board = Self {
+            width: 0,
+            height: rows.len(),
+            cells: Vec::new(),
+        };
+        for (y, (line_number, row)) in (0..).zip(rows) {
+            board.width = board.width.max(row.chars().count());
+            for (x, c) in (0..).zip(row.chars()) {
+                let state = match c {
+                    '#' => CONDUCTOR,
+                    '@' => HEAD,
+                    '~' => TAIL,
+                    '.' | ' ' => continue,
+                    c => return Err(format!("line {line_number}: unexpected character `{c}`")),
+                };
+                board.cells.push((x, y, state));
+            }
+        }
+        if board.cells.is_empty() {
+            return Err("the circuit is empty".to_string());
+        }
+        Ok(board)
+    }
+
+    pub fn place_centered(&self, field: &mut Field) -> Result<(), String> {
+        if self.width > field.width || self.height > field.height {
+            return Err(format!(
+                "circuit is {}x{}, which does not fit in the {}x{} field (see --width and --height)",
+                self.width, self.height, field.width, field.height
+            ));
+        }
+
+        let dx = (field.width - self.width) / 2;
+        let dy = (field.height - self.height) / 2;
+        for &(x, y, state) in &self.cells {
+            field
+                .set_state(x + dx, y + dy, state)
+                .map_err(|err| err.to_string())?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,129 @@
+//! Showing the generations to whoever connects over TCP, such as with `nc host 4000`, while
+//! the simulation goes on wherever it runs
+
+use std::{
+    fmt::Write as _,
+    io::Write,
+    net::{SocketAddr, TcpListener, TcpStream},
+    sync::{
+        mpsc::{self, Receiver, SyncSender, TrySendError},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use game_of_life::Life;
+
+/// Takes the home of the terminal of a client, so that each frame is drawn over the one before
+const HOME: &str = "\x1b[H";
+/// Clears the rest of a line, of what the frame before left there
+const CLEAR_LINE: &str = "\x1b[K";
+/// Clears the rest of the screen below the frame
+const CLEAR_BELOW: &str = "\x1b[J";
+
+/// Frames are sent at most this often, however fast the generations go
+const MIN_INTERVAL: Duration = Duration::from_millis(1000 / 60);
+
+/// What the thread accepting clients shares with the simulation
+#[derive(Default)]
+struct Clients {
+    /// Where the frames of each client wait to be written, one at most, so that a slow one
+    /// misses frames rather than holding up the simulation
+    queues: Vec<SyncSender<Arc<String>>>,
+    /// The last frame, which new clients start with
+    last: Option<Arc<String>>,
+}
+
+/// Sends text frames of the generations to the clients connected to a TCP port, at most one
+/// every `interval` or 60 every second
+pub struct Server {
+    clients: Arc<Mutex<Clients>>,
+    interval: Duration,
+    /// When the last frame was sent and its generation, to tell how fast the generations go
+    sent: Option<(Instant, u64)>,
+}
+
+impl Server {
+    /// Listens on `address` on a thread of its own, which gives each client it accepts one
+    /// too
+    pub fn new(address: SocketAddr, interval: Duration) -> Result<Self, String> {
+        let listener = TcpListener::bind(address)
+            .map_err(|err| format!("failed to listen on {address}: {err}"))?;
+        if let Ok(address) = listener.local_addr() {
+            eprintln!("listening on {address}");
+        }
+        let clients = Arc::new(Mutex::new(Clients::default()));
+        let accepting = Arc::clone(&clients);
+        thread::spawn(move || {
+            // A client that fails to connect is no reason to stop taking others
+            for stream in listener.incoming().filter_map(Result::ok) {
+                let (sender, receiver) = mpsc::sync_channel(1);
+                let mut clients = accepting.lock().unwrap();
+                if let Some(last) = &clients.last {
+                    let _ = sender.try_send(Arc::clone(last));
+                }
+                clients.queues.push(sender);
+                thread::spawn(move || serve(stream, receiver));
+            }
+        });
+        Ok(Self {
+            clients,
+            interval: interval.max(MIN_INTERVAL),
+            sent: None,
+        })
+    }
+
+    /// Sends the generation `life` is at to the clients, unless the last frame was sent too
+    /// recently
+    pub fn record(&mut self, life: &Life) {
+        let now = Instant::now();
+        let rate = match self.sent {
+            Some((sent, _)) if now - sent < self.interval => return,
+            Some((sent, generation)) => {
+                let seconds = (now - sent).as_secs_f64();
+                Some(life.generation().saturating_sub(generation) as f64 / seconds)
+            }
+            None => None,
+        };
+        self.sent = Some((now, life.generation()));
+
+        let mut frame = String::from(HOME);
+        let _ = write!(
+            frame,
+            "gen {} pop {} {}",
+            life.generation(),
+            life.population(),
+            life.rules
+        );
+        if let Some(rate) = rate {
+            let _ = write!(frame, " {rate:.0} gen/s");
+        }
+        let _ = writeln!(frame, "{CLEAR_LINE}");
+        for line in life.board().lines() {
+            let _ = writeln!(frame, "{line}{CLEAR_LINE}");
+        }
+        frame.push_str(CLEAR_BELOW);
+
+        let frame = Arc::new(frame);
+        let mut clients = self.clients.lock().unwrap();
+        // Clients that went away no longer take frames
+        clients
+            .queues
+            .retain(|queue| match queue.try_send(Arc::clone(&frame)) {
+                Ok(()) | Err(TrySendError::Full(_)) => true,
+                Err(TrySendError::Disconnected(_)) => false,
+            });
+        clients.last = Some(frame);
+    }
+}
+
+/// Writes the frames of a client to it as they come, until it goes away
+fn serve(mut stream: TcpStream, frames: Receiver<Arc<String>>) {
+    let _ = stream.set_nodelay(true);
+    for frame in frames {
+        if stream.write_all(frame.as_bytes()).is_err() {
+            return;
+        }
+    }
+}
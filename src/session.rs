@@ -0,0 +1,352 @@
+//! Interactive runs written down as they go, the edits along with the generations they were
+//! made at, to be gone through again the very same way
+//!
+//! A session file starts with `game-of-life session` and the version of the format, followed
+//! by the state the run started from, as a state file has it. Each line after that starting
+//! with `at` and a generation is an edit made at that generation, before the next one was
+//! computed: `toggle` and a cell, `stamp` and the cells, `poke` and the cells brought to life
+//! at random, `clear` and the left, top, right
+//! and bottom of a rectangle of cells killed, `set` and the cells undone or redone each with
+//! the state it was put in, `rules` and the rules switched to,
+//! `resize` and the size and anchor of the field, `travel` and how many generations the run
+//! went back or forth through its history, `speed` and the generations shown every second
+//! from then on, or 0 for as fast as possible, or `reseed` followed by the state of the new
+//! soup.
+//! The last line, `end` and a generation, is where the run stopped.
+
+use std::{
+    collections::VecDeque,
+    fmt::Write as _,
+    fs::{self, File},
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use clap::ValueEnum;
+
+use crate::{engine::EngineKind, rule::Rules, state, Anchor, Life, LoadError, Rect};
+
+const HEADER: &str = "game-of-life session";
+
+/// Bumped whenever the format changes in a way older versions can't read
+const VERSION: u32 = 1;
+
+/// Something done to a run other than computing its next generation
+pub enum Event {
+    Toggle(i64, i64),
+    Stamp(Vec<(i64, i64)>),
+    Poke(Vec<(i64, i64)>),
+    Clear(Rect),
+    /// Cells put in states one after another, as undoing or redoing an edit does
+    Set(Vec<(i64, i64, u8)>),
+    Rules(Rules),
+    Resize {
+        width: usize,
+        height: usize,
+        anchor: Anchor,
+    },
+    Travel(isize),
+    /// Showing this many generations every second from then on, which only the pace of a
+    /// run replayed follows
+    Speed(f64),
+    /// Starting over from a new soup, in the state it starts in
+    Reseed(String),
+}
+
+impl Event {
+    /// Does to `life` what was done to the run, computing the generations of a new soup with
+    /// `kind` of engine
+    pub fn apply(&self, life: &mut Life, kind: EngineKind, threads: usize) -> Result<(), String> {
+        match self {
+            Self::Toggle(x, y) => life.toggle(*x, *y),
+            Self::Stamp(cells) => life.stamp(cells.iter().copied()),
+            Self::Poke(cells) => {
+                life.poke(cells.iter().copied());
+            }
+            Self::Clear(rect) => life.erase(rect.cells()),
+            Self::Set(cells) => life.put(cells.iter().copied()),
+            Self::Rules(rules) => life.set_rules(rules.clone())?,
+            &Self::Resize {
+                width,
+                height,
+                anchor,
+            } => {
+                life.resize(width, height, anchor)?;
+            }
+            &Self::Travel(by) => {
+                if life.travel(by) != by {
+                    return Err(format!(
+                        "can't go {by} generations through the history from generation {}",
+                        life.generation()
+                    ));
+                }
+            }
+            Self::Speed(_) => {}
+            Self::Reseed(text) => *life = state::parse(text, kind, threads)?,
+        }
+        Ok(())
+    }
+
+    fn write(&self, out: &mut String) {
+        // Writing to a string can't fail
+        let _ = match self {
+            Self::Toggle(x, y) => write!(out, "toggle {x} {y}"),
+            Self::Stamp(cells) | Self::Poke(cells) => {
+                out.push_str(match self {
+                    Self::Stamp(_) => "stamp",
+                    _ => "poke",
+                });
+                for (x, y) in cells {
+                    let _ = write!(out, " {x} {y}");
+                }
+                Ok(())
+            }
+            Self::Clear(rect) => write!(
+                out,
+                "clear {} {} {} {}",
+                rect.left, rect.top, rect.right, rect.bottom
+            ),
+            Self::Set(cells) => {
+                out.push_str("set");
+                for (x, y, state) in cells {
+                    let _ = write!(out, " {x} {y} {state}");
+                }
+                Ok(())
+            }
+            Self::Rules(rules) => write!(out, "rules {rules}"),
+            Self::Resize {
+                width,
+                height,
+                anchor,
+            } => write!(
+                out,
+                "resize {width} {height} {}",
+                anchor.to_possible_value().unwrap().get_name()
+            ),
+            Self::Travel(by) => write!(out, "travel {by}"),
+            Self::Speed(fps) => write!(out, "speed {fps}"),
+            Self::Reseed(text) => write!(out, "reseed\n{}", text.trim_end()),
+        };
+        out.push('\n');
+    }
+}
+
+/// Writes the edits of a run to a session file as they are made
+pub struct SessionRecorder {
+    out: BufWriter<File>,
+    path: PathBuf,
+}
+
+impl SessionRecorder {
+    /// Starts a session at `path` of a run starting from where `life` is, replacing any file
+    /// there
+    pub fn create(path: &Path, life: &Life) -> Result<Self, String> {
+        let file = File::create(path)
+            .map_err(|err| format!("failed to create {}: {err}", path.display()))?;
+        let mut recorder = Self {
+            out: BufWriter::new(file),
+            path: path.to_path_buf(),
+        };
+        recorder.write(&format!("{HEADER} {VERSION}\n{}", state::write(life)))?;
+        Ok(recorder)
+    }
+
+    /// Appends an edit made at `generation`, written out right away so that it isn't lost if
+    /// the run is cut short
+    pub fn record(&mut self, generation: u64, event: &Event) -> Result<(), String> {
+        let mut line = format!("at {generation} ");
+        event.write(&mut line);
+        self.write(&line)
+    }
+
+    /// Ends the session at the generation the run stopped at
+    pub fn finish(mut self, generation: u64) -> Result<(), String> {
+        self.write(&format!("end {generation}\n"))
+    }
+
+    fn write(&mut self, text: &str) -> Result<(), String> {
+        self.out
+            .write_all(text.as_bytes())
+            .and_then(|()| self.out.flush())
+            .map_err(|err| format!("failed to write to {}: {err}", self.path.display()))
+    }
+}
+
+/// A session read back, handing out its edits as the run gets to their generations
+pub struct Session {
+    start: String,
+    events: VecDeque<(u64, Event)>,
+    /// The generation the run stopped at, if it got to the end of the session
+    end: Option<u64>,
+}
+
+impl Session {
+    pub fn load(path: &Path) -> Result<Self, LoadError> {
+        let text = fs::read_to_string(path)
+            .map_err(|err| LoadError::Read(format!("failed to read {}: {err}", path.display())))?;
+        parse(&text).map_err(|err| LoadError::Parse(format!("{}: {err}", path.display())))
+    }
+
+    /// The run as it started, computing its generations with `kind` of engine
+    pub fn start(&self, kind: EngineKind, threads: usize) -> Result<Life, String> {
+        state::parse(&self.start, kind, threads)
+    }
+
+    /// The next edit if it was made at `generation`, which can't be past it
+    pub fn next(&mut self, generation: u64) -> Result<Option<Event>, String> {
+        match self.events.front() {
+            Some(&(at, _)) if at == generation => {
+                Ok(self.events.pop_front().map(|(_, event)| event))
+            }
+            Some(&(at, _)) if at < generation => Err(format!(
+                "the edit made at generation {at} was due before generation {generation}"
+            )),
+            _ => Ok(None),
+        }
+    }
+
+    /// Whether all the edits were made and the run is at the generation it stopped at, or
+    /// with a session cut short, at the last edit
+    pub fn ended(&self, generation: u64) -> bool {
+        self.events.is_empty() && self.end.is_none_or(|end| generation >= end)
+    }
+}
+
+fn parse(text: &str) -> Result<Session, String> {
+    let mut lines = (1..).zip(text.lines()).map(|(i, line)| (i, line.trim()));
+    let version = lines
+        .next()
+        .and_then(|(_, line)| line.strip_prefix(HEADER))
+        .ok_or("not a session file")?
+        .trim();
+    if version != VERSION.to_string() {
+        return Err(format!(
+            "session files of version {version} can't be read, only of version {VERSION}"
+        ));
+    }
+
+    // The lines of the state being read, that of the start or of a soup reseeded to
+    let mut state = String::new();
+    let mut start = None;
+    let mut events = VecDeque::new();
+    let mut end = None;
+    for (number, line) in lines {
+        let (key, rest) = line.split_once(' ').unwrap_or((line, ""));
+        if !matches!(key, "at" | "end") {
+            if end.is_some() {
+                return Err(format!("line {number}: {line:?} comes after the end"));
+            }
+            state.push_str(line);
+            state.push('\n');
+            continue;
+        }
+        let finished = std::mem::take(&mut state);
+        match (&start, events.back_mut()) {
+            (None, _) => start = Some(finished),
+            (Some(_), Some((_, Event::Reseed(text)))) if text.is_empty() => *text = finished,
+            _ if finished.trim().is_empty() => {}
+            _ => {
+                return Err(format!(
+                    "line {number}: a state that no reseed comes before"
+                ))
+            }
+        }
+
+        let wrong = || format!("line {number}: {line:?} is not a valid {key} line");
+        let mut words = rest.split_whitespace();
+        let generation = words
+            .next()
+            .and_then(|word| word.parse::<u64>().ok())
+            .ok_or_else(wrong)?;
+        if end.is_some() {
+            return Err(format!("line {number}: {line:?} comes after the end"));
+        }
+        if key == "end" {
+            end = Some(generation);
+            continue;
+        }
+        let words: Vec<_> = words.collect();
+        let numbers = || {
+            words[1..]
+                .iter()
+                .map(|word| word.parse::<i64>().map_err(|_| wrong()))
+                .collect::<Result<Vec<_>, _>>()
+        };
+        let event = match (words.first().copied(), words.len()) {
+            (Some("toggle"), 3) => Event::Toggle(
+                words[1].parse().map_err(|_| wrong())?,
+                words[2].parse().map_err(|_| wrong())?,
+            ),
+            (Some(kind @ ("stamp" | "poke")), len) if len % 2 == 1 => {
+                let cells = numbers()?
+                    .chunks(2)
+                    .map(|cell| (cell[0], cell[1]))
+                    .collect();
+                match kind {
+                    "stamp" => Event::Stamp(cells),
+                    _ => Event::Poke(cells),
+                }
+            }
+            (Some("clear"), 5) => {
+                let numbers = numbers()?;
+                Event::Clear(Rect::new(
+                    (numbers[0], numbers[1]),
+                    (numbers[2], numbers[3]),
+                ))
+            }
+            (Some("set"), len) if len % 3 == 1 => Event::Set(
+                numbers()?
+                    .chunks(3)
+                    .map(|cell| {
+                        let state = u8::try_from(cell[2]).map_err(|_| wrong())?;
+                        Ok((cell[0], cell[1], state))
+                    })
+                    .collect::<Result<_, String>>()?,
+            ),
+            (Some("rules"), _) => {
+                let rules = rest
+                    .split_once("rules")
+                    .map_or("", |(_, rules)| rules.trim());
+                Event::Rules(
+                    rules
+                        .parse()
+                        .map_err(|err| format!("line {number}: {err}"))?,
+                )
+            }
+            (Some("resize"), 4) => Event::Resize {
+                width: words[1].parse().map_err(|_| wrong())?,
+                height: words[2].parse().map_err(|_| wrong())?,
+                anchor: Anchor::from_str(words[3], true).map_err(|_| wrong())?,
+            },
+            (Some("travel"), 2) => Event::Travel(words[1].parse().map_err(|_| wrong())?),
+            (Some("speed"), 2) => Event::Speed(
+                words[1]
+                    .parse()
+                    .ok()
+                    .filter(|fps: &f64| fps.is_finite() && *fps >= 0.0)
+                    .ok_or_else(wrong)?,
+            ),
+            (Some("reseed"), 1) => Event::Reseed(String::new()),
+            _ => return Err(format!("line {number}: {line:?} is not understood")),
+        };
+        events.push_back((generation, event));
+    }
+
+    // A session cut short has no end line, and lost the state of a soup it was reseeding to
+    match (&start, events.back_mut()) {
+        (None, _) => start = Some(state),
+        (Some(_), Some((_, Event::Reseed(text)))) if text.is_empty() => {
+            if state.trim().is_empty() {
+                events.pop_back();
+            } else {
+                *text = state;
+            }
+        }
+        _ => {}
+    }
+    Ok(Session {
+        start: start.unwrap_or_default(),
+        events,
+        end,
+    })
+}
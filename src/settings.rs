@@ -0,0 +1,229 @@
+//! Settings kept in a config file, for the options that would be too long to type every time
+//!
+//! The file is a flat TOML table of the long options without their dashes, such as
+//! `charset = "block"`, `fps = 30` or `place = ["glider@2,2", "lwss@20,5"]`, switches being
+//! `true` or `false`. The settings go on the command line after whatever was typed, except for
+//! the options that were typed or that conflict with one that was, so that typed ones always
+//! win over the file, and the file over the defaults.
+
+use std::{
+    env,
+    ffi::OsString,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use clap::{parser::ValueSource, Arg, ArgAction, ArgMatches, Command};
+
+/// The value of a setting, numbers being kept as they are written for the options to parse
+enum Value {
+    Switch(bool),
+    Text(String),
+    List(Vec<String>),
+}
+
+struct Setting {
+    line: usize,
+    key: String,
+    value: Value,
+}
+
+/// The config file looked for without --config, under `$XDG_CONFIG_HOME` or `~/.config`
+pub fn default_path() -> Option<PathBuf> {
+    let base = env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".config")))?;
+    Some(base.join("game-of-life").join("config.toml"))
+}
+
+/// Reads the settings of the file at `path` and turns them into the options to add to the
+/// command line that `matches` were parsed from by `command`. A file that isn't there is
+/// only an error if it was asked for.
+pub fn args(
+    path: &Path,
+    required: bool,
+    command: &Command,
+    matches: &ArgMatches,
+) -> Result<Vec<OsString>, String> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(err) if err.kind() == io::ErrorKind::NotFound && !required => return Ok(Vec::new()),
+        Err(err) => return Err(format!("failed to read {}: {err}", path.display())),
+    };
+    let at = |line| format!("{}:{line}", path.display());
+    let typed = |id: &str| matches.value_source(id) == Some(ValueSource::CommandLine);
+
+    let mut args = Vec::new();
+    for Setting { line, key, value } in
+        parse(&text).map_err(|(line, err)| format!("{}: {err}", at(line)))?
+    {
+        let long = key.replace('_', "-");
+        let Some(arg) = command.get_arguments().find(|arg| {
+            arg.get_long() == Some(&long) && !matches!(long.as_str(), "config" | "no-config")
+        }) else {
+            eprintln!("warning: {}: unknown setting {key}", at(line));
+            continue;
+        };
+        let id = arg.get_id().as_str();
+        if typed(id)
+            || command
+                .get_arg_conflicts_with(arg)
+                .iter()
+                .any(|other| typed(other.get_id().as_str()))
+        {
+            continue;
+        }
+        let switch = matches!(arg.get_action(), ArgAction::SetTrue);
+        let values = match value {
+            Value::Switch(on) if switch => {
+                if on {
+                    args.push(OsString::from(format!("--{long}")));
+                }
+                continue;
+            }
+            _ if switch => return Err(format!("{}: {key} is either true or false", at(line))),
+            Value::Switch(_) => {
+                return Err(format!(
+                    "{}: {key} takes a value, not true or false",
+                    at(line)
+                ))
+            }
+            Value::List(values) if matches!(arg.get_action(), ArgAction::Append) => values,
+            Value::List(_) => {
+                return Err(format!(
+                    "{}: {key} takes a single value, not a list",
+                    at(line)
+                ))
+            }
+            Value::Text(value) => vec![value],
+        };
+        // Checked one at a time against the option alone, so that a wrong value is told apart
+        // by its line
+        let alone = Command::new("config").no_binary_name(true).arg(
+            Arg::new(arg.get_id().clone())
+                .value_parser(arg.get_value_parser().clone())
+                .allow_hyphen_values(true),
+        );
+        for value in values {
+            alone
+                .clone()
+                .try_get_matches_from([&value])
+                .map_err(|err| {
+                    let err = err.to_string();
+                    let err = err.lines().next().unwrap_or_default();
+                    let err = err.trim_start_matches("error: ");
+                    format!("{}: {}", at(line), err.replace(&format!("'[{id}]'"), &key))
+                })?;
+            args.push(OsString::from(format!("--{long}={value}")));
+        }
+    }
+    Ok(args)
+}
+
+/// Reads the `key = value` lines of a flat TOML table, failing with the line of the first
+/// one that isn't
+fn parse(text: &str) -> Result<Vec<Setting>, (usize, String)> {
+    let mut settings: Vec<Setting> = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let line_number = i + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if line.starts_with('[') {
+            return Err((
+                line_number,
+                "tables are not supported, only settings".to_string(),
+            ));
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            return Err((
+                line_number,
+                format!("expected a setting such as `fps = 30`, found {line:?}"),
+            ));
+        };
+        let key = key.trim().trim_matches('"').to_string();
+        if settings.iter().any(|setting| setting.key == key) {
+            return Err((line_number, format!("{key} is set twice")));
+        }
+        let (value, rest) =
+            value_of(value.trim()).map_err(|err| (line_number, format!("{key}: {err}")))?;
+        let rest = rest.trim_start();
+        if !rest.is_empty() && !rest.starts_with('#') {
+            return Err((
+                line_number,
+                format!("{key}: unexpected {rest:?} after the value"),
+            ));
+        }
+        settings.push(Setting {
+            line: line_number,
+            key,
+            value,
+        });
+    }
+    Ok(settings)
+}
+
+/// The value at the start of `text`, and what comes after it
+fn value_of(text: &str) -> Result<(Value, &str), String> {
+    if let Some(rest) = text.strip_prefix('[') {
+        let mut values = Vec::new();
+        let mut rest = rest.trim_start();
+        loop {
+            if let Some(rest) = rest.strip_prefix(']') {
+                return Ok((Value::List(values), rest));
+            }
+            let (value, after) = scalar(rest)?;
+            match value {
+                Value::Text(value) => values.push(value),
+                _ => return Err("lists can only hold strings and numbers".to_string()),
+            }
+            rest = after.trim_start();
+            rest = match rest.strip_prefix(',') {
+                Some(after) => after.trim_start(),
+                None if rest.starts_with(']') => rest,
+                None => return Err("expected a comma or the end of the list".to_string()),
+            };
+        }
+    }
+    scalar(text)
+}
+
+fn scalar(text: &str) -> Result<(Value, &str), String> {
+    if let Some(rest) = text.strip_prefix('"') {
+        let mut value = String::new();
+        let mut chars = rest.char_indices();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '"' => return Ok((Value::Text(value), &rest[i + 1..])),
+                '\\' => value.push(match chars.next().map(|(_, c)| c) {
+                    Some('n') => '\n',
+                    Some('t') => '\t',
+                    Some(c @ ('"' | '\\')) => c,
+                    _ => return Err("unknown escape in the string".to_string()),
+                }),
+                c => value.push(c),
+            }
+        }
+        return Err("the string doesn't end".to_string());
+    }
+    if let Some(rest) = text.strip_prefix('\'') {
+        let end = rest.find('\'').ok_or("the string doesn't end")?;
+        return Ok((Value::Text(rest[..end].to_string()), &rest[end + 1..]));
+    }
+    let end = text
+        .find(|c: char| c.is_whitespace() || matches!(c, ',' | ']' | '#'))
+        .unwrap_or(text.len());
+    let (word, rest) = text.split_at(end);
+    let value = match word {
+        "true" => Value::Switch(true),
+        "false" => Value::Switch(false),
+        _ if word.parse::<f64>().is_ok() || word.replace('_', "").parse::<f64>().is_ok() => {
+            Value::Text(word.replace('_', ""))
+        }
+        "" => return Err("the value is missing".to_string()),
+        _ => return Err(format!("{word:?} is not a value, strings go in quotes")),
+    };
+    Ok((value, rest))
+}
@@ -0,0 +1,403 @@
+//! Setting up the simulation the command line asks for
+
+use std::{
+    fs,
+    io::{self, Read},
+};
+
+use clap::ValueEnum;
+use crossterm::terminal;
+use game_of_life::{
+    age::Ages,
+    cycle::Cycles,
+    engine::EngineKind,
+    formats,
+    history::History,
+    image, library,
+    noise::Noise,
+    pattern::Pattern,
+    rule::{ant, wireworld::Board, Automaton, Neighborhood},
+    session::Session,
+    state,
+    symmetry::Symmetry,
+    trail::Trails,
+    undo::Edits,
+    variant::{Immigration, Variant},
+    Boundary, Field, Life, Rule, Rules, Topology,
+};
+
+use crate::{charset::Glyphs, color::Theme, compare, error::Error, render, Config, Display};
+
+/// The size of the terminal in columns and rows to fit the field to, or half of it for each of
+/// the simulations compared, if the field is to fill it
+pub fn terminal_size(config: &Config, glyphs: &Glyphs) -> Option<(u16, u16)> {
+    // Queried before loading the pattern, which an image needs the size for
+    let terminal_size = config.fit.then(|| {
+        terminal::size()
+            .map_err(|err| {
+                eprintln!(
+                    "warning: can't tell the size of the terminal ({err}), using the default size"
+                )
+            })
+            .ok()
+    });
+    let terminal_size = terminal_size.flatten();
+    // Each of the simulations compared gets half of the terminal
+    match terminal_size {
+        Some((columns, rows)) if config.compare => {
+            Some(if compare::beside((columns / glyphs.width.max(1), rows)) {
+                (columns.saturating_sub(glyphs.width) / 2, rows)
+            } else {
+                (columns, rows / 2)
+            })
+        }
+        terminal_size => terminal_size,
+    }
+}
+
+/// The simulation of the session to replay, of the state to load or otherwise a new one
+pub fn load(
+    config: &Config,
+    replay: Option<&Session>,
+    glyphs: &Glyphs,
+    terminal_size: Option<(u16, u16)>,
+) -> Result<Life, Error> {
+    match (replay, &config.load_state) {
+        (Some(session), _) => {
+            let mut life = session
+                .start(config.engine, config.threads.get())
+                .map_err(|err| {
+                    Error::Pattern(format!(
+                        "{}: {err}",
+                        config.replay.as_ref().unwrap().display()
+                    ))
+                })?;
+            track(&mut life, config).map_err(Error::Usage)?;
+            Ok(life)
+        }
+        (None, Some(path)) => {
+            let mut life = state::load(path, config.engine, config.threads.get())?;
+            track(&mut life, config).map_err(Error::Usage)?;
+            Ok(life)
+        }
+        (None, None) => create(config, glyphs, terminal_size),
+    }
+}
+
+/// Sets up a new simulation from the pattern, the circuit or the random soup configured
+pub fn create(
+    config: &Config,
+    glyphs: &Glyphs,
+    terminal_size: Option<(u16, u16)>,
+) -> Result<Life, Error> {
+    let wireworld = config.automaton == Automaton::Wireworld;
+    let ant = config.automaton == Automaton::Ant;
+    let elementary = config.automaton == Automaton::Elementary;
+    if (ant || elementary) && (config.pattern_file.is_some() || config.stdin) {
+        return Err(Error::Usage(format!(
+            "--automaton {} starts from a field of its own, without a --pattern-file or --stdin",
+            config.automaton.to_possible_value().unwrap().get_name()
+        )));
+    }
+    let circuit = wireworld.then(|| read_circuit(config)).transpose()?;
+    let pattern = match (&config.pattern_file, config.pattern) {
+        _ if wireworld || ant || elementary => None,
+        (Some(path), _) => Some(Pattern::load(path, config.format).map_err(Error::from)),
+        (None, Some(name)) => Some(Ok(library::get(name).unwrap())),
+        (None, None) if config.stdin => Some(read_stdin_board()),
+        (None, None) if config.image.is_some() => {
+            // Images come without a rule, so the one given is the one they run under
+            let neighborhood = config
+                .neighborhood
+                .or(config.rule.as_ref().map(Rules::neighborhood))
+                .unwrap_or(Neighborhood::Moore);
+            let (width, height) = terminal_size
+                .map(|terminal| {
+                    render::fit(
+                        terminal,
+                        neighborhood,
+                        config.renderer,
+                        glyphs,
+                        config.status_lines(),
+                    )
+                })
+                .unwrap_or((DEFAULT_WIDTH, DEFAULT_HEIGHT));
+            Some(
+                image::load(
+                    config.image.as_deref().unwrap(),
+                    config.width.unwrap_or(width),
+                    config.height.unwrap_or(height),
+                    config.threshold,
+                )
+                .map_err(Error::from),
+            )
+        }
+        (None, None) => None,
+    }
+    .transpose()?;
+
+    let rules = if wireworld {
+        Rule::Wireworld.into()
+    } else if ant {
+        Rule::Ant(config.turns).into()
+    } else if elementary {
+        Rule::Elementary(config.wolfram).into()
+    } else {
+        config
+            .rule
+            .clone()
+            .or_else(|| config.rules.as_ref().map(|list| list.get(0).clone()))
+            .or(pattern
+                .as_ref()
+                .and_then(|pattern| pattern.rule.map(Rules::from)))
+            .unwrap_or_default()
+    };
+    let rules = match config.neighborhood {
+        Some(neighborhood) => rules
+            .with_neighborhood(neighborhood)
+            .map_err(Error::Usage)?,
+        None => rules,
+    };
+    // A board from stdin or a circuit brings its own size, anything else gets the usual
+    // defaults, unless it is to fill the terminal
+    let (width, height) = match (&pattern, terminal_size) {
+        (_, Some(terminal)) => render::fit(
+            terminal,
+            rules.neighborhood(),
+            config.renderer,
+            glyphs,
+            config.status_lines(),
+        ),
+        (Some(pattern), None) if config.stdin => (pattern.width, pattern.height),
+        (None, None) if circuit.is_some() => {
+            let circuit = circuit.as_ref().unwrap();
+            (circuit.width, circuit.height)
+        }
+        _ => (DEFAULT_WIDTH, DEFAULT_HEIGHT),
+    };
+    let width = config.width.unwrap_or(width);
+    let height = config.height.unwrap_or(height);
+    if width.checked_mul(height).is_none() {
+        return Err(Error::Usage(format!(
+            "a {width}x{height} field has more cells than can be counted"
+        )));
+    }
+    // Without a pattern or any placements to start from, or a field to draw by hand, the
+    // field is a random soup
+    let drawn = pattern.is_some() || circuit.is_some() || ant || (elementary && !config.random_row);
+    let seed = (!drawn && config.place.is_empty() && !config.edit).then(|| {
+        config.seed.unwrap_or_else(|| {
+            let seed = rand::random();
+            eprintln!("seed: {seed}");
+            seed
+        })
+    });
+    let field = match (pattern, seed) {
+        _ if ant => {
+            let mut field = Field::new(width, height);
+            ant::place_ants(&mut field, config.turns, config.ants as usize)
+                .map_err(Error::Usage)?;
+            field
+        }
+        (_, seed) if elementary => {
+            let mut field = Field::new(width, height);
+            // The first generation is the bottom row, which scrolls up from there
+            let row: Field = match seed {
+                Some(seed) => Field::random(seed, width, 1, config.density, Symmetry::None)
+                    .map_err(Error::Usage)?,
+                None => {
+                    let mut row = Field::new(width, 1);
+                    row.set(width / 2, 0, true)
+                        .map_err(|err| Error::Usage(err.to_string()))?;
+                    row
+                }
+            };
+            for x in 0..width {
+                let state = row.get(x, 0).map_err(|err| Error::Usage(err.to_string()))?;
+                field
+                    .set(x, height - 1, state)
+                    .map_err(|err| Error::Usage(err.to_string()))?;
+            }
+            field
+        }
+        _ if circuit.is_some() => {
+            let mut field = Field::new(width, height);
+            circuit
+                .unwrap()
+                .place_centered(&mut field)
+                .map_err(Error::Pattern)?;
+            field
+        }
+        (Some(pattern), _) => {
+            let mut field = Field::new(width, height);
+            pattern.place_centered(&mut field).map_err(Error::Pattern)?;
+            field
+        }
+        (None, Some(seed)) => Field::random(seed, width, height, config.density, config.symmetry)
+            .map_err(Error::Usage)?,
+        (None, None) => Field::new(width, height),
+    };
+    if config.topology != Topology::Torus && config.boundary != Boundary::Wrap {
+        return Err(Error::Usage(
+            "--topology only applies to the wrap boundary".to_string(),
+        ));
+    }
+    // Rather than the torus of the default boundary
+    if config.engine == EngineKind::Hashlife && !config.unbounded {
+        return Err(Error::Usage(
+            "HashLife runs on an unbounded plane, which has no edges, so it needs --unbounded"
+                .to_string(),
+        ));
+    }
+    start(field, rules, seed, config)
+}
+
+/// Sets up the simulation of a field as configured, with the placements on top of it
+pub fn start(
+    mut field: Field,
+    rules: Rules,
+    seed: Option<u64>,
+    config: &Config,
+) -> Result<Life, Error> {
+    field.set_edges(config.boundary, config.topology);
+    for placement in &config.place {
+        placement
+            .apply(&mut field, config.clip)
+            .map_err(Error::Pattern)?;
+    }
+    let mut life = Life::new(
+        field,
+        rules,
+        config.unbounded,
+        config.engine,
+        config.threads.get(),
+    )
+    .map_err(Error::Usage)?;
+    if config.stop_if_escapes.is_some() && life.edges.is_none() {
+        return Err(Error::Usage(
+            "--stop-if-escapes needs a field with edges, which an unbounded plane has none of"
+                .to_string(),
+        ));
+    }
+    life.seed = seed;
+    if config.noise > 0.0 {
+        let seed = seed.or(config.seed).unwrap_or_else(|| {
+            let seed = rand::random();
+            eprintln!("noise seed: {seed}");
+            seed
+        });
+        life.noise = Some(Noise::new(config.noise, seed));
+    }
+    if config.variant == Some(Variant::Immigration) {
+        let rules = &life.rules;
+        if let Some(rule) = rules
+            .iter()
+            .find(|rule| !matches!(rule, Rule::OuterTotalistic(_)))
+        {
+            return Err(Error::Usage(format!(
+                "--variant immigration needs rules counting the neighbors, not {rule}"
+            )));
+        }
+        life.immigration = Some(Immigration::new(
+            config.split,
+            seed.unwrap_or(0),
+            life.width,
+            rules.neighborhood(),
+            &*life.engine,
+        ));
+    }
+    track(&mut life, config).map_err(Error::Usage)?;
+    Ok(life)
+}
+
+/// Keeps track of what is configured to be about the generations to come, other than what a
+/// simulation that was picked up where it was left off already kept
+pub fn track(life: &mut Life, config: &Config) -> Result<(), String> {
+    // Nothing that is only there to be shown is kept track of without showing it
+    let shown = Display::of(config) != Display::Headless;
+    // Other than for a session replayed to go back and forth through it the way it did
+    if (shown || config.replay.is_some())
+        && config.history > 0
+        && life.edges.is_some()
+        && life.history.is_none()
+    {
+        life.history = Some(History::new(
+            config.history,
+            life.width,
+            life.height,
+            life.rules.states(),
+            &*life.engine,
+        ));
+    }
+    if shown && config.color == Some(Theme::Age) && life.ages.is_none() {
+        life.ages = Some(Ages::new(&*life.engine));
+    }
+    // Under noise, the generations repeating doesn't mean that they will go on repeating
+    if config.cycle_window > 0
+        && (shown || config.stop_on_cycle || config.looping)
+        && life.noise.is_none()
+    {
+        life.cycles = Some(Cycles::new(
+            config.cycle_window,
+            life.rules.turns() as u64,
+            life.generation(),
+            &*life.engine,
+        ));
+    }
+    if shown && config.trail > 0 && life.trails.is_none() {
+        life.trails = Some(Trails::new(config.trail, &*life.engine));
+    }
+    if Display::of(config) == Display::Screen && life.edits.is_none() {
+        life.edits = Some(Edits::new(config.undo_depth));
+    }
+    if config.stop_on_cycle && life.noise.is_some() {
+        return Err("--stop-on-cycle can't be used with a state under noise".to_string());
+    }
+    Ok(())
+}
+
+/// Starts over from a new random soup of the same size, under the same rules
+pub fn reseed(life: &mut Life, config: &Config, seed: u64) -> Result<(), String> {
+    let field = Field::random(
+        seed,
+        life.width,
+        life.height,
+        config.density,
+        config.symmetry,
+    )?;
+    *life = start(field, life.rules.clone(), Some(seed), config).map_err(|err| err.to_string())?;
+    Ok(())
+}
+
+fn read_stdin() -> Result<String, Error> {
+    let mut text = String::new();
+    io::stdin()
+        .read_to_string(&mut text)
+        .map_err(|err| Error::Io(format!("failed to read stdin: {err}")))?;
+    Ok(text)
+}
+
+fn read_stdin_board() -> Result<Pattern, Error> {
+    formats::ascii::parse(&read_stdin()?).map_err(|err| Error::Pattern(format!("stdin: {err}")))
+}
+
+/// The circuit Wireworld runs, which it can't do without since random soups make no circuits
+fn read_circuit(config: &Config) -> Result<Board, Error> {
+    let (text, source) = match &config.pattern_file {
+        Some(path) => (
+            fs::read_to_string(path)
+                .map_err(|err| Error::Io(format!("failed to read {}: {err}", path.display())))?,
+            path.display().to_string(),
+        ),
+        None if config.stdin => (read_stdin()?, "stdin".to_string()),
+        None => {
+            return Err(Error::Usage(
+                "--automaton wireworld runs the circuit of a --pattern-file or --stdin".to_string(),
+            ))
+        }
+    };
+    Board::parse(&text).map_err(|err| Error::Pattern(format!("{source}: {err}")))
+}
+
+const DEFAULT_WIDTH: usize = 40;
+const DEFAULT_HEIGHT: usize = 15;
@@ -0,0 +1,103 @@
+//! Pictures of the field at chosen generations, or whenever asked for
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use game_of_life::{
+    image::{render_png, render_svg, SvgOptions},
+    Life,
+};
+
+/// Stands for the generation in the path of the snapshots
+const PLACEHOLDER: &str = "{gen}";
+
+/// Writes PNG pictures of the field, each cell a square block of `scale` pixels on a side
+pub struct Snapshots {
+    /// Where the snapshots of the chosen generations go, or that of the last one without any
+    path: Option<PathBuf>,
+    at: Vec<u64>,
+    scale: u16,
+    /// Colors of the cells by state
+    colors: Vec<[u8; 3]>,
+}
+
+impl Snapshots {
+    /// Takes snapshots at the generations `at` into `path`, which needs a `{gen}` to tell more
+    /// than one of them apart
+    pub fn new(
+        path: Option<&Path>,
+        at: &[u64],
+        scale: u16,
+        colors: Vec<[u8; 3]>,
+    ) -> Result<Self, String> {
+        if let Some(path) = path {
+            let mut at = at.to_vec();
+            at.sort_unstable();
+            at.dedup();
+            if at.len() > 1 && !path.to_string_lossy().contains(PLACEHOLDER) {
+                return Err(format!(
+                    "snapshots of several generations need a {PLACEHOLDER} in the --snapshot path to be told apart"
+                ));
+            }
+        }
+        Ok(Self {
+            path: path.map(Path::to_path_buf),
+            at: at.to_vec(),
+            scale,
+            colors,
+        })
+    }
+
+    /// Takes the snapshot of the generation `life` is at, if it is one of the chosen ones
+    pub fn record(&self, life: &Life) -> Result<(), String> {
+        match &self.path {
+            Some(path) if self.at.contains(&life.generation()) => self.write(life, path),
+            _ => Ok(()),
+        }
+    }
+
+    /// Whether snapshots are taken of chosen generations, which the run has to go through
+    pub fn chosen(&self) -> bool {
+        self.path.is_some() && !self.at.is_empty()
+    }
+
+    /// Takes the snapshot of the last generation, if no others were chosen
+    pub fn finish(&self, life: &Life) -> Result<(), String> {
+        match &self.path {
+            Some(path) if self.at.is_empty() => self.write(life, path),
+            _ => Ok(()),
+        }
+    }
+
+    /// Takes a snapshot of the generation `life` is at in the working directory, giving back
+    /// where it went
+    pub fn take(&self, life: &Life) -> Result<PathBuf, String> {
+        let path = PathBuf::from(format!("snapshot-{}.png", life.generation()));
+        self.write(life, &path)?;
+        Ok(path)
+    }
+
+    fn write(&self, life: &Life, path: &Path) -> Result<(), String> {
+        let path = path
+            .to_string_lossy()
+            .replace(PLACEHOLDER, &life.generation().to_string());
+        let png = render_png(&life.snapshot(), self.scale, &self.colors);
+        fs::write(&path, png).map_err(|err| format!("failed to write {path}: {err}"))
+    }
+}
+
+/// Vector pictures of the field, written over the same file each time
+pub struct Svg {
+    pub path: PathBuf,
+    pub options: SvgOptions,
+}
+
+impl Svg {
+    pub fn write(&self, life: &Life) -> Result<(), String> {
+        let svg = render_svg(&life.snapshot(), &self.options);
+        fs::write(&self.path, svg)
+            .map_err(|err| format!("failed to write {}: {err}", self.path.display()))
+    }
+}
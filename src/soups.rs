@@ -0,0 +1,53 @@
+//! Random soups one after another with --loop, each taking over once the one before settled
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use game_of_life::Life;
+
+use crate::stop::Stop;
+
+/// The summaries of the soups, a line each with what is needed to get back to the soup,
+/// written to stderr as they settle or, while a screen takes up the terminal, once it is gone
+pub struct Soups {
+    deferred: Option<Vec<String>>,
+}
+
+impl Soups {
+    pub fn new(defer: bool) -> Self {
+        Self {
+            deferred: defer.then(Vec::new),
+        }
+    }
+
+    /// Sums up the soup `life` is at, which stopped by `stop`, giving back the summary
+    pub fn finish(&mut self, life: &Life, stop: Stop) -> String {
+        let summary = format!(
+            "seed {}: {}, {} live cells",
+            life.seed.unwrap_or_default(),
+            stop.describe(life),
+            life.population()
+        );
+        match &mut self.deferred {
+            Some(deferred) => deferred.push(summary.clone()),
+            None => eprintln!("{summary}"),
+        }
+        summary
+    }
+
+    /// Writes out the summaries kept until now
+    pub fn flush(&mut self) {
+        for summary in self
+            .deferred
+            .iter_mut()
+            .flat_map(|deferred| deferred.drain(..))
+        {
+            eprintln!("{summary}");
+        }
+    }
+}
+
+/// The seed of the soup after the one `life` is at, derived from its seed so that the same
+/// --seed goes through the same soups
+pub fn next_seed(life: &Life) -> u64 {
+    StdRng::seed_from_u64(life.seed.unwrap_or_default()).gen()
+}
@@ -0,0 +1,55 @@
+//! The population of the last generations at a glance
+
+use std::collections::VecDeque;
+
+/// Bars from the lowest to the highest population in view
+const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Populations of the last generations, as many as there are columns to show them in
+pub struct Sparkline {
+    /// Oldest generation first
+    populations: VecDeque<u64>,
+    capacity: usize,
+}
+
+impl Sparkline {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            populations: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Keeps the populations of fewer or more generations, dropping the oldest ones if need be
+    pub fn resize(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        while self.populations.len() > capacity {
+            self.populations.pop_front();
+        }
+    }
+
+    /// Adds the population of the next generation, dropping the oldest one if full
+    pub fn push(&mut self, population: u64) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.populations.len() == self.capacity {
+            self.populations.pop_front();
+        }
+        self.populations.push_back(population);
+    }
+
+    pub fn clear(&mut self) {
+        self.populations.clear();
+    }
+
+    /// Draws a bar per generation, as high as its population compared to the highest one, or
+    /// a space when everything died
+    pub fn render(&self, out: &mut String) {
+        let max = self.populations.iter().copied().max().unwrap_or(0);
+        out.extend(self.populations.iter().map(|&population| match population {
+            0 => ' ',
+            _ => BARS[((population * BARS.len() as u64).div_ceil(max) - 1) as usize],
+        }));
+    }
+}
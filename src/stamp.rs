@@ -0,0 +1,63 @@
+//! Built-in patterns placed onto the field while it is shown
+
+use game_of_life::{library, pattern::Pattern, Rect};
+
+pub struct Stamp {
+    pub name: &'static str,
+    pattern: Pattern,
+    /// Whether its dead cells kill those under them too, as pasting does
+    overwrite: bool,
+}
+
+impl Stamp {
+    pub fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            pattern: library::get(name).expect("stamps are built-in patterns"),
+            overwrite: false,
+        }
+    }
+
+    /// The cells copied from the field, to be pasted over the ones there
+    pub fn pasted(pattern: Pattern) -> Self {
+        Self {
+            name: "the copied cells",
+            pattern,
+            overwrite: true,
+        }
+    }
+
+    /// Rotates the stamp clockwise by this many quarter turns
+    pub fn rotated(self, turns: u8) -> Self {
+        Self {
+            name: self.name,
+            pattern: (0..turns % 4).fold(self.pattern, |pattern, _| pattern.rotated()),
+            overwrite: self.overwrite,
+        }
+    }
+
+    /// Where the live cells of the stamp land when it is centered on (x, y)
+    pub fn cells(&self, (x, y): (i64, i64)) -> impl Iterator<Item = (i64, i64)> + '_ {
+        let (left, top) = self.corner((x, y));
+        self.pattern
+            .cells
+            .iter()
+            .map(move |&(px, py)| (left + px as i64, top + py as i64))
+    }
+
+    /// The cells the stamp kills before bringing its own to life when centered on (x, y), if
+    /// it does
+    pub fn cleared(&self, (x, y): (i64, i64)) -> Option<Rect> {
+        let (left, top) = self.corner((x, y));
+        let (width, height) = (self.pattern.width as i64, self.pattern.height as i64);
+        (self.overwrite && width > 0 && height > 0)
+            .then(|| Rect::new((left, top), (left + width - 1, top + height - 1)))
+    }
+
+    fn corner(&self, (x, y): (i64, i64)) -> (i64, i64) {
+        (
+            x - self.pattern.width as i64 / 2,
+            y - self.pattern.height as i64 / 2,
+        )
+    }
+}
@@ -0,0 +1,270 @@
+//! Everything about a simulation needed to pick it up where it was left off, down to the
+//! random flips still to come
+//!
+//! A state file is made of lines of words, starting with `game-of-life state` and the version
+//! of the format, followed by the generation, the rules, the size of the field, its edges, the
+//! seed of the random soup if any and the noise if any. After that come the cells that are not
+//! dead with their states, and then whichever of the ages, the trails, the heat and the colors
+//! of Immigration were kept.
+
+use std::{fmt::Write, fs, path::Path, str::FromStr};
+
+use clap::ValueEnum;
+
+use crate::{
+    age::Ages,
+    boundary::{Boundary, Topology},
+    engine::EngineKind,
+    heat::Heat,
+    noise::Noise,
+    rule::{Rules, DEAD},
+    trail::Trails,
+    variant::{Immigration, Side, Split},
+    Field, Life, LoadError,
+};
+
+const HEADER: &str = "game-of-life state";
+
+/// Bumped whenever the format changes in a way older versions can't read
+const VERSION: u32 = 1;
+
+pub fn save(life: &Life, path: &Path) -> Result<(), String> {
+    fs::write(path, write(life)).map_err(|err| format!("failed to write {}: {err}", path.display()))
+}
+
+pub fn load(path: &Path, kind: EngineKind, threads: usize) -> Result<Life, LoadError> {
+    let text = fs::read_to_string(path)
+        .map_err(|err| LoadError::Read(format!("failed to read {}: {err}", path.display())))?;
+    parse(&text, kind, threads)
+        .map_err(|err| LoadError::Parse(format!("{}: {err}", path.display())))
+}
+
+/// The state of the simulation, with the cells sorted so that the same state is always
+/// written the same
+pub fn write(life: &Life) -> String {
+    let mut out = format!("{HEADER} {VERSION}\n");
+    // Writing to a string can't fail
+    let _ = writeln!(out, "generation {}", life.generation());
+    let _ = writeln!(out, "rules {}", life.rules);
+    let _ = writeln!(out, "size {} {}", life.width, life.height);
+    match life.edges {
+        Some((boundary, topology)) => {
+            let _ = writeln!(out, "edges {} {}", name(boundary), name(topology));
+        }
+        None => out.push_str("edges none\n"),
+    }
+    if let Some(seed) = life.seed {
+        let _ = writeln!(out, "seed {seed}");
+    }
+    if let Some(noise) = &life.noise {
+        // Floats are written with as many digits as it takes to read back the very same one
+        let _ = writeln!(out, "noise {} {}", noise.probability(), noise.seed());
+    }
+
+    for ((x, y), state) in sorted(life.engine.cell_states()) {
+        let _ = writeln!(out, "cell {x} {y} {state}");
+    }
+    if let Some(ages) = &life.ages {
+        for ((x, y), age) in sorted(ages.iter().collect()) {
+            let _ = writeln!(out, "age {x} {y} {age}");
+        }
+    }
+    if let Some(trails) = &life.trails {
+        let _ = writeln!(out, "trails {}", trails.length());
+        for ((x, y), left) in sorted(trails.iter().collect()) {
+            let _ = writeln!(out, "trail {x} {y} {left}");
+        }
+    }
+    if let Some(heat) = &life.heat {
+        out.push_str("heat\n");
+        for ((x, y), heat) in sorted(heat.iter().collect()) {
+            let _ = writeln!(out, "hot {x} {y} {heat}");
+        }
+    }
+    if let Some(immigration) = &life.immigration {
+        let (split, seed, middle) = immigration.split();
+        let _ = writeln!(out, "immigration {} {middle} {seed}", name(split));
+        for ((x, y), side) in sorted(immigration.iter().collect()) {
+            let side = match side {
+                Side::First => "first",
+                Side::Second => "second",
+            };
+            let _ = writeln!(out, "side {x} {y} {side}");
+        }
+    }
+    out
+}
+
+/// Reads back a state, computing the generations after it with `kind` of engine
+pub fn parse(text: &str, kind: EngineKind, threads: usize) -> Result<Life, String> {
+    let mut lines = text
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty());
+
+    let version = lines
+        .next()
+        .and_then(|(_, line)| line.strip_prefix(HEADER))
+        .ok_or("not a state file")?
+        .trim();
+    if version != VERSION.to_string() {
+        return Err(format!(
+            "state files of version {version} can't be read, only of version {VERSION}"
+        ));
+    }
+
+    let mut generation = None;
+    let mut rules = None;
+    let mut size = None;
+    let mut edges = None;
+    let mut seed = None;
+    let mut noise = None;
+    let mut cells = Vec::new();
+    let mut ages = None::<Vec<_>>;
+    let mut trails = None::<(u8, Vec<_>)>;
+    let mut heat = None::<Vec<_>>;
+    let mut immigration = None::<(Split, i64, u64, Vec<_>)>;
+    for (number, line) in lines {
+        let (key, rest) = line.split_once(' ').unwrap_or((line, ""));
+        let words: Vec<_> = rest.split_whitespace().collect();
+        let wrong = || format!("line {number}: {line:?} is not a valid {key} line");
+        let value = |i: usize| words.get(i).copied().ok_or_else(wrong);
+        match (key, words.len()) {
+            ("generation", 1) => generation = Some(number_at::<u64>(&words, 0, &wrong)?),
+            ("rules", _) => {
+                rules = Some(
+                    rest.parse::<Rules>()
+                        .map_err(|err| format!("line {number}: {err}"))?,
+                );
+            }
+            ("size", 2) => {
+                let (width, height) = (
+                    number_at::<usize>(&words, 0, &wrong)?,
+                    number_at::<usize>(&words, 1, &wrong)?,
+                );
+                if width == 0 || height == 0 {
+                    return Err(wrong());
+                }
+                size = Some((width, height));
+            }
+            ("edges", 1) if value(0)? == "none" => edges = Some(None),
+            ("edges", 2) => {
+                let boundary = Boundary::from_str(value(0)?, true).map_err(|_| wrong())?;
+                let topology = Topology::from_str(value(1)?, true).map_err(|_| wrong())?;
+                edges = Some(Some((boundary, topology)));
+            }
+            ("seed", 1) => seed = Some(number_at::<u64>(&words, 0, &wrong)?),
+            ("noise", 2) => {
+                let probability = number_at::<f64>(&words, 0, &wrong)?;
+                if !(0.0..=1.0).contains(&probability) {
+                    return Err(wrong());
+                }
+                noise = Some((probability, number_at::<u64>(&words, 1, &wrong)?));
+            }
+            ("cell", 3) => cells.push(entry(&words, &wrong)?),
+            ("age", 3) => ages
+                .get_or_insert_with(Vec::new)
+                .push(entry(&words, &wrong)?),
+            ("trails", 1) => trails = Some((number_at::<u8>(&words, 0, &wrong)?, Vec::new())),
+            ("trail", 3) => trails
+                .as_mut()
+                .ok_or_else(|| format!("line {number}: a trail before the trails line"))?
+                .1
+                .push(entry(&words, &wrong)?),
+            ("heat", 0) => heat = Some(Vec::new()),
+            ("hot", 3) => heat
+                .as_mut()
+                .ok_or_else(|| format!("line {number}: a hot cell before the heat line"))?
+                .push(entry(&words, &wrong)?),
+            ("immigration", 3) => {
+                let split = Split::from_str(value(0)?, true).map_err(|_| wrong())?;
+                let middle = number_at::<i64>(&words, 1, &wrong)?;
+                immigration = Some((
+                    split,
+                    middle,
+                    number_at::<u64>(&words, 2, &wrong)?,
+                    Vec::new(),
+                ));
+            }
+            ("side", 3) => {
+                let side = match value(2)? {
+                    "first" => Side::First,
+                    "second" => Side::Second,
+                    _ => return Err(wrong()),
+                };
+                immigration
+                    .as_mut()
+                    .ok_or_else(|| format!("line {number}: a side before the immigration line"))?
+                    .3
+                    .push((cell_at(&words, &wrong)?, side));
+            }
+            _ => return Err(format!("line {number}: {line:?} is not understood")),
+        }
+    }
+
+    let missing = |what| format!("the {what} line is missing");
+    let generation = generation.ok_or_else(|| missing("generation"))?;
+    let rules = rules.ok_or_else(|| missing("rules"))?;
+    let (width, height) = size.ok_or_else(|| missing("size"))?;
+    let edges = edges.ok_or_else(|| missing("edges"))?;
+    if edges.is_some() && kind == EngineKind::Hashlife {
+        return Err("HashLife can't go on with a bounded field".to_string());
+    }
+    if let Some(&(cell, state)) = cells
+        .iter()
+        .find(|&&(_, state)| state == DEAD || state >= rules.states())
+    {
+        return Err(format!("{cell:?} can't be in state {state} under {rules}"));
+    }
+
+    let mut field = Field::new(width, height);
+    if let Some((boundary, topology)) = edges {
+        field.set_edges(boundary, topology);
+    }
+    let mut life = Life::new(field, rules, edges.is_none(), kind, threads)?;
+    for ((x, y), state) in cells {
+        life.engine.set(x, y, state);
+    }
+    life.generation = generation;
+    life.tallied = generation;
+    life.recount();
+    life.seed = seed;
+    life.noise = noise.map(|(probability, seed)| Noise::new(probability, seed));
+    life.ages = ages.map(Ages::restore);
+    life.trails = trails.map(|(length, fading)| Trails::restore(length, fading, &*life.engine));
+    life.heat = heat.map(|heat| Heat::restore(heat, &*life.engine));
+    life.immigration = immigration.map(|(split, middle, seed, sides)| {
+        Immigration::restore(split, seed, middle, life.rules.neighborhood(), sides)
+    });
+    Ok(life)
+}
+
+fn name(value: impl ValueEnum) -> String {
+    value.to_possible_value().unwrap().get_name().to_string()
+}
+
+fn sorted<T>(mut cells: Vec<((i64, i64), T)>) -> Vec<((i64, i64), T)> {
+    cells.sort_unstable_by_key(|&(cell, _)| cell);
+    cells
+}
+
+fn number_at<T: FromStr>(
+    words: &[&str],
+    i: usize,
+    wrong: &impl Fn() -> String,
+) -> Result<T, String> {
+    words[i].parse().map_err(|_| wrong())
+}
+
+fn cell_at(words: &[&str], wrong: &impl Fn() -> String) -> Result<(i64, i64), String> {
+    Ok((number_at(words, 0, wrong)?, number_at(words, 1, wrong)?))
+}
+
+/// A cell followed by a number about it
+fn entry<T: FromStr>(
+    words: &[&str],
+    wrong: &impl Fn() -> String,
+) -> Result<((i64, i64), T), String> {
+    Ok((cell_at(words, wrong)?, number_at(words, 2, wrong)?))
+}
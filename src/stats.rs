@@ -0,0 +1,103 @@
+//! Numbers about each generation, written out for other tools to analyze
+
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use clap::ValueEnum;
+
+use game_of_life::{noise::Noise, Life};
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum StatsFormat {
+    /// A JSON object per line, such as
+    /// `{"gen":12,"population":148,"births":31,"deaths":27,"flips":0,"poked":0,"bbox":[3,5,38,14]}`,
+    /// where the births and deaths are those of the rule, the flips those of the --noise, the
+    /// poked the cells that x brought to life right before the generation was computed, and
+    /// the bounding box of the live cells goes from the left, top to the right, bottom one, or
+    /// is null without any. Under --compare, `"divergence"` adds how many cells the two
+    /// simulations differ in, the numbers before it being those of the first.
+    Ndjson,
+}
+
+/// Where the numbers about the generations go, flushed after each one so that they can be
+/// followed as they come
+pub struct Stats {
+    format: StatsFormat,
+    out: BufWriter<Box<dyn Write>>,
+    path: Option<String>,
+}
+
+impl Stats {
+    /// Writes to the file at `path`, or to stdout if there is none
+    pub fn new(format: StatsFormat, path: Option<&Path>) -> Result<Self, String> {
+        let out: Box<dyn Write> = match path {
+            Some(path) => Box::new(
+                File::create(path)
+                    .map_err(|err| format!("failed to create {}: {err}", path.display()))?,
+            ),
+            None => Box::new(io::stdout()),
+        };
+        Ok(Self {
+            format,
+            out: BufWriter::new(out),
+            path: path.map(|path| path.display().to_string()),
+        })
+    }
+
+    /// Writes out the generation shown
+    pub fn record(&mut self, life: &Life) -> Result<(), String> {
+        self.write(life, None)
+    }
+
+    /// Writes out the generation shown of the first of two simulations compared, which differ
+    /// in `divergence` cells
+    pub fn record_divergence(&mut self, life: &Life, divergence: u64) -> Result<(), String> {
+        self.write(life, Some(divergence))
+    }
+
+    fn write(&mut self, life: &Life, divergence: Option<u64>) -> Result<(), String> {
+        let changes = life.engine.changes();
+        // Nothing was born or died before the first generation
+        let (births, deaths) = match life.generation() {
+            0 => (0, 0),
+            _ => (changes.births(), changes.deaths()),
+        };
+        let flips = life.noise.as_ref().map_or(0, Noise::flips);
+        let live = life.engine.live_cells();
+        let bbox = live
+            .iter()
+            .fold(None::<[i64; 4]>, |bbox, &(x, y)| match bbox {
+                None => Some([x, y, x, y]),
+                Some([left, top, right, bottom]) => {
+                    Some([left.min(x), top.min(y), right.max(x), bottom.max(y)])
+                }
+            });
+        let result = match self.format {
+            StatsFormat::Ndjson => write!(
+                self.out,
+                "{{\"gen\":{},\"population\":{},\"births\":{births},\"deaths\":{deaths},\"flips\":{flips},\"poked\":{},\"bbox\":",
+                life.generation(),
+                life.population(),
+                life.poked()
+            )
+            .and_then(|()| match bbox {
+                Some([left, top, right, bottom]) => {
+                    write!(self.out, "[{left},{top},{right},{bottom}]")
+                }
+                None => write!(self.out, "null"),
+            })
+            .and_then(|()| match divergence {
+                Some(divergence) => writeln!(self.out, ",\"divergence\":{divergence}}}"),
+                None => writeln!(self.out, "}}"),
+            }),
+        }
+        .and_then(|()| self.out.flush());
+        result.map_err(|err| {
+            let to = self.path.as_deref().unwrap_or("stdout");
+            format!("failed to write the stats to {to}: {err}")
+        })
+    }
+}
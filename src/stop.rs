@@ -0,0 +1,160 @@
+//! The ways the simulation stops by itself
+
+use game_of_life::{session::Session, Life, Rule};
+
+use crate::Config;
+
+/// Why the simulation stopped
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Stop {
+    Quit,
+    /// All the cells died, unless running on regardless
+    DiedOut,
+    /// A generation was the same as the one before, unless running on regardless
+    Stable,
+    /// The generations started repeating, with --stop-on-cycle or --loop
+    Cycle,
+    /// As many generations as --generations were computed
+    Limit,
+    /// There were fewer live cells than --stop-population-below
+    PopulationBelow(u64),
+    /// There were more live cells than --stop-population-above
+    PopulationAbove(u64),
+    /// A live cell came as close to the edges as --stop-if-escapes
+    Escaped(u64),
+}
+
+impl Stop {
+    /// Tells apart the ways the simulation can stop by itself, other than running for as long
+    /// as it was asked to
+    pub fn exit_code(self) -> i32 {
+        match self {
+            Self::Quit | Self::Limit => 0,
+            Self::DiedOut => 2,
+            Self::Stable => 3,
+            Self::Cycle => 4,
+            Self::PopulationBelow(_) => 5,
+            Self::PopulationAbove(_) => 6,
+            Self::Escaped(_) => 7,
+        }
+    }
+
+    /// What the --summary json calls the way the simulation stopped
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Quit => "quit",
+            Self::DiedOut => "died-out",
+            Self::Stable => "stable",
+            Self::Cycle => "cycle",
+            Self::Limit => "limit",
+            Self::PopulationBelow(_) => "population-below",
+            Self::PopulationAbove(_) => "population-above",
+            Self::Escaped(_) => "escaped",
+        }
+    }
+
+    /// How the generations of `life` came to a stop, for the summary
+    pub fn describe(self, life: &Life) -> String {
+        let generations = match life.generation() {
+            1 => "1 generation".to_string(),
+            n => format!("{n} generations"),
+        };
+        let cells = |n: u64| if n == 1 { "cell" } else { "cells" };
+        match self {
+            Self::Quit => format!("stopped after {generations}"),
+            Self::DiedOut => format!("died out after {generations}"),
+            Self::Stable => format!("stable after {generations}"),
+            Self::Limit => format!("reached the limit of {generations}"),
+            Self::PopulationBelow(n) => {
+                format!("fell below {n} live {} after {generations}", cells(n))
+            }
+            Self::PopulationAbove(n) => {
+                format!("rose above {n} live {} after {generations}", cells(n))
+            }
+            Self::Escaped(0) => format!("reached the edges after {generations}"),
+            Self::Escaped(k) => {
+                format!(
+                    "came within {k} {} of the edges after {generations}",
+                    cells(k)
+                )
+            }
+            Self::Cycle => {
+                let cycle = life.cycle().expect("stopping on a cycle needs one");
+                format!(
+                    "entered a cycle of period {} at generation {}",
+                    cycle.period, cycle.start
+                )
+            }
+        }
+    }
+}
+
+/// Why the simulation stops at the current generation, if it does. Unless `running`, it only
+/// stops once it has gone far enough, since stepping by hand is up to whoever does it. A
+/// session replayed stops where it did, however it did.
+pub fn check(
+    life: &Life,
+    config: &Config,
+    replay: Option<&Session>,
+    running: bool,
+) -> Option<Stop> {
+    if let Some(session) = replay {
+        let limit = config.generations.unwrap_or(u64::MAX);
+        return (session.ended(life.generation()) || life.generation() >= limit)
+            .then_some(Stop::Limit);
+    }
+    // The noise can always bring cells to life or flip the ones that stopped changing
+    if running && life.generation() > 0 && !config.keep_running && life.noise.is_none() {
+        if life.population() == 0 && !ants_left(life) {
+            return Some(Stop::DiedOut);
+        }
+        if life.engine.changes().is_empty() {
+            return Some(Stop::Stable);
+        }
+    }
+    if running && life.generation() > 0 {
+        let population = life.population();
+        if let Some(below) = config.stop_population_below.filter(|&n| population < n) {
+            return Some(Stop::PopulationBelow(below));
+        }
+        if let Some(above) = config.stop_population_above.filter(|&n| population > n) {
+            return Some(Stop::PopulationAbove(above));
+        }
+        if let Some(margin) = config.stop_if_escapes.filter(|&k| escaped(life, k)) {
+            return Some(Stop::Escaped(margin));
+        }
+    }
+    if running && (config.stop_on_cycle || config.looping) && life.cycle().is_some() {
+        return Some(Stop::Cycle);
+    }
+    // Generations stepped through by hand count as well
+    config
+        .generations
+        .is_some_and(|limit| life.generation() >= limit)
+        .then_some(Stop::Limit)
+}
+
+/// Whether a live cell is `margin` cells or fewer from the edges of the field, counting the
+/// cells of the strips along them rather than all of them
+fn escaped(life: &Life, margin: u64) -> bool {
+    let (width, height) = (life.width as u64, life.height as u64);
+    let strip = (margin + 1).min(width).min(height);
+    let (right, bottom) = ((width - strip) as i64, (height - strip) as i64);
+    let engine = &life.engine;
+    engine.population(0, 0, width, strip) > 0
+        || engine.population(0, bottom, width, strip) > 0
+        || engine.population(0, 0, strip, height) > 0
+        || engine.population(right, 0, strip, height) > 0
+}
+
+/// Whether there are ants of a turmite still walking, which go on painting cells whatever
+/// colors the ones they painted are now
+fn ants_left(life: &Life) -> bool {
+    let Rule::Ant(turmite) = life.rules.first() else {
+        return false;
+    };
+    let cells = life.engine.cell_states();
+    cells
+        .iter()
+        .any(|&(_, state)| turmite.heading(state).is_some())
+}
@@ -0,0 +1,110 @@
+//! What is printed about the run once it stopped
+
+use std::{fmt::Write as _, io::Write, time::Duration};
+
+use clap::ValueEnum;
+
+use game_of_life::{census::census, Life};
+
+use crate::{stop::Stop, Config};
+
+/// How the summary at the end is printed
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SummaryFormat {
+    /// How the simulation stopped with its live cells, then the births, deaths, the peak of
+    /// the live cells and the time taken
+    Text,
+    /// An object of the same, such as `{"end":"limit","generations":100,"seconds":0.012,
+    /// "rate":8333.3,"population":40,"peak":52,"peak_generation":12,"births":830,
+    /// "deaths":790}`, where the end is quit, died-out, stable, cycle, limit,
+    /// population-below, population-above or escaped
+    Json,
+}
+
+/// How the --census is printed
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum CensusFormat {
+    /// A line for each object found, with its name and count
+    Table,
+    /// An object of the counts by name, such as `{"block":2,"blinker":1,"unidentified":0}`
+    Json,
+}
+
+/// Prints how the run of `life` stopped, which took `elapsed` from generation `from`, in the
+/// --summary format, followed by the --census if there is one
+pub fn print(
+    out: &mut dyn Write,
+    life: &Life,
+    stop: Stop,
+    config: &Config,
+    from: u64,
+    elapsed: Duration,
+) {
+    let generations = life.generation();
+    let seconds = elapsed.as_secs_f64();
+    // Not counting the generations of a --load-state, which were computed by an earlier run
+    let rate = generations.saturating_sub(from) as f64 / seconds;
+    let (peak, peak_generation) = life.peak();
+    let mut summary = String::new();
+    match config.summary {
+        SummaryFormat::Text => {
+            let cells = |n: u64| if n == 1 { "cell" } else { "cells" };
+            summary = stop.describe(life);
+            let population = life.population();
+            let _ = write!(summary, ", {population} live {}", cells(population));
+            if let Some(immigration) = &life.immigration {
+                let (first, second) = immigration.populations();
+                let _ = write!(summary, " ({first} red, {second} blue)");
+            }
+            let _ = write!(
+                summary,
+                "\n{} births and {} deaths, at most {peak} live {} at generation \
+                 {peak_generation}",
+                life.births(),
+                life.deaths(),
+                cells(peak)
+            );
+            if life.total_poked() > 0 {
+                let _ = write!(summary, ", {} poked to life", life.total_poked());
+            }
+            // Without waiting for the frames, the time taken is what computing the
+            // generations took
+            let shown = if config.headless { "" } else { "shown " };
+            let _ = write!(
+                summary,
+                ", {seconds:.3}s at {rate:.0} generations {shown}per second"
+            );
+        }
+        SummaryFormat::Json => {
+            let _ = write!(
+                summary,
+                "{{\"end\":\"{}\",\"generations\":{generations},\"seconds\":{seconds:.3},\
+                 \"rate\":{rate:.1},\"population\":{},\"peak\":{peak},\
+                 \"peak_generation\":{peak_generation},\"births\":{},\"deaths\":{},\
+                 \"poked\":{}}}",
+                stop.name(),
+                life.population(),
+                life.births(),
+                life.deaths(),
+                life.total_poked()
+            );
+        }
+    }
+    let _ = writeln!(out, "{summary}");
+    if let Some(format) = config.census {
+        let census = census(life);
+        let _ = match format {
+            CensusFormat::Table => census
+                .found()
+                .chain([("unidentified", census.unidentified)])
+                .try_for_each(|(name, count)| writeln!(out, "{name:<12} {count}")),
+            CensusFormat::Json => {
+                let mut json = String::from("{");
+                for (name, count) in census.found() {
+                    let _ = write!(json, "\"{name}\":{count},");
+                }
+                writeln!(out, "{json}\"unidentified\":{}}}", census.unidentified)
+            }
+        };
+    }
+}
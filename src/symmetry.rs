@@ -0,0 +1,75 @@
+//! Symmetries enforced on random soups, named like in apgsearch
+
+use clap::ValueEnum;
+
+use crate::{cell::CellState, Field};
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum Symmetry {
+    /// Plain noise
+    #[default]
+    None,
+    /// Unchanged by a half turn
+    C2,
+    /// Unchanged by a quarter turn, square fields only
+    C4,
+    /// Mirrored left to right
+    D2,
+    /// Mirrored left to right and top to bottom
+    D4,
+    /// Unchanged by quarter turns and mirroring, square fields only
+    D8,
+}
+
+impl Symmetry {
+    /// Makes every cell take the state of the first cell in row-major order that the
+    /// symmetry maps it to, so each orbit gets exactly one random draw, cells on an axis
+    /// included
+    pub fn apply<C: CellState>(self, field: &mut Field<C>) -> Result<(), String> {
+        let (width, height) = (field.width, field.height);
+        if matches!(self, Self::C4 | Self::D8) && width != height {
+            return Err(format!(
+                "--symmetry {} needs a square field, not {width}x{height}",
+                self.to_possible_value().unwrap().get_name()
+            ));
+        }
+
+        let (w, h) = (width - 1, height - 1);
+        for y in 0..height {
+            for x in 0..width {
+                // Ordered so that each symmetry uses a contiguous run of them. Quarter turns
+                // and diagonal mirrors only make sense on square fields.
+                let (turned, transposed) = if width == height {
+                    ([(w - y, x), (y, h - x)], [(y, x), (w - y, h - x)])
+                } else {
+                    ([(x, y); 2], [(x, y); 2])
+                };
+                let images = [
+                    (x, h - y),
+                    (w - x, y),
+                    (w - x, h - y),
+                    turned[0],
+                    turned[1],
+                    transposed[0],
+                    transposed[1],
+                ];
+                let images = match self {
+                    Self::None => &images[..0],
+                    Self::C2 => &images[2..3],
+                    Self::C4 => &images[2..5],
+                    Self::D2 => &images[1..2],
+                    Self::D4 => &images[..3],
+                    Self::D8 => &images[..],
+                };
+                // Computed before this cell was, so it still holds its own random state
+                let &(rx, ry) = images
+                    .iter()
+                    .filter(|&&(ix, iy)| (iy, ix) < (y, x))
+                    .min_by_key(|&&(ix, iy)| (iy, ix))
+                    .unwrap_or(&(x, y));
+                field.cells[y * width + x] = field.cells[ry * width + rx];
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,563 @@
+//! Whole runs recorded a generation after another into a file, to play back later
+//!
+//! A tape starts with `LIFH`, the version of the format, the states of the cells and the
+//! rules they ran under. Each generation follows as a frame of its own: a `K` or a `D`, the
+//! generation, the width and height of the field and the length of the cells, which are
+//! packed into as few bits each as their states need. A key frame, the `K` that every few
+//! frames and those of a field of another size are, holds the cells as they are, and the
+//! frames in between only how they differ from the frame before, as the bits that flipped.
+//! Either way, runs of zero bytes are shortened to a zero and their length. Numbers are
+//! LEB128 varints. Once the recording is done, an index of the key frames follows, and last
+//! of all its offset as 8 little-endian bytes and `LIFH` again, so that playback can seek
+//! without going through all of them. A tape cut short without an index is indexed by going
+//! through the frames as far as they are whole.
+
+use std::{
+    fs::File,
+    io::{self, BufReader, BufWriter, ErrorKind, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use crate::{rule::Rules, Field, Life, LoadError};
+
+const MAGIC: &[u8; 4] = b"LIFH";
+
+/// Bumped whenever the format changes in a way older versions can't read
+const VERSION: u8 = 1;
+
+const KEY: u8 = b'K';
+const DELTA: u8 = b'D';
+const INDEX: u8 = b'I';
+
+/// Frames up to the next key frame, the ones the index has, which is all of them that is kept
+/// in memory
+const KEY_EVERY: u64 = 256;
+
+/// The recording is written out at least this often, to lose little if it is cut short
+const FLUSH_EVERY: Duration = Duration::from_secs(1);
+
+/// Where in a tape a key frame starts, and its generation
+#[derive(Clone, Copy)]
+struct Entry {
+    generation: u64,
+    offset: u64,
+}
+
+/// Writes the generations of a run to a tape as they come
+pub struct Recorder {
+    out: BufWriter<File>,
+    path: PathBuf,
+    /// Bits per cell
+    bits: u32,
+    /// Where the next frame goes
+    offset: u64,
+    /// Frames since the last key frame
+    frames: u64,
+    index: Vec<Entry>,
+    /// The generation of the last frame, which isn't written again while paused
+    last: Option<u64>,
+    /// The size of the field of the last frame and its cells packed, which the next one is
+    /// told apart from
+    size: (usize, usize),
+    previous: Vec<u8>,
+    flushed: Instant,
+    /// Buffers for the cells of a frame, packed, made the bits that flipped and shortened
+    packed: Vec<u8>,
+    delta: Vec<u8>,
+    payload: Vec<u8>,
+}
+
+impl Recorder {
+    /// Starts a tape at `path` of a run under `rules`, replacing any file there
+    pub fn create(path: &Path, rules: &Rules) -> Result<Self, String> {
+        let file = File::create(path)
+            .map_err(|err| format!("failed to create {}: {err}", path.display()))?;
+        let mut header = MAGIC.to_vec();
+        header.push(VERSION);
+        header.push(rules.states());
+        let name = rules.to_string();
+        write_varint(&mut header, name.len() as u64);
+        header.extend_from_slice(name.as_bytes());
+        let mut recorder = Self {
+            out: BufWriter::new(file),
+            path: path.to_path_buf(),
+            bits: bits(rules.states()),
+            offset: header.len() as u64,
+            frames: 0,
+            index: Vec::new(),
+            last: None,
+            size: (0, 0),
+            previous: Vec::new(),
+            flushed: Instant::now(),
+            packed: Vec::new(),
+            delta: Vec::new(),
+            payload: Vec::new(),
+        };
+        recorder.write(&header)?;
+        Ok(recorder)
+    }
+
+    /// Appends the generation `life` is at, unless it is the one of the last frame
+    pub fn record(&mut self, life: &Life) -> Result<(), String> {
+        if self.last == Some(life.generation()) {
+            return Ok(());
+        }
+        self.last = Some(life.generation());
+
+        let field = life.snapshot();
+        pack(&field.cells, self.bits, &mut self.packed);
+        let key = self.frames >= KEY_EVERY || self.size != (field.width, field.height);
+        self.payload.clear();
+        if key {
+            self.index.push(Entry {
+                generation: life.generation(),
+                offset: self.offset,
+            });
+            self.frames = 0;
+            shorten(&self.packed, &mut self.payload);
+        } else {
+            self.delta.clear();
+            self.delta.extend(
+                self.packed
+                    .iter()
+                    .zip(&self.previous)
+                    .map(|(byte, previous)| byte ^ previous),
+            );
+            shorten(&self.delta, &mut self.payload);
+        }
+        self.frames += 1;
+        self.size = (field.width, field.height);
+        std::mem::swap(&mut self.previous, &mut self.packed);
+
+        let mut frame = vec![if key { KEY } else { DELTA }];
+        write_varint(&mut frame, life.generation());
+        write_varint(&mut frame, field.width as u64);
+        write_varint(&mut frame, field.height as u64);
+        write_varint(&mut frame, self.payload.len() as u64);
+        frame.extend_from_slice(&self.payload);
+        self.write(&frame)?;
+
+        if self.flushed.elapsed() >= FLUSH_EVERY {
+            self.flushed = Instant::now();
+            self.out.flush().map_err(|err| self.error(err))?;
+        }
+        Ok(())
+    }
+
+    /// Writes the index after the frames, and everything out
+    pub fn finish(mut self) -> Result<(), String> {
+        let start = self.offset;
+        let mut index = vec![INDEX];
+        write_varint(&mut index, self.index.len() as u64);
+        for entry in &self.index {
+            write_varint(&mut index, entry.generation);
+            write_varint(&mut index, entry.offset);
+        }
+        index.extend_from_slice(&start.to_le_bytes());
+        index.extend_from_slice(MAGIC);
+        self.write(&index)?;
+        self.out.flush().map_err(|err| self.error(err))
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> Result<(), String> {
+        self.out.write_all(bytes).map_err(|err| self.error(err))?;
+        self.offset += bytes.len() as u64;
+        Ok(())
+    }
+
+    fn error(&self, err: io::Error) -> String {
+        format!("failed to write {}: {err}", self.path.display())
+    }
+}
+
+/// What comes before the cells of a frame
+struct Header {
+    key: bool,
+    generation: u64,
+    width: usize,
+    height: usize,
+    /// Bytes of the cells
+    len: u64,
+}
+
+/// A generation read back from a tape
+pub struct Frame {
+    pub generation: u64,
+    pub field: Field,
+}
+
+/// Reads the frames of a tape back one after another, or from a chosen generation on
+pub struct Player {
+    input: BufReader<File>,
+    path: PathBuf,
+    pub rules: Rules,
+    bits: u32,
+    /// Where the first frame starts and the last one ends
+    start: u64,
+    end: u64,
+    index: Vec<Entry>,
+    /// Where the next frame starts
+    offset: u64,
+    /// The cells of the last frame read packed, which those of the next one are the changes
+    /// to unless it is a key frame
+    previous: Option<Vec<u8>>,
+}
+
+impl Player {
+    pub fn open(path: &Path) -> Result<Self, LoadError> {
+        let read_error =
+            |err: io::Error| LoadError::Read(format!("failed to read {}: {err}", path.display()));
+        let parse_error =
+            |message: &str| LoadError::Parse(format!("{}: {message}", path.display()));
+        let file = File::open(path).map_err(read_error)?;
+        let len = file.metadata().map_err(read_error)?.len();
+        let mut input = BufReader::new(file);
+
+        let mut magic = [0; 6];
+        input
+            .read_exact(&mut magic)
+            .map_err(|err| match err.kind() {
+                ErrorKind::UnexpectedEof => parse_error("not a tape"),
+                _ => read_error(err),
+            })?;
+        if &magic[..4] != MAGIC {
+            return Err(parse_error("not a tape"));
+        }
+        if magic[4] != VERSION {
+            return Err(parse_error(&format!(
+                "version {} of the format isn't supported, only {VERSION}",
+                magic[4]
+            )));
+        }
+        let states = magic[5];
+        let rules = read_varint(&mut input)
+            .ok()
+            .flatten()
+            .and_then(|len| {
+                let mut rules = vec![0; usize::try_from(len).ok()?.min(1 << 16)];
+                input.read_exact(&mut rules).ok()?;
+                String::from_utf8(rules).ok()
+            })
+            .ok_or_else(|| parse_error("the rules are cut short"))?;
+        let rules: Rules = rules
+            .parse()
+            .map_err(|err: String| parse_error(&format!("the rules: {err}")))?;
+        if rules.states() != states {
+            return Err(parse_error("the rules don't have the states of the cells"));
+        }
+        let start = input.stream_position().map_err(read_error)?;
+
+        let mut player = Self {
+            input,
+            path: path.to_path_buf(),
+            rules,
+            bits: bits(states),
+            start,
+            end: len,
+            index: Vec::new(),
+            offset: start,
+            previous: None,
+        };
+        if !player.read_index(len).map_err(read_error)? {
+            player.scan().map_err(read_error)?;
+        }
+        player.rewind().map_err(read_error)?;
+        Ok(player)
+    }
+
+    /// The next frame, or none past the last one or one that was cut short
+    pub fn next_frame(&mut self) -> Result<Option<Frame>, LoadError> {
+        let Some(Header {
+            generation,
+            width,
+            height,
+            ..
+        }) = self.advance()?
+        else {
+            return Ok(None);
+        };
+        let mut field = Field::new(width, height);
+        unpack(
+            self.previous.as_deref().unwrap(),
+            self.bits,
+            &mut field.cells,
+        );
+        Ok(Some(Frame { generation, field }))
+    }
+
+    /// Reads the next frame into the cells of the last one, giving back its header
+    fn advance(&mut self) -> Result<Option<Header>, LoadError> {
+        let (header, payload) = match self.read_frame() {
+            Ok(Some(frame)) => frame,
+            Ok(None) => return Ok(None),
+            Err(err) if err.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+            Err(err) => return Err(self.read_error(err)),
+        };
+        self.offset = self.input.stream_position().unwrap_or(self.end);
+        let len = (header.width * header.height * self.bits as usize).div_ceil(8);
+        let mut packed = Vec::with_capacity(len);
+        let whole = expand(&payload, &mut packed) && packed.len() == len;
+        let previous = match (header.key, self.previous.take()) {
+            (true, _) => Some(packed),
+            (false, Some(mut previous)) if whole && previous.len() == len => {
+                for (byte, flipped) in previous.iter_mut().zip(packed) {
+                    *byte ^= flipped;
+                }
+                Some(previous)
+            }
+            _ => None,
+        };
+        match previous {
+            Some(previous) if whole => {
+                self.previous = Some(previous);
+                Ok(Some(header))
+            }
+            _ => Err(LoadError::Parse(format!(
+                "{}: the cells of generation {} don't fit its field",
+                self.path.display(),
+                header.generation
+            ))),
+        }
+    }
+
+    /// Goes to the first frame of `generation` or after it, from the key frame of the index
+    /// at the last generation before it
+    pub fn seek(&mut self, generation: u64) -> Result<(), LoadError> {
+        let from = self
+            .index
+            .iter()
+            .rev()
+            .find(|entry| entry.generation <= generation)
+            .map_or(self.start, |entry| entry.offset);
+        self.jump(from).map_err(|err| self.read_error(err))?;
+        self.previous = None;
+        loop {
+            let offset = self.offset;
+            let header = match self.read_frame_header() {
+                Ok(header) => header,
+                Err(err) if err.kind() == ErrorKind::UnexpectedEof => None,
+                Err(err) => return Err(self.read_error(err)),
+            };
+            self.jump(offset).map_err(|err| self.read_error(err))?;
+            match header {
+                Some(header) if header.generation < generation => {
+                    if self.advance()?.is_none() {
+                        return Ok(());
+                    }
+                }
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    /// Goes back to the first frame
+    pub fn rewind(&mut self) -> io::Result<()> {
+        self.previous = None;
+        self.jump(self.start)
+    }
+
+    fn jump(&mut self, offset: u64) -> io::Result<()> {
+        self.input.seek(SeekFrom::Start(offset))?;
+        self.offset = offset;
+        Ok(())
+    }
+
+    /// Skips the cells of a frame whose header was just read
+    fn skip(&mut self, len: u64) -> io::Result<()> {
+        self.input.seek_relative(len as i64)?;
+        self.offset = self.input.stream_position()?;
+        Ok(())
+    }
+
+    fn read_frame(&mut self) -> io::Result<Option<(Header, Vec<u8>)>> {
+        let Some(header) = self.read_frame_header()? else {
+            return Ok(None);
+        };
+        let mut payload = vec![0; header.len as usize];
+        self.input.read_exact(&mut payload)?;
+        Ok(Some((header, payload)))
+    }
+
+    fn read_error(&self, err: io::Error) -> LoadError {
+        LoadError::Read(format!("failed to read {}: {err}", self.path.display()))
+    }
+
+    /// The header of the next frame, if there is a frame before the end
+    fn read_frame_header(&mut self) -> io::Result<Option<Header>> {
+        if self.offset >= self.end {
+            return Ok(None);
+        }
+        let mut tag = [0];
+        self.input.read_exact(&mut tag)?;
+        if tag[0] != KEY && tag[0] != DELTA {
+            return Ok(None);
+        }
+        let mut number = || {
+            read_varint(&mut self.input)?.ok_or_else(|| io::Error::from(ErrorKind::UnexpectedEof))
+        };
+        let (generation, width, height, len) = (number()?, number()?, number()?, number()?);
+        let size = |n: u64| usize::try_from(n).map_err(|_| io::Error::from(ErrorKind::InvalidData));
+        let (width, height) = (size(width)?, size(height)?);
+        // The cells can't take up more of the file than there is
+        if len > self.end || width.checked_mul(height).is_none() {
+            return Err(io::Error::from(ErrorKind::InvalidData));
+        }
+        Ok(Some(Header {
+            key: tag[0] == KEY,
+            generation,
+            width,
+            height,
+            len,
+        }))
+    }
+
+    /// Reads the index at the end of the tape, if it was finished
+    fn read_index(&mut self, len: u64) -> io::Result<bool> {
+        if len < self.start + 12 {
+            return Ok(false);
+        }
+        self.input.seek(SeekFrom::Start(len - 12))?;
+        let mut trailer = [0; 12];
+        self.input.read_exact(&mut trailer)?;
+        let offset = u64::from_le_bytes(trailer[..8].try_into().unwrap());
+        if &trailer[8..] != MAGIC || offset < self.start || offset >= len - 12 {
+            return Ok(false);
+        }
+        self.input.seek(SeekFrom::Start(offset))?;
+        let mut tag = [0];
+        self.input.read_exact(&mut tag)?;
+        if tag[0] != INDEX {
+            return Ok(false);
+        }
+        let Some(count) = read_varint(&mut self.input)? else {
+            return Ok(false);
+        };
+        let mut index = Vec::new();
+        for _ in 0..count {
+            let (Some(generation), Some(offset)) =
+                (read_varint(&mut self.input)?, read_varint(&mut self.input)?)
+            else {
+                return Ok(false);
+            };
+            index.push(Entry { generation, offset });
+        }
+        (self.index, self.end) = (index, offset);
+        Ok(true)
+    }
+
+    /// Goes through the frames without reading their cells, indexing the key frames and
+    /// ending the tape after the last frame that is whole
+    fn scan(&mut self) -> io::Result<()> {
+        self.jump(self.start)?;
+        loop {
+            let offset = self.offset;
+            let header = match self.read_frame_header() {
+                Ok(header) => header,
+                Err(err) if err.kind() == ErrorKind::UnexpectedEof => None,
+                Err(err) => return Err(err),
+            };
+            let cells = self.input.stream_position()?;
+            let Some(header) = header.filter(|header| cells + header.len <= self.end) else {
+                self.end = offset;
+                return Ok(());
+            };
+            if header.key {
+                self.index.push(Entry {
+                    generation: header.generation,
+                    offset,
+                });
+            }
+            self.skip(header.len)?;
+        }
+    }
+}
+
+/// Bits per cell of `states` states
+fn bits(states: u8) -> u32 {
+    (u8::BITS - states.saturating_sub(1).leading_zeros()).max(1)
+}
+
+/// Packs the states of cells into `bits` bits each, the first cell in the lowest bits
+fn pack(cells: &[u8], bits: u32, out: &mut Vec<u8>) {
+    out.clear();
+    out.resize((cells.len() * bits as usize).div_ceil(8), 0);
+    for (i, &state) in cells.iter().enumerate() {
+        let bit = i * bits as usize;
+        // With bits per cell that divide 8, no cell straddles two bytes
+        out[bit / 8] |= state << (bit % 8);
+        if bit % 8 + bits as usize > 8 {
+            out[bit / 8 + 1] |= state >> (8 - bit % 8);
+        }
+    }
+}
+
+/// Unpacks the states of as many cells as `packed` holds of `bits` bits each
+fn unpack(packed: &[u8], bits: u32, cells: &mut [u8]) {
+    let mask = ((1u16 << bits) - 1) as u8;
+    for (i, cell) in cells.iter_mut().enumerate() {
+        let bit = i * bits as usize;
+        let mut state = u16::from(packed[bit / 8]) >> (bit % 8);
+        if bit % 8 + bits as usize > 8 {
+            state |= u16::from(packed[bit / 8 + 1]) << (8 - bit % 8);
+        }
+        *cell = state as u8 & mask;
+    }
+}
+
+/// Shortens the runs of zero bytes, which most of a sparse field packs into, to a zero
+/// followed by the length of the run less one
+fn shorten(packed: &[u8], out: &mut Vec<u8>) {
+    let mut bytes = packed.iter().peekable();
+    while let Some(&byte) = bytes.next() {
+        out.push(byte);
+        if byte == 0 {
+            let mut run = 0u8;
+            while run < u8::MAX && bytes.next_if_eq(&&0).is_some() {
+                run += 1;
+            }
+            out.push(run);
+        }
+    }
+}
+
+/// Undoes `shorten`, unless the payload ends in the middle of a run
+fn expand(payload: &[u8], out: &mut Vec<u8>) -> bool {
+    let mut bytes = payload.iter();
+    while let Some(&byte) = bytes.next() {
+        match byte {
+            0 => match bytes.next() {
+                Some(&run) => out.extend(std::iter::repeat_n(0, run as usize + 1)),
+                None => return false,
+            },
+            _ => out.push(byte),
+        }
+    }
+    true
+}
+
+fn write_varint(out: &mut Vec<u8>, mut n: u64) {
+    while n >= 0x80 {
+        out.push(n as u8 | 0x80);
+        n >>= 7;
+    }
+    out.push(n as u8);
+}
+
+/// A varint, or none if the input ends at once
+fn read_varint(input: &mut impl Read) -> io::Result<Option<u64>> {
+    let mut n = 0u64;
+    for shift in (0..64).step_by(7) {
+        let mut byte = [0];
+        match input.read_exact(&mut byte) {
+            Ok(()) => {}
+            Err(err) if err.kind() == ErrorKind::UnexpectedEof && shift == 0 => return Ok(None),
+            Err(err) => return Err(err),
+        }
+        n |= u64::from(byte[0] & 0x7f) << shift;
+        if byte[0] & 0x80 == 0 {
+            return Ok(Some(n));
+        }
+    }
+    Err(io::Error::from(ErrorKind::InvalidData))
+}
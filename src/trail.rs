@@ -0,0 +1,93 @@
+//! Trails of the cells that died lately, fading out over a few generations
+
+use std::{
+    collections::{HashMap, HashSet},
+    mem,
+};
+
+use crate::engine::Engine;
+
+/// Glyphs of the cells along a trail, from those that just died to the ones about to fade out
+pub const GLYPHS: [char; 3] = ['•', '·', '˙'];
+
+/// Generations left of the trail of each cell that died lately. The engines know nothing of
+/// them, so that they don't count as neighbors.
+pub struct Trails {
+    /// Generations the trail of a cell lasts for
+    length: u8,
+    fading: HashMap<(i64, i64), u8>,
+    /// The live cells of the generation before, to tell which ones died since
+    alive: HashSet<(i64, i64)>,
+}
+
+impl Trails {
+    pub fn new(length: u8, engine: &dyn Engine) -> Self {
+        let mut trails = Self {
+            length,
+            fading: HashMap::new(),
+            alive: HashSet::new(),
+        };
+        trails.reset(engine);
+        trails
+    }
+
+    /// Takes the trails as they were, for picking up a simulation that was left off
+    pub(crate) fn restore(
+        length: u8,
+        fading: impl IntoIterator<Item = ((i64, i64), u8)>,
+        engine: &dyn Engine,
+    ) -> Self {
+        Self {
+            length,
+            fading: fading.into_iter().collect(),
+            alive: engine.live_cells().into_iter().collect(),
+        }
+    }
+
+    pub(crate) fn length(&self) -> u8 {
+        self.length
+    }
+
+    /// Moves the trails along with the cells, which moved by `dx` and `dy`
+    pub(crate) fn shift(&mut self, dx: i64, dy: i64) {
+        let fading = mem::take(&mut self.fading).into_iter();
+        self.fading = fading
+            .map(|((x, y), left)| ((x + dx, y + dy), left))
+            .collect();
+        let alive = mem::take(&mut self.alive).into_iter();
+        self.alive = alive.map(|(x, y)| (x + dx, y + dy)).collect();
+    }
+
+    /// Generations left of each trail
+    pub(crate) fn iter(&self) -> impl Iterator<Item = ((i64, i64), u8)> + '_ {
+        self.fading.iter().map(|(&cell, &left)| (cell, left))
+    }
+
+    /// Wipes out the trails, starting over from the cells alive in the engine
+    pub fn reset(&mut self, engine: &dyn Engine) {
+        self.fading.clear();
+        self.alive.clear();
+        self.alive.extend(engine.live_cells());
+    }
+
+    /// Fades the trails by a generation, and starts new ones for the cells that died in the
+    /// generation the engine is at
+    pub fn update(&mut self, engine: &dyn Engine) {
+        let alive: HashSet<_> = engine.live_cells().into_iter().collect();
+        self.fading.retain(|cell, left| {
+            *left -= 1;
+            *left > 0 && !alive.contains(cell)
+        });
+        for &cell in self.alive.difference(&alive) {
+            self.fading.insert(cell, self.length);
+        }
+        self.alive = alive;
+    }
+
+    /// The glyph of the trail at a cell, if there is one
+    pub fn glyph(&self, x: i64, y: i64) -> Option<char> {
+        let left = *self.fading.get(&(x, y))?;
+        let faded = (self.length - left) as usize;
+        Some(GLYPHS[faded * GLYPHS.len() / self.length as usize])
+    }
+}
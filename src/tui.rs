@@ -0,0 +1,1004 @@
+//! The screen of the terminal the generations are shown on, along with the keys and the
+//! mouse to edit them with
+
+use std::{
+    collections::VecDeque,
+    fmt::Write as _,
+    fs,
+    io::{self, stdout, BufWriter, StdoutLock, Write},
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use crossterm::terminal;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use game_of_life::{
+    census::census, formats::Format, heat::Heat, library, pattern::Pattern, rule::DEAD,
+    session::Event, state, Life, Rect, Rule,
+};
+
+use crate::{
+    charset::Glyphs,
+    color::{Palette, Theme},
+    controls::{self, Command, Mode},
+    graphics,
+    outputs::{output, replay, Outputs},
+    pace::{Pace, SPEEDS},
+    render::{self, CellStyle, Resize, Screen, TerminalGuard, View},
+    setup::reseed,
+    soups,
+    sparkline::Sparkline,
+    stamp::Stamp,
+    stop::{self, Stop},
+    Config,
+};
+
+/// Shows the generations one after another on a screen of their own, until quitting or the
+/// field has nothing more to show
+pub fn run(
+    life: &mut Life,
+    config: &Config,
+    glyphs: &Glyphs,
+    outputs: &mut Outputs,
+) -> Result<Stop, String> {
+    let _guard = TerminalGuard::enter().map_err(terminal_error)?;
+    let mut tui = Tui::new(life, config, glyphs);
+    if let Some(stats) = &mut outputs.stats {
+        stats.record(life)?;
+    }
+    if let Some(tape) = &mut outputs.tape {
+        tape.record(life)?;
+    }
+    if tui.started {
+        outputs.snapshots.record(life)?;
+    }
+    let stop = loop {
+        tui.draw(life, outputs)?;
+        replay(life, config, outputs, Some(&mut tui.pace))?;
+        let running = tui.mode == Mode::Running;
+        if let Some(stop) = stop::check(life, config, outputs.replay.as_ref(), running) {
+            match tui.settled(life, outputs, stop)? {
+                Some(stop) => break stop,
+                None => continue,
+            }
+        }
+        let Some(steps) = tui.wait(life, outputs)? else {
+            break Stop::Quit;
+        };
+        tui.advance(life, outputs, steps)?;
+    };
+    // Unless quitting the editor before the simulation started
+    if let (Some((path, format)), true) = (output(config), tui.started) {
+        life.save(path, format)?;
+    }
+    Ok(stop)
+}
+
+fn terminal_error(err: io::Error) -> String {
+    format!("failed to draw on the terminal: {err}")
+}
+
+/// As wide as the terminal, except for the last column that lines are kept off
+fn sparkline_width(columns: u16) -> usize {
+    columns.saturating_sub(1) as usize
+}
+
+/// What a key leads to
+enum Flow {
+    /// Nothing that is shown changed, so the next key is waited for
+    Wait,
+    /// The frame is drawn again, after computing this many generations
+    Draw(u64),
+    Quit,
+}
+
+/// The screen along with what the keys pressed so far made of it
+struct Tui<'a> {
+    config: &'a Config,
+    glyphs: &'a Glyphs,
+    screen: Screen,
+    stdout: BufWriter<StdoutLock<'static>>,
+    pace: Pace,
+    /// When the frames of the last second while running were drawn, to measure the frame rate
+    frames: VecDeque<Instant>,
+    sparkline: Option<Sparkline>,
+    mode: Mode,
+    /// Only saved once drawing by hand is done
+    started: bool,
+    /// On the screen rather than the field, so that it stays in view when panning
+    cursor: (u16, u16),
+    /// The stamp being aimed and the mode to return to once it is placed
+    stamp: Option<(Stamp, Mode)>,
+    /// The corner a rectangle is being selected from and the mode to return to once it is done
+    selection: Option<((i64, i64), Mode)>,
+    /// The cells copied last, for as long as the run goes on
+    clipboard: Option<Pattern>,
+    preview: Vec<(u16, u16)>,
+    /// Shown below the field instead of the help of the mode until the next key press, or
+    /// while running, for a while
+    notice: Option<String>,
+    /// When the notice was first shown
+    noticed: Option<Instant>,
+    status: String,
+    /// Which of the --rules the field runs under
+    switched: isize,
+    /// The cells poked are the same every time for the same --seed
+    pokes: StdRng,
+}
+
+impl<'a> Tui<'a> {
+    fn new(life: &Life, config: &'a Config, glyphs: &'a Glyphs) -> Self {
+        let mut screen = Screen::new(
+            config.full_redraw,
+            config.viewport,
+            config.zoom,
+            config.follow,
+            config.renderer,
+            glyphs.clone(),
+            palette(life, config),
+        );
+        screen.set_view(config.view);
+        screen.set_style(config.style);
+        screen.set_status_lines(config.status_lines());
+        let theme = config.color.unwrap_or_default();
+        if let Some(graphics) = graphics::new(
+            config.renderer,
+            theme,
+            config.alive_color,
+            config.dead_color,
+        ) {
+            screen.set_graphics(graphics);
+        }
+        let mut sparkline = config.sparkline.then(|| {
+            let columns = terminal::size().map_or(80, |(columns, _)| columns);
+            Sparkline::new(sparkline_width(columns))
+        });
+        if let Some(sparkline) = &mut sparkline {
+            sparkline.push(life.population());
+        }
+        let mode = if config.edit {
+            Mode::Editing
+        } else if config.paused {
+            Mode::Paused
+        } else {
+            Mode::Running
+        };
+        screen.fit_viewport(life);
+        let cursor = (screen.visible().0 / 2, screen.visible().1 / 2);
+        Self {
+            config,
+            glyphs,
+            screen,
+            stdout: BufWriter::new(stdout().lock()),
+            pace: Pace::new(config),
+            frames: VecDeque::new(),
+            sparkline,
+            mode,
+            started: !config.edit,
+            cursor,
+            stamp: None,
+            selection: None,
+            clipboard: None,
+            preview: Vec::new(),
+            notice: None,
+            noticed: None,
+            status: String::new(),
+            switched: 0,
+            pokes: StdRng::seed_from_u64(config.seed.unwrap_or_else(rand::random)),
+        }
+    }
+
+    /// Draws the frame of the generation `life` is at, and writes it to the outputs that take
+    /// the frames shown
+    fn draw(&mut self, life: &mut Life, outputs: &mut Outputs) -> Result<(), String> {
+        // The origin of the viewport is only known once it has been fitted to the field
+        self.screen.fit_viewport(life);
+        self.screen.follow(life);
+        // Heat is only kept track of from the first time it is shown, also after reseeding
+        if self.screen.view() == View::Heatmap && life.heat.is_none() {
+            life.heat = Some(Heat::new(&*life.engine));
+        }
+        let now = Instant::now();
+        if self.mode == Mode::Running {
+            self.frames.push_back(now);
+        } else {
+            self.frames.clear();
+        }
+        while self
+            .frames
+            .front()
+            .is_some_and(|&frame| now - frame > Duration::from_secs(1))
+        {
+            self.frames.pop_front();
+        }
+        self.write_status(life, now);
+        self.preview.clear();
+        if let Some((stamp, _)) = &self.stamp {
+            let cells = stamp.cells(self.screen.on_field(self.cursor));
+            let screen = &self.screen;
+            self.preview
+                .extend(cells.filter_map(|cell| screen.on_screen(cell)));
+        }
+        self.screen.set_selection(self.selected());
+        let cell_cursor = matches!(self.mode, Mode::Editing | Mode::Stamping | Mode::Selecting)
+            .then_some(self.cursor);
+        let frame = self
+            .screen
+            .draw(life, &self.status, cell_cursor, &self.preview)
+            .map_err(terminal_error)?;
+        self.stdout
+            .write_all(frame)
+            .and_then(|()| self.stdout.flush())
+            .map_err(terminal_error)?;
+        if let Some(recording) = &mut outputs.recording {
+            recording.record(life)?;
+        }
+        if let Some(frames) = &mut outputs.frames {
+            frames.record(life)?;
+        }
+        if let Some(server) = &mut outputs.server {
+            server.record(life);
+        }
+        Ok(())
+    }
+
+    /// The lines below the field: the status line, or the notice in its place, and the
+    /// sparkline
+    fn write_status(&mut self, life: &Life, now: Instant) {
+        self.status.clear();
+        let status = &mut self.status;
+        let _ = write!(status, "gen {} ", life.generation());
+        if let Some(cycle) = life.cycle() {
+            let _ = write!(status, "(period {} from {}) ", cycle.period, cycle.start);
+        }
+        let _ = write!(status, "pop {} ", life.population());
+        if let Some(immigration) = &life.immigration {
+            let (first, second) = immigration.populations();
+            let _ = write!(status, "(red {first} blue {second}) ");
+        }
+        if self.config.rules.is_some() {
+            let _ = write!(status, "{} ", life.rules);
+        }
+        let target = match self.pace.fps {
+            0.0 => "max".to_string(),
+            // Speeds stepped to are shown to a tenth
+            fps => ((fps * 10.0).round() / 10.0).to_string(),
+        };
+        let _ = match (self.frames.front(), self.frames.len()) {
+            (Some(&first), 2..) => {
+                let fps = (self.frames.len() - 1) as f64 / (now - first).as_secs_f64();
+                write!(status, "{fps:.1}/{target} fps ")
+            }
+            _ => write!(status, "-/{target} fps "),
+        };
+        let _ = write!(status, "{}x{} ", life.width, life.height);
+        let (x, y) = self.screen.origin();
+        let _ = write!(status, "[{x},{y}");
+        let _ = match self.screen.zoom() {
+            1 => Ok(()),
+            zoom => write!(status, " 1:{zoom}"),
+        };
+        if self.screen.following() {
+            status.push_str(" following");
+        }
+        if self.screen.view() == View::Heatmap {
+            status.push_str(" heatmap");
+        }
+        status.push(']');
+        let rect = self.selected();
+        let status = &mut self.status;
+        let _ = match (self.notice.as_deref(), self.mode, &self.stamp) {
+            (Some(notice), ..) => {
+                self.noticed.get_or_insert_with(Instant::now);
+                status.clear();
+                write!(status, "{notice}")
+            }
+            (None, Mode::Editing, _) => write!(
+                status,
+                " editing: arrows or hjkl to move, space to flip, g or 1-9 to stamp, s to save, \
+                 Enter to start, ? for all the keys"
+            ),
+            (None, Mode::Paused, _) => write!(
+                status,
+                " paused: space to resume, n to step, left and right to rewind, hjkl to pan, g \
+                 or 1-9 to stamp, q to quit, ? for all the keys"
+            ),
+            (None, Mode::Selecting, _) => write!(
+                status,
+                " selecting {}x{}: arrows, hjkl or mouse to move the corner, y to copy, d to \
+                 clear, Esc to cancel",
+                rect.map_or(0, Rect::width),
+                rect.map_or(0, Rect::height)
+            ),
+            (None, Mode::Stamping, Some((stamp, _))) => write!(
+                status,
+                " stamping {}: arrows or mouse to aim, [ and ] to rotate, Enter or click to \
+                 place, Esc to cancel",
+                stamp.name
+            ),
+            (None, ..) => Ok(()),
+        };
+        if self.config.no_status {
+            status.clear();
+        }
+        if let Some(sparkline) = &self.sparkline {
+            if !self.config.no_status {
+                status.push('\n');
+            }
+            sparkline.render(status);
+        }
+    }
+
+    /// Sums up the soup of --loop that stopped by `stop` and fades it out to start over from
+    /// the next one, or without --loop, gives back the stop
+    fn settled(
+        &mut self,
+        life: &mut Life,
+        outputs: &mut Outputs,
+        stop: Stop,
+    ) -> Result<Option<Stop>, String> {
+        let Some(soups) = &mut outputs.soups else {
+            return Ok(Some(stop));
+        };
+        let summary = soups.finish(life, stop);
+        let pause = self.config.loop_pause;
+        if fade(life, &mut self.screen, &mut self.stdout, pause, &summary)
+            .map_err(terminal_error)?
+        {
+            return Ok(Some(Stop::Quit));
+        }
+        let generation = life.generation();
+        reseed(life, self.config, soups::next_seed(life))?;
+        outputs.edit(generation, || Event::Reseed(state::write(life)))?;
+        self.restarted(life);
+        if let Some(stats) = &mut outputs.stats {
+            stats.record(life)?;
+        }
+        if let Some(tape) = &mut outputs.tape {
+            tape.record(life)?;
+        }
+        outputs.snapshots.record(life)?;
+        self.pace.next_frame = Instant::now() + self.pace.frame_time();
+        Ok(None)
+    }
+
+    /// Starts the sparkline over from the new field
+    fn restarted(&mut self, life: &Life) {
+        if let Some(sparkline) = &mut self.sparkline {
+            sparkline.clear();
+            sparkline.push(life.population());
+        }
+    }
+
+    /// Waits for the next frame, or unless running, for a key that changes what is shown,
+    /// giving back the generations to compute before it, or nothing when quitting
+    fn wait(&mut self, life: &mut Life, outputs: &mut Outputs) -> Result<Option<u64>, String> {
+        loop {
+            let deadline = (self.mode == Mode::Running).then_some(self.pace.next_frame);
+            let command = controls::next_command(self.mode, deadline).map_err(terminal_error)?;
+            if command.is_some() || self.noticed.is_some_and(|at| at.elapsed() >= NOTICE_TIME) {
+                self.notice = None;
+                self.noticed = None;
+            }
+            let Some(command) = command else {
+                return Ok(Some(self.next_frame()));
+            };
+            match self.dispatch(command, life, outputs)? {
+                Flow::Wait => continue,
+                Flow::Draw(steps) => return Ok(Some(steps)),
+                Flow::Quit => return Ok(None),
+            }
+        }
+    }
+
+    /// Moves on to the frame that is due, giving back the generations to compute for it
+    fn next_frame(&mut self) -> u64 {
+        let config = self.config;
+        let pace = &mut self.pace;
+        let mut steps = config.render_every;
+        let now = Instant::now();
+        let frame_time = pace.frame_time();
+        let behind = match frame_time.as_nanos() {
+            0 => 0,
+            nanos => now.saturating_duration_since(pace.next_frame).as_nanos() / nanos,
+        };
+        if config.drop_frames {
+            // Generations of the frames there was no time to draw are computed without being
+            // shown, up to a second's worth
+            let behind = behind.min(pace.fps.ceil() as u128) as u32;
+            steps += behind as u64 * config.render_every;
+            pace.next_frame += frame_time * (behind + 1);
+        } else {
+            // Running late doesn't make up for the lost time with a burst of frames
+            pace.next_frame = (pace.next_frame + frame_time).max(now);
+        }
+        steps
+    }
+
+    /// Does what a key or the mouse asks for
+    fn dispatch(
+        &mut self,
+        command: Command,
+        life: &mut Life,
+        outputs: &mut Outputs,
+    ) -> Result<Flow, String> {
+        let config = self.config;
+        match command {
+            Command::TogglePause => {
+                self.mode = match self.mode {
+                    Mode::Running => Mode::Paused,
+                    _ => Mode::Running,
+                };
+                self.pace.next_frame = Instant::now() + self.pace.frame_time();
+            }
+            Command::Start => {
+                self.mode = Mode::Running;
+                self.started = true;
+                outputs.snapshots.record(life)?;
+                self.pace.next_frame = Instant::now() + self.pace.frame_time();
+            }
+            Command::Step => return Ok(Flow::Draw(1)),
+            Command::Speed { faster } => {
+                if self.pace.set(self.pace.stepped(faster)) {
+                    outputs.edit(life.generation(), || Event::Speed(self.pace.fps))?;
+                }
+            }
+            Command::Preset(preset) => {
+                if self.pace.set(SPEEDS[preset]) {
+                    outputs.edit(life.generation(), || Event::Speed(self.pace.fps))?;
+                }
+            }
+            Command::Travel(by) => {
+                let generation = life.generation();
+                match life.travel(by) {
+                    0 if by > 0 => return Ok(Flow::Draw(1)),
+                    0 if life.history.is_none() => {
+                        self.notice = Some("no history to go back through".to_string());
+                    }
+                    0 => return Ok(Flow::Wait),
+                    moved => outputs.edit(generation, || Event::Travel(moved))?,
+                }
+            }
+            Command::SwitchRules(by) => self.switch_rules(life, outputs, by)?,
+            Command::Reseed => {
+                let generation = life.generation();
+                reseed(life, config, rand::random())?;
+                outputs.edit(generation, || Event::Reseed(state::write(life)))?;
+                self.restarted(life);
+                if let Some(stats) = &mut outputs.stats {
+                    stats.record(life)?;
+                }
+                if let Some(tape) = &mut outputs.tape {
+                    tape.record(life)?;
+                }
+            }
+            Command::Arm(index) => {
+                let Some(name) = library::names().nth(index) else {
+                    return Ok(Flow::Wait);
+                };
+                let resume = match self.stamp.take() {
+                    Some((_, resume)) => resume,
+                    None => self.mode,
+                };
+                self.stamp = Some((Stamp::new(name), resume));
+                self.mode = Mode::Stamping;
+            }
+            Command::Rotate(turns) => {
+                self.stamp = self
+                    .stamp
+                    .take()
+                    .map(|(stamp, resume)| (stamp.rotated(turns), resume));
+            }
+            Command::Place => {
+                let (placed, resume) = self.stamp.take().expect("stamping needs a stamp");
+                place(life, outputs, &placed, self.screen.on_field(self.cursor))?;
+                self.mode = resume;
+            }
+            Command::Cancel => {
+                self.mode = match (self.selection.take(), self.stamp.take()) {
+                    (Some((_, resume)), _) | (_, Some((_, resume))) => resume,
+                    (None, None) => unreachable!("cancelling needs a stamp or a selection"),
+                };
+            }
+            Command::Select => {
+                self.selection = Some((self.screen.on_field(self.cursor), self.mode));
+                self.mode = Mode::Selecting;
+            }
+            Command::Copy => self.copy(life),
+            Command::Clear => {
+                let rect = self.selected().expect("clearing needs a selection");
+                life.erase(rect.cells());
+                outputs.edit(life.generation(), || Event::Clear(rect))?;
+                self.mode = self
+                    .selection
+                    .take()
+                    .map_or(self.mode, |(_, resume)| resume);
+            }
+            Command::Paste => match &self.clipboard {
+                Some(copied) => {
+                    self.stamp = Some((Stamp::pasted(copied.clone()), self.mode));
+                    self.mode = Mode::Stamping;
+                }
+                None => {
+                    self.notice =
+                        Some("nothing to paste: v selects a rectangle and y copies it".to_string());
+                }
+            },
+            Command::Move { dx, dy } => {
+                // Moving past the edge of the screen pans the viewport instead
+                let (width, height) = self.screen.visible();
+                let moved = (
+                    self.cursor.0.checked_add_signed(dx).filter(|&x| x < width),
+                    self.cursor.1.checked_add_signed(dy).filter(|&y| y < height),
+                );
+                match moved {
+                    (Some(x), Some(y)) => self.cursor = (x, y),
+                    _ => self.screen.pan(life, dx as i64, dy as i64),
+                }
+            }
+            Command::Pan { dx, dy } => self.screen.pan(life, dx as i64, dy as i64),
+            Command::Flip => {
+                let (x, y) = self.screen.on_field(self.cursor);
+                life.toggle(x, y);
+                outputs.edit(life.generation(), || Event::Toggle(x, y))?;
+            }
+            Command::Poke => {
+                let center =
+                    (self.mode == Mode::Editing).then(|| self.screen.on_field(self.cursor));
+                let cells = poke_cells(life, config, center, &mut self.pokes);
+                let poked = life.poke(cells.iter().copied());
+                outputs.edit(life.generation(), || Event::Poke(cells))?;
+                let cells = if poked == 1 { "cell" } else { "cells" };
+                let around = match center {
+                    Some(_) => "around the cursor",
+                    None => "all over the field",
+                };
+                self.notice = Some(format!("poked {poked} live {cells} {around}"));
+            }
+            Command::Undo => match life.undo() {
+                Some(cells) => outputs.edit(life.generation(), || Event::Set(cells))?,
+                None => self.notice = Some("no edit of this generation to undo".to_string()),
+            },
+            Command::Redo => match life.redo() {
+                Some(cells) => outputs.edit(life.generation(), || Event::Set(cells))?,
+                None => self.notice = Some("no edit undone to redo".to_string()),
+            },
+            Command::Follow => self.screen.set_follow(!self.screen.following()),
+            Command::View => self.screen.set_view(match self.screen.view() {
+                View::Cells => View::Heatmap,
+                View::Heatmap => View::Cells,
+            }),
+            Command::Zoom { out } => {
+                let zoom = self.screen.zoom();
+                self.screen
+                    .set_zoom(life, if out { zoom * 2 } else { zoom / 2 });
+            }
+            Command::Save => {
+                self.notice = Some(match output(config) {
+                    Some((path, format)) => match life.save(path, format) {
+                        Ok(()) => format!("saved to {}", path.display()),
+                        Err(err) => err,
+                    },
+                    None => "nowhere to save to without --output".to_string(),
+                });
+            }
+            Command::SaveState => {
+                self.notice = Some(match &config.save_state {
+                    Some(path) => match state::save(life, path) {
+                        Ok(()) => format!("saved the state to {}", path.display()),
+                        Err(err) => err,
+                    },
+                    None => "nowhere to save the state to without --save-state".to_string(),
+                });
+            }
+            Command::SaveSvg => {
+                self.notice = Some(match &outputs.svg {
+                    Some(svg) => match svg.write(life) {
+                        Ok(()) => format!("saved an SVG to {}", svg.path.display()),
+                        Err(err) => err,
+                    },
+                    None => "nowhere to save an SVG to without --svg".to_string(),
+                });
+            }
+            Command::Census => self.notice = Some(census(life).to_string()),
+            // Nothing is computed while the keys are shown
+            Command::Help => self.help()?,
+            Command::Snapshot => {
+                self.notice = Some(match outputs.snapshots.take(life) {
+                    Ok(path) => format!("saved a snapshot to {}", path.display()),
+                    Err(err) => err,
+                });
+            }
+            Command::Dump => {
+                self.notice = Some(match dump(life, config.save_dir.as_deref()) {
+                    Ok(path) => format!("saved to {}", path.display()),
+                    Err(err) => err,
+                });
+            }
+            Command::Click { column, row } => {
+                let Some((x, y)) = self.screen.cell_at(life, column, row) else {
+                    return Ok(Flow::Wait);
+                };
+                self.cursor = (x, y);
+                match self.stamp.take() {
+                    Some((placed, resume)) => {
+                        place(life, outputs, &placed, self.screen.on_field((x, y)))?;
+                        self.mode = resume;
+                    }
+                    // Clicking moves the corner of the rectangle
+                    None if self.mode == Mode::Selecting => {}
+                    None => {
+                        let (x, y) = self.screen.on_field((x, y));
+                        life.toggle(x, y);
+                        outputs.edit(life.generation(), || Event::Toggle(x, y))?;
+                    }
+                }
+            }
+            Command::Hover { column, row } => match self.screen.cell_at(life, column, row) {
+                Some(cell) if cell != self.cursor => self.cursor = cell,
+                _ => return Ok(Flow::Wait),
+            },
+            Command::Resize { columns, rows } => self.resize(life, outputs, columns, rows)?,
+            Command::ResizeField { grow } => self.resize_field(life, outputs, grow)?,
+            Command::Quit => return Ok(Flow::Quit),
+        }
+        Ok(Flow::Draw(0))
+    }
+
+    /// The rectangle between the corner a selection started at and the cursor, if selecting
+    fn selected(&self) -> Option<Rect> {
+        let corner = self.selection.map(|(corner, _)| corner)?;
+        Some(Rect::new(corner, self.screen.on_field(self.cursor)))
+    }
+
+    /// Goes `by` of the --rules on from the ones the field runs under
+    fn switch_rules(
+        &mut self,
+        life: &mut Life,
+        outputs: &mut Outputs,
+        by: isize,
+    ) -> Result<(), String> {
+        let Some(list) = &self.config.rules else {
+            self.notice = Some("no --rules to switch between".to_string());
+            return Ok(());
+        };
+        let rules = list.get(self.switched + by).clone();
+        let rules = match self.config.neighborhood {
+            Some(neighborhood) => rules.with_neighborhood(neighborhood),
+            None => Ok(rules),
+        };
+        match rules.and_then(|rules| life.set_rules(rules)) {
+            Ok(()) => {
+                self.switched += by;
+                outputs.edit(life.generation(), || Event::Rules(life.rules.clone()))?;
+            }
+            Err(err) => self.notice = Some(err),
+        }
+        Ok(())
+    }
+
+    /// Copies the live cells of the selection, to the --clipboard too if there is one
+    fn copy(&mut self, life: &Life) {
+        let rect = self.selected().expect("copying needs a selection");
+        let copied = life.copy(rect);
+        let cells = if copied.cells.len() == 1 {
+            "cell"
+        } else {
+            "cells"
+        };
+        let mut copy = format!(
+            "copied {}x{} with {} live {cells}",
+            rect.width(),
+            rect.height(),
+            copied.cells.len()
+        );
+        if let Some(path) = &self.config.clipboard {
+            let format = Format::from_extension(path).unwrap_or(Format::Rle);
+            copy = match copied.save(path, format) {
+                Ok(()) => format!("{copy} to {}", path.display()),
+                Err(err) => err,
+            };
+        }
+        self.notice = Some(copy);
+        self.clipboard = Some(copied);
+        self.mode = self
+            .selection
+            .take()
+            .map_or(self.mode, |(_, resume)| resume);
+    }
+
+    /// Shows the keys over the frame until any of them is pressed, computing nothing in the
+    /// meantime
+    fn help(&mut self) -> Result<(), String> {
+        let keys = controls::help(self.mode);
+        loop {
+            let overlay = self
+                .screen
+                .overlay("keys, any of them to go on", &keys)
+                .map_err(terminal_error)?;
+            self.stdout
+                .write_all(overlay)
+                .and_then(|()| self.stdout.flush())
+                .map_err(terminal_error)?;
+            let Some((columns, rows)) = controls::any_key().map_err(terminal_error)? else {
+                break;
+            };
+            // The box goes in the middle of the terminal again, which likely lost the frame
+            // under it
+            self.screen.resize(columns, rows);
+            crossterm::execute!(self.stdout, terminal::Clear(terminal::ClearType::All))
+                .map_err(terminal_error)?;
+        }
+        self.pace.next_frame = Instant::now() + self.pace.frame_time();
+        Ok(())
+    }
+
+    /// Fits the screen to the terminal resized to `columns` and `rows`, along with the field
+    /// with --resize grow
+    fn resize(
+        &mut self,
+        life: &mut Life,
+        outputs: &mut Outputs,
+        columns: u16,
+        rows: u16,
+    ) -> Result<(), String> {
+        let config = self.config;
+        self.screen.resize(columns, rows);
+        if let Some(sparkline) = &mut self.sparkline {
+            sparkline.resize(sparkline_width(columns));
+        }
+        if config.resize == Resize::Grow {
+            let neighborhood = life.rules.neighborhood();
+            let (width, height) = render::fit(
+                (columns, rows),
+                neighborhood,
+                config.renderer,
+                self.glyphs,
+                config.status_lines(),
+            );
+            let resized = life.resize(width, height, config.resize_anchor);
+            if resized.is_ok() {
+                outputs.edit(life.generation(), || resize_event(life, config))?;
+            }
+            match resized {
+                Ok(0) => {}
+                Ok(cropped) => self.notice = Some(cropped_notice(cropped)),
+                Err(err) => self.notice = Some(err),
+            }
+        }
+        self.refit(life);
+        Ok(())
+    }
+
+    /// Grows or shrinks the field by a tenth around the --resize-anchor
+    fn resize_field(
+        &mut self,
+        life: &mut Life,
+        outputs: &mut Outputs,
+        grow: bool,
+    ) -> Result<(), String> {
+        let config = self.config;
+        let step = |size: usize| (size / 10).max(1);
+        let (width, height) = if grow {
+            (
+                life.width.saturating_add(step(life.width)),
+                life.height.saturating_add(step(life.height)),
+            )
+        } else {
+            (
+                life.width.saturating_sub(step(life.width)),
+                life.height.saturating_sub(step(life.height)),
+            )
+        };
+        let resized = life.resize(width, height, config.resize_anchor);
+        if resized.is_ok() {
+            outputs.edit(life.generation(), || resize_event(life, config))?;
+        }
+        self.notice = Some(match resized {
+            Ok(0) => format!("resized the field to {width}x{height}"),
+            Ok(cropped) => cropped_notice(cropped),
+            Err(err) => err,
+        });
+        self.refit(life);
+        Ok(())
+    }
+
+    /// Fits the viewport to the field again, keeping the cursor on the screen
+    fn refit(&mut self, life: &Life) {
+        self.screen.fit_viewport(life);
+        let (width, height) = self.screen.visible();
+        self.cursor = (
+            self.cursor.0.min(width.saturating_sub(1)),
+            self.cursor.1.min(height.saturating_sub(1)),
+        );
+    }
+
+    /// Computes `steps` generations, not going past the limit, where the simulation stops
+    fn advance(
+        &mut self,
+        life: &mut Life,
+        outputs: &mut Outputs,
+        steps: u64,
+    ) -> Result<(), String> {
+        let mut steps = steps;
+        if let Some(limit) = self.config.generations {
+            steps = steps.min(limit.saturating_sub(life.generation()));
+        }
+        for _ in 0..steps {
+            if replay(life, self.config, outputs, Some(&mut self.pace))? {
+                break;
+            }
+            life.step();
+            outputs.snapshots.record(life)?;
+            if let Some(sparkline) = &mut self.sparkline {
+                sparkline.push(life.population());
+            }
+            if let Some(stats) = &mut outputs.stats {
+                stats.record(life)?;
+            }
+            if let Some(tape) = &mut outputs.tape {
+                tape.record(life)?;
+            }
+            if let Some(checkpoints) = &mut outputs.checkpoints {
+                if let Some(err) = checkpoints.record(life)? {
+                    self.notice = Some(err);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Places `stamp` centered on a cell, killing the cells under it first if it is pasted over
+/// them
+fn place(
+    life: &mut Life,
+    outputs: &mut Outputs,
+    stamp: &Stamp,
+    at: (i64, i64),
+) -> Result<(), String> {
+    let cells: Vec<_> = stamp.cells(at).collect();
+    match stamp.cleared(at) {
+        Some(rect) => {
+            life.paste(rect, cells.iter().copied());
+            outputs.edit(life.generation(), || Event::Clear(rect))?;
+        }
+        None => life.stamp(cells.iter().copied()),
+    }
+    outputs.edit(life.generation(), || Event::Stamp(cells))
+}
+
+/// Dead cells picked at random with the --poke-density, those within the --poke-radius of
+/// `center` if given and all over the part of the field shown otherwise
+fn poke_cells(
+    life: &Life,
+    config: &Config,
+    center: Option<(i64, i64)>,
+    rng: &mut impl Rng,
+) -> Vec<(i64, i64)> {
+    let radius = config.poke_radius as i64;
+    let area = match center {
+        Some((x, y)) => Rect::new((x - radius, y - radius), (x + radius, y + radius)),
+        None => Rect::new((0, 0), (life.width as i64 - 1, life.height as i64 - 1)),
+    };
+    area.cells()
+        .filter(|&(x, y)| {
+            center.is_none_or(|(cx, cy)| (x - cx).pow(2) + (y - cy).pow(2) <= radius.pow(2))
+        })
+        .filter(|&(x, y)| {
+            life.resolve(x, y)
+                .is_some_and(|(x, y)| life.engine.state(x, y) == DEAD)
+        })
+        .filter(|_| rng.gen_bool(config.poke_density))
+        .collect()
+}
+
+/// The field of `life` resized to the size it is now
+fn resize_event(life: &Life, config: &Config) -> Event {
+    Event::Resize {
+        width: life.width,
+        height: life.height,
+        anchor: config.resize_anchor,
+    }
+}
+
+/// Saves the live cells of the generation `life` is at as RLE, cropped to them, or under
+/// Wireworld the whole circuit, to a file in
+/// `dir` or the working directory named after the time and the generation, giving back where
+/// it went. A file saved in the same second gets a number after its name instead of being
+/// written over.
+fn dump(life: &Life, dir: Option<&Path>) -> Result<PathBuf, String> {
+    let dir = dir.unwrap_or(Path::new(""));
+    if !dir.as_os_str().is_empty() {
+        fs::create_dir_all(dir)
+            .map_err(|err| format!("failed to create {}: {err}", dir.display()))?;
+    }
+    let rle = match life.circuit() {
+        Some(circuit) => circuit,
+        None => Format::Rle.write(&life.to_pattern()?.trimmed()),
+    };
+    let time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_secs());
+    let name = format!("gol-{time}-gen{}", life.generation());
+    for copy in 1.. {
+        let path = match copy {
+            1 => dir.join(format!("{name}.rle")),
+            _ => dir.join(format!("{name}-{copy}.rle")),
+        };
+        match fs::File::create_new(&path) {
+            Ok(mut file) => {
+                return file
+                    .write_all(rle.as_bytes())
+                    .map(|()| path.clone())
+                    .map_err(|err| format!("failed to write {}: {err}", path.display()))
+            }
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {}
+            Err(err) => return Err(format!("failed to create {}: {err}", path.display())),
+        }
+    }
+    unreachable!("there is always another number to try")
+}
+
+/// Warns of the live cells that resizing the field cropped away
+fn cropped_notice(cropped: u64) -> String {
+    let cells = if cropped == 1 { "cell" } else { "cells" };
+    format!("resizing cropped away {cropped} live {cells}")
+}
+
+/// Fades the cells of a soup that settled out over `pause`, with `summary` below them. Gives
+/// back whether quitting was asked for in the meantime, which doesn't wait for the rest.
+fn fade(
+    life: &mut Life,
+    screen: &mut Screen,
+    out: &mut impl Write,
+    pause: Duration,
+    summary: &str,
+) -> io::Result<bool> {
+    let end = Instant::now() + pause;
+    let mut rng = rand::thread_rng();
+    loop {
+        let frame = screen.draw(life, summary, None, &[])?;
+        out.write_all(frame)?;
+        out.flush()?;
+        let now = Instant::now();
+        if now >= end {
+            return Ok(false);
+        }
+        let next = (now + FADE_FRAME).min(end);
+        match controls::next_command(Mode::Running, Some(next))? {
+            Some(Command::Quit) => return Ok(true),
+            Some(Command::Resize { columns, rows }) => screen.resize(columns, rows),
+            _ => {}
+        }
+        // Each live cell has an even chance to go in each of the frames left
+        let frames = (end.saturating_duration_since(Instant::now()).as_secs_f64()
+            / FADE_FRAME.as_secs_f64())
+        .ceil()
+        .max(1.0);
+        let cells = life.engine.live_cells();
+        life.erase(cells.into_iter().filter(|_| rng.gen_bool(1.0 / frames)));
+    }
+}
+
+/// The colors to draw the cells with. Giving colors of cells, or a variant or automaton drawn
+/// in colors, without a theme implies the white one.
+fn palette(life: &Life, config: &Config) -> Option<Palette> {
+    let custom = config.alive_color.is_some()
+        || config.dead_color.is_some()
+        || life.immigration.is_some()
+        || life.rules.first() == Rule::Wireworld
+        || matches!(life.rules.first(), Rule::Ant(_))
+        || config.style == CellStyle::Solid;
+    let theme = config
+        .color
+        .unwrap_or(if custom { Theme::White } else { Theme::Never });
+    Palette::new(theme, config.alive_color, config.dead_color)
+}
+
+/// How long a notice stays below the field while running
+const NOTICE_TIME: Duration = Duration::from_secs(2);
+
+/// How often the cells of a soup of --loop are drawn fading out
+const FADE_FRAME: Duration = Duration::from_millis(50);
@@ -0,0 +1,77 @@
+//! Edits of the cells made by hand, kept to be undone and redone until the generation changes
+
+use std::collections::VecDeque;
+
+use crate::engine::Engine;
+
+/// A cell that an edit changed: where it is, the state it was in and the one it was put in
+pub type Change = (i64, i64, u8, u8);
+
+/// The last edits made to the generation shown, each the cells it changed
+pub struct Edits {
+    /// Oldest edit first
+    done: VecDeque<Vec<Change>>,
+    /// The edits undone, the last of them undone last
+    undone: Vec<Vec<Change>>,
+    /// Edits kept at most, the oldest ones being forgotten past them
+    depth: usize,
+}
+
+impl Edits {
+    pub fn new(depth: usize) -> Self {
+        Self {
+            done: VecDeque::new(),
+            undone: Vec::new(),
+            depth,
+        }
+    }
+
+    /// Keeps an edit to be undone, after which those undone before it can't be redone
+    pub fn push(&mut self, changes: Vec<Change>) {
+        if changes.is_empty() || self.depth == 0 {
+            return;
+        }
+        self.undone.clear();
+        if self.done.len() == self.depth {
+            self.done.pop_front();
+        }
+        self.done.push_back(changes);
+    }
+
+    /// Forgets all the edits, since they can't be undone on any other generation than the
+    /// one they were made to
+    pub fn barrier(&mut self) {
+        self.done.clear();
+        self.undone.clear();
+    }
+
+    /// Puts the cells of the last edit back in the states they were in, giving back the
+    /// cells as they were put in the order they were put, if there was an edit to undo
+    pub fn undo(&mut self, engine: &mut dyn Engine) -> Option<Vec<(i64, i64, u8)>> {
+        let changes = self.done.pop_back()?;
+        let cells = apply(
+            engine,
+            changes.iter().rev().map(|&(x, y, old, _)| (x, y, old)),
+        );
+        self.undone.push(changes);
+        Some(cells)
+    }
+
+    /// Makes the edit undone last again, giving back the cells as they were put in the order
+    /// they were put, if there was an edit to redo
+    pub fn redo(&mut self, engine: &mut dyn Engine) -> Option<Vec<(i64, i64, u8)>> {
+        let changes = self.undone.pop()?;
+        let cells = apply(engine, changes.iter().map(|&(x, y, _, new)| (x, y, new)));
+        self.done.push_back(changes);
+        Some(cells)
+    }
+}
+
+fn apply(
+    engine: &mut dyn Engine,
+    cells: impl Iterator<Item = (i64, i64, u8)>,
+) -> Vec<(i64, i64, u8)> {
+    cells
+        .inspect(|&(x, y, state)| engine.set(x, y, state))
+        .collect()
+}
@@ -0,0 +1,192 @@
+//! Variants of Life that keep more about the live cells than the rules do
+
+use std::{cmp::Ordering, collections::HashMap, mem};
+
+use crate::{cycle::hash_cell, engine::Engine, rule::Neighborhood};
+use clap::ValueEnum;
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Variant {
+    /// Live cells come in two colors, and the cells born take the color most of their parents
+    /// have
+    Immigration,
+}
+
+/// How the live cells are colored to start with
+#[derive(Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum Split {
+    /// The first color on the left half of the field, the second on the right one
+    #[default]
+    Halves,
+    /// Either color at random, the same for the same seed
+    Random,
+}
+
+/// One of the two colors of Immigration
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    First,
+    Second,
+}
+
+/// Colors of the live cells of Immigration. Kept apart from the engines, which compute
+/// which cells are alive the same as in the rule without colors.
+pub struct Immigration {
+    sides: HashMap<(i64, i64), Side>,
+    /// The colors of the previous generation, kept to reuse the allocation
+    previous: HashMap<(i64, i64), Side>,
+    split: Split,
+    /// Column the right half of the field starts at
+    middle: i64,
+    seed: u64,
+    /// The neighbors a cell is born from
+    neighborhood: Neighborhood,
+}
+
+impl Immigration {
+    /// Colors the live cells by `split`, across the middle of a `width` cells wide field
+    pub fn new(
+        split: Split,
+        seed: u64,
+        width: usize,
+        neighborhood: Neighborhood,
+        engine: &dyn Engine,
+    ) -> Self {
+        let mut immigration = Self {
+            sides: HashMap::new(),
+            previous: HashMap::new(),
+            split,
+            middle: width as i64 / 2,
+            seed,
+            neighborhood,
+        };
+        immigration.reset(engine);
+        immigration
+    }
+
+    /// Takes the colors of the live cells as they were, for picking up a simulation that was
+    /// left off
+    pub(crate) fn restore(
+        split: Split,
+        seed: u64,
+        middle: i64,
+        neighborhood: Neighborhood,
+        sides: impl IntoIterator<Item = ((i64, i64), Side)>,
+    ) -> Self {
+        Self {
+            sides: sides.into_iter().collect(),
+            previous: HashMap::new(),
+            split,
+            middle,
+            seed,
+            neighborhood,
+        }
+    }
+
+    /// The split, the seed of a random one and the middle of the field, which the colors of
+    /// the cells brought to life without parents go by
+    pub(crate) fn split(&self) -> (Split, u64, i64) {
+        (self.split, self.seed, self.middle)
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = ((i64, i64), Side)> + '_ {
+        self.sides.iter().map(|(&cell, &side)| (cell, side))
+    }
+
+    /// Colors all the live cells by the split again
+    pub fn reset(&mut self, engine: &dyn Engine) {
+        self.sides.clear();
+        for (x, y) in engine.live_cells() {
+            let side = self.split_side(x, y);
+            self.sides.insert((x, y), side);
+        }
+    }
+
+    /// Keeps the colors of the cells that survived into the generation the engine is at, and
+    /// gives the ones born in it the color most of their live neighbors had. Ties, which
+    /// Life itself never has, go by the split.
+    pub fn update(
+        &mut self,
+        engine: &dyn Engine,
+        resolve: impl Fn(i64, i64) -> Option<(i64, i64)>,
+    ) {
+        mem::swap(&mut self.sides, &mut self.previous);
+        self.sides.clear();
+        for (x, y) in engine.live_cells() {
+            let side = match self.previous.get(&(x, y)) {
+                Some(&side) => side,
+                None => {
+                    let mut first = 0;
+                    let mut second = 0;
+                    for &[i, j] in self.neighborhood.offsets(y.rem_euclid(2) == 1) {
+                        let parent =
+                            resolve(x + i, y + j).and_then(|cell| self.previous.get(&cell));
+                        match parent {
+                            Some(Side::First) => first += 1,
+                            Some(Side::Second) => second += 1,
+                            None => {}
+                        }
+                    }
+                    match first.cmp(&second) {
+                        Ordering::Greater => Side::First,
+                        Ordering::Less => Side::Second,
+                        Ordering::Equal => self.split_side(x, y),
+                    }
+                }
+            };
+            self.sides.insert((x, y), side);
+        }
+    }
+
+    /// Moves the colors along with the cells, which moved by `dx` and `dy`, and the middle of
+    /// the field with them
+    pub(crate) fn shift(&mut self, dx: i64, dy: i64) {
+        let sides = mem::take(&mut self.sides).into_iter();
+        self.sides = sides
+            .map(|((x, y), side)| ((x + dx, y + dy), side))
+            .collect();
+        self.middle += dx;
+    }
+
+    /// Keeps up with cells that changed by hand, coloring the ones brought to life by the
+    /// split
+    pub fn edited(&mut self, engine: &dyn Engine) {
+        mem::swap(&mut self.sides, &mut self.previous);
+        self.sides.clear();
+        for (x, y) in engine.live_cells() {
+            let side = match self.previous.get(&(x, y)) {
+                Some(&side) => side,
+                None => self.split_side(x, y),
+            };
+            self.sides.insert((x, y), side);
+        }
+    }
+
+    pub fn get(&self, x: i64, y: i64) -> Option<Side> {
+        self.sides.get(&(x, y)).copied()
+    }
+
+    /// Live cells of the first and the second color
+    pub fn populations(&self) -> (u64, u64) {
+        let first = self
+            .sides
+            .values()
+            .filter(|&&side| side == Side::First)
+            .count() as u64;
+        (first, self.sides.len() as u64 - first)
+    }
+
+    /// The color of a cell by the split, at random by its position for a random one so that
+    /// it is the same whatever order the cells come in
+    fn split_side(&self, x: i64, y: i64) -> Side {
+        let first = match self.split {
+            Split::Halves => x < self.middle,
+            Split::Random => hash_cell((hash_cell((x, y)) as i64, self.seed as i64)) & 1 == 0,
+        };
+        if first {
+            Side::First
+        } else {
+            Side::Second
+        }
+    }
+}